@@ -0,0 +1,136 @@
+//! Command line utility for verifying zkSync transactions.
+//!
+//! Reads a JSON-encoded zkSync transaction either from the provided file or from stdin
+//! and checks its correctness, including the zkSync signature.
+//!
+//! The process exit code reflects the verification result, so the tool can be used in scripts:
+//!
+//! - `0`: transaction is valid.
+//! - `1`: transaction is invalid.
+//! - `2`: input cannot be read or parsed as a transaction.
+
+use std::{io::Read, path::PathBuf, process, str::FromStr};
+
+use serde::Serialize;
+use structopt::StructOpt;
+
+use zksync_types::ZkSyncTx;
+
+const EXIT_VALID: i32 = 0;
+const EXIT_INVALID: i32 = 1;
+const EXIT_PARSE_ERROR: i32 = 2;
+
+/// Format of the verification report printed to stdout.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum OutputFormat {
+    Human,
+    Json,
+}
+
+impl FromStr for OutputFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "human" => Ok(Self::Human),
+            "json" => Ok(Self::Json),
+            _ => Err("Should be either: human or json".to_owned()),
+        }
+    }
+}
+
+#[derive(StructOpt)]
+enum Command {
+    /// Verify the JSON-encoded transaction
+    Verify {
+        /// Output format: `human` or `json`
+        #[structopt(long, default_value = "human")]
+        format: OutputFormat,
+        /// Path to the file with transaction, stdin is used if omitted
+        #[structopt(parse(from_os_str))]
+        input: Option<PathBuf>,
+    },
+}
+
+#[derive(StructOpt)]
+#[structopt(name = "zkSync transaction verifier", author = "Matter Labs")]
+struct Opt {
+    #[structopt(subcommand)]
+    command: Command,
+}
+
+/// Verification report in the JSON output format.
+#[derive(Debug, Serialize)]
+struct Report {
+    valid: bool,
+    error: Option<String>,
+}
+
+fn read_input(input: Option<PathBuf>) -> std::io::Result<String> {
+    match input {
+        Some(path) => std::fs::read_to_string(path),
+        None => {
+            let mut buf = String::new();
+            std::io::stdin().read_to_string(&mut buf)?;
+            Ok(buf)
+        }
+    }
+}
+
+/// Parses and verifies the transaction, returning the exit code and the error (if any).
+fn verify(input: std::io::Result<String>) -> (i32, Option<String>) {
+    let input = match input {
+        Ok(input) => input,
+        Err(err) => {
+            return (
+                EXIT_PARSE_ERROR,
+                Some(format!("Unable to read input: {}", err)),
+            )
+        }
+    };
+
+    let mut tx: ZkSyncTx = match serde_json::from_str(&input) {
+        Ok(tx) => tx,
+        Err(err) => {
+            return (
+                EXIT_PARSE_ERROR,
+                Some(format!("Unable to parse transaction: {}", err)),
+            )
+        }
+    };
+
+    if tx.check_correctness() {
+        (EXIT_VALID, None)
+    } else {
+        (EXIT_INVALID, Some("Transaction is incorrect".to_owned()))
+    }
+}
+
+fn main() {
+    let opt = Opt::from_args();
+
+    match opt.command {
+        Command::Verify { format, input } => {
+            let (exit_code, error) = verify(read_input(input));
+
+            match format {
+                OutputFormat::Human => match &error {
+                    None => println!("Transaction is valid"),
+                    Some(error) => println!("{}", error),
+                },
+                OutputFormat::Json => {
+                    let report = Report {
+                        valid: exit_code == EXIT_VALID,
+                        error,
+                    };
+                    println!(
+                        "{}",
+                        serde_json::to_string(&report).expect("report serialization")
+                    );
+                }
+            }
+
+            process::exit(exit_code);
+        }
+    }
+}
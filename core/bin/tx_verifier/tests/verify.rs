@@ -0,0 +1,80 @@
+use std::{
+    io::Write,
+    process::{Command, Stdio},
+};
+
+use zksync_crypto::{
+    priv_key_from_fs,
+    rand::{Rng, SeedableRng, XorShiftRng},
+};
+use zksync_types::{Address, Transfer, ZkSyncTx};
+
+/// Runs `tx_verifier verify --format json` with the provided stdin content,
+/// returning the exit code and the parsed report.
+fn run_verifier(input: &str) -> (i32, serde_json::Value) {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_tx_verifier"))
+        .args(&["verify", "--format", "json"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .expect("failed to start tx_verifier");
+
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(input.as_bytes())
+        .unwrap();
+    let output = child.wait_with_output().unwrap();
+
+    let report = serde_json::from_slice(&output.stdout).expect("report is not a JSON");
+    (output.status.code().unwrap(), report)
+}
+
+fn signed_transfer() -> Transfer {
+    let mut rng = XorShiftRng::from_seed([1, 2, 3, 4]);
+    let private_key = priv_key_from_fs(rng.gen());
+
+    Transfer::new_signed(
+        1,
+        Address::from(rng.gen::<[u8; 20]>()),
+        Address::from(rng.gen::<[u8; 20]>()),
+        0,
+        1_000u64.into(),
+        10u64.into(),
+        0,
+        &private_key,
+    )
+    .unwrap()
+}
+
+#[test]
+fn verify_valid_tx() {
+    let tx = ZkSyncTx::from(signed_transfer());
+
+    let (code, report) = run_verifier(&serde_json::to_string(&tx).unwrap());
+    assert_eq!(code, 0);
+    assert_eq!(report["valid"], true);
+    assert!(report["error"].is_null());
+}
+
+#[test]
+fn verify_invalid_tx() {
+    let mut transfer = signed_transfer();
+    // Changing the amount invalidates the signature.
+    transfer.amount = 2_000u64.into();
+    let tx = ZkSyncTx::from(transfer);
+
+    let (code, report) = run_verifier(&serde_json::to_string(&tx).unwrap());
+    assert_eq!(code, 1);
+    assert_eq!(report["valid"], false);
+    assert!(report["error"].is_string());
+}
+
+#[test]
+fn verify_malformed_input() {
+    let (code, report) = run_verifier("{ not a transaction");
+    assert_eq!(code, 2);
+    assert_eq!(report["valid"], false);
+    assert!(report["error"].is_string());
+}
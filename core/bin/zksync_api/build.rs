@@ -0,0 +1,76 @@
+//! Embeds the build information returned by the `api/v1/version` endpoint.
+
+use std::{
+    env, fs,
+    path::PathBuf,
+    process::Command,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+fn git_sha() -> Option<String> {
+    let output = Command::new("git")
+        .args(&["rev-parse", "HEAD"])
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    String::from_utf8(output.stdout)
+        .ok()
+        .map(|sha| sha.trim().to_owned())
+}
+
+/// Returns the git files which have to be watched for the `git_sha` to stay up to date:
+/// `HEAD` itself and the file storing the branch it refers to.
+fn git_files() -> Vec<PathBuf> {
+    let git_dir = Command::new("git")
+        .args(&["rev-parse", "--git-dir"])
+        .output()
+        .ok()
+        .filter(|output| output.status.success())
+        .and_then(|output| String::from_utf8(output.stdout).ok())
+        .map(|git_dir| PathBuf::from(git_dir.trim()));
+    let git_dir = match git_dir {
+        Some(git_dir) => git_dir,
+        None => return Vec::new(),
+    };
+
+    let head = git_dir.join("HEAD");
+    let mut files = vec![head.clone()];
+    // `HEAD` is either a commit hash or a reference like `ref: refs/heads/master`.
+    let head_ref = fs::read_to_string(&head).unwrap_or_default();
+    if let Some(head_ref) = head_ref.trim().strip_prefix("ref: ") {
+        files.push(git_dir.join(head_ref));
+        // The branch may be stored in the packed form only.
+        files.push(git_dir.join("packed-refs"));
+    }
+
+    // Watching a missing file makes the script rerun on every build.
+    files.retain(|file| file.exists());
+    files
+}
+
+fn main() {
+    // Builds performed outside of the git repository (e.g. docker images) may provide
+    // the commit hash explicitly.
+    let git_sha = env::var("ZKSYNC_GIT_SHA")
+        .ok()
+        .or_else(git_sha)
+        .unwrap_or_else(|| "unknown".to_owned());
+    let build_timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .expect("system time is before the UNIX epoch")
+        .as_secs();
+
+    // Emitting the `rerun-if-*` directives disables the default behavior of rerunning
+    // the script on any package change, so the package sources are watched explicitly.
+    println!("cargo:rerun-if-changed=build.rs");
+    println!("cargo:rerun-if-changed=Cargo.toml");
+    println!("cargo:rerun-if-changed=src");
+    println!("cargo:rerun-if-env-changed=ZKSYNC_GIT_SHA");
+    for file in git_files() {
+        println!("cargo:rerun-if-changed={}", file.display());
+    }
+    println!("cargo:rustc-env=ZKSYNC_GIT_SHA={}", git_sha);
+    println!("cargo:rustc-env=ZKSYNC_BUILD_TIMESTAMP={}", build_timestamp);
+}
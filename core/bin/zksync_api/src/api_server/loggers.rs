@@ -22,7 +22,12 @@ const HEADERS: [&str; 5] = [
 
 pub mod rest {
     use super::HEADERS;
+    use actix_web::{
+        dev::{Service, ServiceRequest, ServiceResponse},
+        Error,
+    };
     use itertools::Itertools;
+    use std::{future::Future, time::Instant};
 
     pub fn get_logger_format() -> String {
         let headers_formatted = HEADERS
@@ -38,6 +43,51 @@ pub mod rest {
             headers_formatted,
         )
     }
+
+    /// Middleware function to be used with `App::wrap_fn`.
+    ///
+    /// If `enabled`, emits a structured access log line with the request method, path,
+    /// response status, latency and client IP for every request at the `info` level.
+    pub fn access_log<S>(
+        enabled: bool,
+        req: ServiceRequest,
+        srv: &mut S,
+    ) -> impl Future<Output = Result<ServiceResponse, Error>>
+    where
+        S: Service<Request = ServiceRequest, Response = ServiceResponse, Error = Error>,
+    {
+        let request_info = if enabled {
+            let client_ip = req
+                .connection_info()
+                .realip_remote_addr()
+                .unwrap_or("-")
+                .to_owned();
+            Some((
+                req.method().to_string(),
+                req.path().to_owned(),
+                client_ip,
+                Instant::now(),
+            ))
+        } else {
+            None
+        };
+        let fut = srv.call(req);
+
+        async move {
+            let res = fut.await?;
+            if let Some((method, path, client_ip, started_at)) = request_info {
+                log::info!(
+                    "method=\"{}\" path=\"{}\" status=\"{}\" latency=\"{}ms\" client-ip=\"{}\"",
+                    method,
+                    path,
+                    res.status().as_u16(),
+                    started_at.elapsed().as_millis(),
+                    client_ip,
+                );
+            }
+            Ok(res)
+        }
+    }
 }
 
 pub mod http_rpc {
@@ -102,3 +152,74 @@ pub mod ws_rpc {
         None
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use actix_web::{test, web, App, HttpResponse};
+    use log::{Level, LevelFilter, Log, Metadata, Record};
+    use once_cell::sync::Lazy;
+    use std::sync::Mutex;
+
+    /// Logger storing all the emitted messages.
+    struct CapturingLogger;
+
+    static LOGGED: Lazy<Mutex<Vec<String>>> = Lazy::new(Default::default);
+    static LOGGER: CapturingLogger = CapturingLogger;
+
+    impl Log for CapturingLogger {
+        fn enabled(&self, metadata: &Metadata<'_>) -> bool {
+            metadata.level() <= Level::Info
+        }
+
+        fn log(&self, record: &Record<'_>) {
+            if self.enabled(record.metadata()) {
+                LOGGED.lock().unwrap().push(record.args().to_string());
+            }
+        }
+
+        fn flush(&self) {}
+    }
+
+    #[actix_rt::test]
+    async fn test_access_log() {
+        log::set_logger(&LOGGER).expect("logger is set only once");
+        log::set_max_level(LevelFilter::Info);
+
+        for &enabled in &[false, true] {
+            let mut app = test::init_service(
+                App::new()
+                    .wrap_fn(move |req, srv| super::rest::access_log(enabled, req, srv))
+                    .route(
+                        "/status",
+                        web::get().to(|| HttpResponse::NotFound().finish()),
+                    ),
+            )
+            .await;
+
+            let req = test::TestRequest::get()
+                .uri("/status?foo=bar")
+                .peer_addr("127.0.0.1:8080".parse().unwrap())
+                .to_request();
+            test::call_service(&mut app, req).await;
+        }
+
+        let logged = LOGGED.lock().unwrap();
+        let access_logs: Vec<_> = logged
+            .iter()
+            .filter(|line| line.starts_with("method="))
+            .collect();
+        // Only the request with the enabled access log is recorded.
+        assert_eq!(access_logs.len(), 1);
+        let line = access_logs[0];
+        assert!(
+            line.starts_with("method=\"GET\" path=\"/status\" status=\"404\" latency=\""),
+            "{}",
+            line
+        );
+        assert!(
+            line.ends_with("ms\" client-ip=\"127.0.0.1:8080\""),
+            "{}",
+            line
+        );
+    }
+}
@@ -19,7 +19,7 @@ use crate::signature_checker;
 mod admin_server;
 mod event_notify;
 mod loggers;
-mod rest;
+pub(crate) mod rest;
 pub mod rpc_server;
 mod rpc_subscriptions;
 mod tx_sender;
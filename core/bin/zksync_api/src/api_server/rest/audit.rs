@@ -0,0 +1,223 @@
+//! Middleware recording raw request bodies for the audit purposes.
+//!
+//! Audit is disabled by default. If the `API_AUDIT_LOG` environment variable is set,
+//! every request body is appended to the specified file along with the request timestamp
+//! and the response status, one JSON record per line.
+//!
+//! The body is copied while the handler reads it, so the payload is available to the
+//! extractors as usual, and the recorded bytes are exactly the ones the handler received.
+//! Bodies which are not valid UTF-8 (e.g. binary transactions) are recorded hex-encoded.
+//! Records are written on the blocking thread pool, so the actix workers never wait for
+//! the file system.
+
+// Built-in uses
+use std::{
+    cell::RefCell,
+    fs::{File, OpenOptions},
+    future::Future,
+    io::Write,
+    path::Path,
+    rc::Rc,
+    sync::{Arc, Mutex},
+};
+
+// External uses
+use actix_web::{
+    dev::{Payload, Service, ServiceRequest, ServiceResponse},
+    web::{self, BytesMut},
+    Error,
+};
+use chrono::{DateTime, Utc};
+use futures::{future::Either, StreamExt};
+use serde::Serialize;
+
+// Workspace uses
+use zksync_config::ConfigurationOptions;
+
+// Local uses
+
+/// Single entry of the audit log.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct AuditRecord {
+    timestamp: DateTime<Utc>,
+    method: String,
+    path: String,
+    /// Response status code.
+    status: u16,
+    /// Request body exactly as it was received, or its `0x`-prefixed hex encoding
+    /// if the body is not a valid UTF-8 string.
+    body: String,
+    /// Set to `hex` for the hex-encoded bodies.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    body_encoding: Option<&'static str>,
+}
+
+impl AuditRecord {
+    fn encode_body(body: &[u8]) -> (String, Option<&'static str>) {
+        match std::str::from_utf8(body) {
+            Ok(body) => (body.to_owned(), None),
+            Err(_) => (format!("0x{}", hex::encode(body)), Some("hex")),
+        }
+    }
+}
+
+/// Append-only file with the audit records.
+#[derive(Debug, Clone)]
+pub struct AuditLog {
+    file: Arc<Mutex<File>>,
+}
+
+impl AuditLog {
+    /// Opens the audit log file, creating it if it doesn't exist.
+    pub fn open(path: impl AsRef<Path>) -> std::io::Result<Self> {
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        Ok(Self {
+            file: Arc::new(Mutex::new(file)),
+        })
+    }
+
+    /// Opens the audit log if it is enabled in the configuration.
+    pub fn from_config(env_options: &ConfigurationOptions) -> Option<Self> {
+        env_options.api_audit_log.as_ref().map(|path| {
+            Self::open(path).unwrap_or_else(|err| {
+                panic!("Unable to open API audit log {}: {}", path.display(), err)
+            })
+        })
+    }
+
+    async fn record(&self, record: AuditRecord) {
+        let file = self.file.clone();
+        let result = web::block(move || {
+            let mut line = serde_json::to_vec(&record).map_err(std::io::Error::from)?;
+            line.push(b'\n');
+            let mut file = file.lock().expect("audit log lock");
+            file.write_all(&line)
+        })
+        .await;
+
+        // Audit failures must not affect the requests processing.
+        if let Err(err) = result {
+            log::warn!("Unable to write API audit record: {}", err);
+        }
+    }
+}
+
+/// Replaces the request payload with the one that copies every chunk read
+/// by the extractors into the returned buffer.
+fn tee_payload(req: &mut ServiceRequest) -> Rc<RefCell<BytesMut>> {
+    let body = Rc::new(RefCell::new(BytesMut::new()));
+
+    let buffer = body.clone();
+    let payload = req.take_payload().inspect(move |chunk| {
+        if let Ok(chunk) = chunk {
+            buffer.borrow_mut().extend_from_slice(chunk);
+        }
+    });
+    req.set_payload(Payload::Stream(Box::pin(payload)));
+
+    body
+}
+
+/// Middleware function to be used with `App::wrap_fn`.
+///
+/// Requests are passed through untouched if audit is disabled.
+pub fn middleware<S>(
+    audit_log: Option<AuditLog>,
+    mut req: ServiceRequest,
+    srv: &mut S,
+) -> impl Future<Output = Result<ServiceResponse, Error>>
+where
+    S: Service<Request = ServiceRequest, Response = ServiceResponse, Error = Error>,
+{
+    let audit_log = match audit_log {
+        Some(audit_log) => audit_log,
+        None => return Either::Left(srv.call(req)),
+    };
+
+    let timestamp = Utc::now();
+    let method = req.method().to_string();
+    let path = req.path().to_owned();
+    let body = tee_payload(&mut req);
+    let fut = srv.call(req);
+
+    Either::Right(async move {
+        let res = fut.await?;
+
+        let (body, body_encoding) = AuditRecord::encode_body(&body.borrow());
+        audit_log
+            .record(AuditRecord {
+                timestamp,
+                method,
+                path,
+                status: res.status().as_u16(),
+                body,
+                body_encoding,
+            })
+            .await;
+        Ok(res)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_web::{test, web, web::Bytes, App, HttpResponse};
+
+    use super::*;
+
+    async fn echo(body: Bytes) -> HttpResponse {
+        HttpResponse::Ok().body(body)
+    }
+
+    #[actix_rt::test]
+    async fn test_audit_log() {
+        let path = std::env::temp_dir().join(format!(
+            "zksync_api_audit_{}_{}.log",
+            std::process::id(),
+            Utc::now().timestamp_nanos()
+        ));
+        let audit_log = AuditLog::open(&path).unwrap();
+
+        let mut app = test::init_service(
+            App::new()
+                .wrap_fn(move |req, srv| middleware(Some(audit_log.clone()), req, srv))
+                .route("/echo", web::post().to(echo)),
+        )
+        .await;
+
+        let body = r#"{"foo":  "bar"}"#;
+        let req = test::TestRequest::post()
+            .uri("/echo")
+            .set_payload(body)
+            .to_request();
+        // Body is still available to the handler after capture.
+        let response = test::read_response(&mut app, req).await;
+        assert_eq!(response, body.as_bytes());
+
+        let binary_body = vec![0x00, 0xff, 0xfe, 0x41];
+        let req = test::TestRequest::post()
+            .uri("/echo")
+            .set_payload(binary_body.clone())
+            .to_request();
+        let response = test::read_response(&mut app, req).await;
+        assert_eq!(response, binary_body);
+
+        let records = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        let records: Vec<serde_json::Value> = records
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[0]["method"], "POST");
+        assert_eq!(records[0]["path"], "/echo");
+        assert_eq!(records[0]["status"], 200);
+        assert_eq!(records[0]["body"], body);
+        assert!(records[0].get("bodyEncoding").is_none());
+
+        // Binary bodies are recorded exactly, in the hex encoding.
+        assert_eq!(records[1]["body"], "0x00fffe41");
+        assert_eq!(records[1]["bodyEncoding"], "hex");
+    }
+}
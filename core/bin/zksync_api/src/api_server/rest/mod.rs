@@ -9,11 +9,16 @@ use zksync_types::H160;
 use zksync_utils::panic_notify::ThreadPanicNotify;
 
 use self::v01::api_decl::ApiV01;
-use crate::{fee_ticker::TickerRequest, signature_checker::VerifyTxSignatureRequest};
+use crate::{
+    api_server::loggers, fee_ticker::TickerRequest, signature_checker::VerifyTxSignatureRequest,
+    utils::replay_cache::ReplayCache,
+};
 
 use super::tx_sender::TxSender;
 
+mod audit;
 mod helpers;
+mod pretty;
 mod v01;
 pub mod v1;
 
@@ -23,10 +28,19 @@ async fn start_server(
     sign_verifier: mpsc::Sender<VerifyTxSignatureRequest>,
     bind_to: SocketAddr,
 ) {
-    let logger_format = crate::api_server::loggers::rest::get_logger_format();
+    let logger_format = loggers::rest::get_logger_format();
+    let audit_log = audit::AuditLog::from_config(&api_v01.config_options);
+    let access_log = api_v01.config_options.api_access_log;
+    // Replay cache is shared between the workers, so duplicates are detected regardless
+    // of the worker which handles the request.
+    let replay_cache = ReplayCache::new(
+        api_v01.config_options.api_requests_caches_size,
+        api_v01.config_options.api_replay_window,
+    );
 
     HttpServer::new(move || {
         let api_v01 = api_v01.clone();
+        let audit_log = audit_log.clone();
 
         let api_v1_scope = {
             let env_options = api_v01.config_options.clone();
@@ -37,11 +51,14 @@ async fn start_server(
                 fee_ticker.clone(),
                 &env_options,
             );
-            v1::api_scope(tx_sender, env_options)
+            v1::api_scope(tx_sender, env_options, replay_cache.clone())
         };
 
         App::new()
+            .wrap_fn(move |req, srv| audit::middleware(audit_log.clone(), req, srv))
+            .wrap_fn(pretty::middleware)
             .wrap(middleware::Logger::new(&logger_format))
+            .wrap_fn(move |req, srv| loggers::rest::access_log(access_log, req, srv))
             .wrap(Cors::new().send_wildcard().max_age(3600).finish())
             .service(api_v01.into_scope())
             .service(api_v1_scope)
@@ -0,0 +1,108 @@
+//! Middleware formatting JSON responses for debugging purposes.
+//!
+//! Responses are compact by default, but if the request contains the `pretty=true`
+//! query parameter, JSON response body is re-formatted with indentation.
+
+// Built-in uses
+use std::future::Future;
+
+// External uses
+use actix_web::{
+    dev::{Body, ResponseBody, Service, ServiceRequest, ServiceResponse},
+    http::header::CONTENT_TYPE,
+    Error,
+};
+use futures::StreamExt;
+
+// Workspace uses
+
+// Local uses
+
+/// Query parameter which enables pretty-printed responses.
+const PRETTY_QUERY: &str = "pretty=true";
+
+fn is_pretty_requested(req: &ServiceRequest) -> bool {
+    req.query_string()
+        .split('&')
+        .any(|param| param == PRETTY_QUERY)
+}
+
+fn is_json(res: &ServiceResponse) -> bool {
+    res.headers()
+        .get(CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .map(|value| value.starts_with("application/json"))
+        .unwrap_or(false)
+}
+
+async fn prettify(mut res: ServiceResponse) -> Result<ServiceResponse, Error> {
+    let mut body = res.take_body();
+    let mut bytes = Vec::new();
+    while let Some(chunk) = body.next().await {
+        bytes.extend_from_slice(&chunk?);
+    }
+
+    // Body is left intact if it cannot be parsed as JSON.
+    let bytes = serde_json::from_slice::<serde_json::Value>(&bytes)
+        .ok()
+        .and_then(|value| serde_json::to_vec_pretty(&value).ok())
+        .unwrap_or(bytes);
+
+    Ok(res.map_body(|_, _| ResponseBody::Body(Body::from(bytes))))
+}
+
+/// Middleware function to be used with `App::wrap_fn`.
+pub fn middleware<S>(
+    req: ServiceRequest,
+    srv: &mut S,
+) -> impl Future<Output = Result<ServiceResponse, Error>>
+where
+    S: Service<Request = ServiceRequest, Response = ServiceResponse, Error = Error>,
+{
+    let pretty = is_pretty_requested(&req);
+    let fut = srv.call(req);
+
+    async move {
+        let res = fut.await?;
+        if pretty && is_json(&res) {
+            prettify(res).await
+        } else {
+            Ok(res)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_web::{test, web, App, HttpResponse};
+
+    use super::*;
+
+    async fn handler() -> HttpResponse {
+        HttpResponse::Ok().json(serde_json::json!({ "foo": [1, 2, 3] }))
+    }
+
+    #[actix_rt::test]
+    async fn test_pretty_json() {
+        let mut app = test::init_service(
+            App::new()
+                .wrap_fn(middleware)
+                .route("/", web::get().to(handler)),
+        )
+        .await;
+
+        let req = test::TestRequest::get().uri("/").to_request();
+        let compact = test::read_response(&mut app, req).await;
+        assert!(!compact.contains(&b'\n'));
+
+        let req = test::TestRequest::get().uri("/?pretty=true").to_request();
+        let pretty = test::read_response(&mut app, req).await;
+        assert!(pretty.contains(&b'\n'));
+
+        // Formatting doesn't change the content.
+        assert_eq!(
+            serde_json::from_slice::<serde_json::Value>(&compact).unwrap(),
+            serde_json::from_slice::<serde_json::Value>(&pretty).unwrap()
+        );
+    }
+}
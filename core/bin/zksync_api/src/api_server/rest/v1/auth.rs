@@ -0,0 +1,70 @@
+//! Shared secret authentication for the protected API endpoints.
+//!
+//! Caller is expected to provide the hex-encoded HMAC-SHA256 of the raw request body
+//! keyed by the shared secret in the `X-Signature` header.
+
+// Built-in uses
+
+// External uses
+use actix_web::HttpRequest;
+use hmac::{Hmac, Mac, NewMac};
+use sha2::Sha256;
+
+// Workspace uses
+use zksync_config::ConfigurationOptions;
+
+// Local uses
+use super::Error;
+
+/// Name of the header containing the request body signature.
+pub const SIGNATURE_HEADER: &str = "X-Signature";
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// Verifies that the request body was signed with the shared secret.
+#[derive(Debug, Clone)]
+pub struct RequestAuthenticator {
+    secret: Vec<u8>,
+}
+
+impl RequestAuthenticator {
+    /// Creates a new authenticator with the specified shared secret.
+    pub fn new(secret: impl AsRef<[u8]>) -> Self {
+        Self {
+            secret: secret.as_ref().to_vec(),
+        }
+    }
+
+    /// Creates an authenticator if the shared secret is set in the configuration.
+    pub fn from_config(env_options: &ConfigurationOptions) -> Option<Self> {
+        env_options
+            .api_requests_signing_secret
+            .as_ref()
+            .map(Self::new)
+    }
+
+    fn mac(&self, body: &[u8]) -> HmacSha256 {
+        let mut mac = HmacSha256::new_varkey(&self.secret).expect("HMAC accepts keys of any size");
+        mac.update(body);
+        mac
+    }
+
+    /// Computes the hex-encoded signature of the request body.
+    pub fn sign(&self, body: &[u8]) -> String {
+        hex::encode(self.mac(body).finalize().into_bytes())
+    }
+
+    /// Checks the signature provided in the `X-Signature` header of the request.
+    pub fn authenticate(&self, req: &HttpRequest, body: &[u8]) -> Result<(), Error> {
+        let signature = req
+            .headers()
+            .get(SIGNATURE_HEADER)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| hex::decode(value).ok())
+            .ok_or_else(|| Error::unauthorized("Missing or malformed request signature"))?;
+
+        self.mac(body)
+            .verify(&signature)
+            .map_err(|_| Error::unauthorized("Incorrect request signature"))
+    }
+}
@@ -51,7 +51,7 @@ impl Client {
         }
     }
 
-    fn endpoint(&self, method: &str) -> String {
+    pub(crate) fn endpoint(&self, method: &str) -> String {
         [&self.url, "/api/v1/", method].concat()
     }
 
@@ -106,6 +106,17 @@ impl ClientRequestBuilder {
         }
     }
 
+    /// Send a raw binary body with the `application/octet-stream` content type.
+    pub fn bytes_body(self, body: Vec<u8>) -> Self {
+        Self {
+            inner: self
+                .inner
+                .header(reqwest::header::CONTENT_TYPE, "application/octet-stream")
+                .body(body),
+            url: self.url,
+        }
+    }
+
     /// Constructs the Request and sends it to the target URL, returning a future Response.
     ///
     /// This method takes account of the responses structure and the error handling specific.
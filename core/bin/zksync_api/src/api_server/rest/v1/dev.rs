@@ -0,0 +1,152 @@
+//! Development part of API implementation.
+//!
+//! Endpoints of this scope are only compiled with the `dev` feature and are intended
+//! for demos and local testing. They refuse to work on the mainnet.
+
+// Built-in uses
+
+// External uses
+use actix_web::{web, Scope};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+// Workspace uses
+use zksync_config::ConfigurationOptions;
+use zksync_crypto::{
+    bellman::{pairing::ff::PrimeField, PrimeFieldRepr},
+    franklin_crypto::alt_babyjubjub::fs::FsRepr,
+    priv_key_from_fs, public_key_from_private,
+    rand::{thread_rng, Rng},
+    Fs, PrivateKey,
+};
+use zksync_storage::utils::{BytesToHexSerde, ZeroxPrefix};
+use zksync_types::{network::Network, tx::PackedPublicKey, PubKeyHash};
+
+// Local uses
+use super::{
+    client::{self, Client},
+    Error as ApiError, JsonResult,
+};
+
+// Data transfer objects.
+
+/// Freshly generated zkSync keypair.
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct GeneratedKeypair {
+    /// Seed the private key is derived from, see `private_key_from_seed`.
+    #[serde(with = "BytesToHexSerde::<ZeroxPrefix>")]
+    pub private_key_seed: Vec<u8>,
+    pub pub_key: PackedPublicKey,
+    pub address: PubKeyHash,
+}
+
+// Client implementation
+
+/// Development API part.
+impl Client {
+    /// Generates a new random keypair, only available on the test networks.
+    pub async fn generate_keypair(&self) -> client::Result<GeneratedKeypair> {
+        self.get("dev/generate_keypair").send().await
+    }
+}
+
+// Server implementation
+
+/// Derives the private key from the seed, using the same algorithm as the
+/// `private_key_from_seed` function of the Rust SDK: seed is hashed via `sha256`,
+/// and the hash is applied repeatedly until the output is a valid `PrivateKey`.
+fn private_key_from_seed(seed: &[u8]) -> PrivateKey {
+    let mut effective_seed = Sha256::digest(seed).to_vec();
+
+    loop {
+        let raw_priv_key = Sha256::digest(&effective_seed).to_vec();
+        let mut fs_repr = FsRepr::default();
+        fs_repr
+            .read_be(&raw_priv_key[..])
+            .expect("failed to read raw_priv_key");
+        match Fs::from_repr(fs_repr) {
+            Ok(fs) => return priv_key_from_fs(fs),
+            Err(_) => effective_seed = raw_priv_key,
+        }
+    }
+}
+
+/// Returns an error if the dev endpoints must not be served on the given network.
+fn ensure_dev_network(network: Network) -> Result<(), ApiError> {
+    if network == Network::Mainnet {
+        return Err(ApiError::forbidden(
+            "Development endpoints are disabled in production",
+        ));
+    }
+    Ok(())
+}
+
+fn generate_keypair_from_seed(private_key_seed: Vec<u8>) -> GeneratedKeypair {
+    let private_key = private_key_from_seed(&private_key_seed);
+    let pub_key = PackedPublicKey(public_key_from_private(&private_key));
+    let address = PubKeyHash::from_pubkey(&pub_key.0);
+
+    GeneratedKeypair {
+        private_key_seed,
+        pub_key,
+        address,
+    }
+}
+
+async fn generate_keypair(network: web::Data<Network>) -> JsonResult<GeneratedKeypair> {
+    ensure_dev_network(*network.get_ref())?;
+
+    let seed: [u8; 32] = thread_rng().gen();
+    Ok(web::Json(generate_keypair_from_seed(seed.to_vec())))
+}
+
+pub fn api_scope(env_options: &ConfigurationOptions) -> Scope {
+    let network: Network = env_options.eth_network.parse().unwrap();
+
+    web::scope("dev")
+        .data(network)
+        .route("generate_keypair", web::get().to(generate_keypair))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{super::test_utils::TestServerConfig, *};
+
+    #[test]
+    fn dev_network_check() {
+        assert!(ensure_dev_network(Network::Localhost).is_ok());
+        assert!(ensure_dev_network(Network::Rinkeby).is_ok());
+        assert!(ensure_dev_network(Network::Mainnet).is_err());
+    }
+
+    #[test]
+    fn keypair_from_seed_is_deterministic() {
+        let seed = vec![7; 32];
+        let first = generate_keypair_from_seed(seed.clone());
+        let second = generate_keypair_from_seed(seed);
+
+        assert_eq!(first.address, second.address);
+        assert_eq!(first.address, PubKeyHash::from_pubkey(&first.pub_key.0));
+    }
+
+    #[actix_rt::test]
+    async fn test_generate_keypair() -> anyhow::Result<()> {
+        let cfg = TestServerConfig::default();
+        let (client, server) = cfg.start_server(|cfg| api_scope(&cfg.env_options));
+
+        let keypair = client.generate_keypair().await?;
+        assert_eq!(keypair.private_key_seed.len(), 32);
+        assert_eq!(keypair.address, PubKeyHash::from_pubkey(&keypair.pub_key.0));
+
+        // The returned seed reproduces the returned keys.
+        let private_key = private_key_from_seed(&keypair.private_key_seed);
+        assert_eq!(
+            PubKeyHash::from_pubkey(&public_key_from_private(&private_key)),
+            keypair.address
+        );
+
+        server.stop().await;
+        Ok(())
+    }
+}
@@ -2,7 +2,9 @@
 use std::fmt::{self, Display};
 
 // External uses
-use actix_web::{dev::Body, http::HeaderValue, HttpResponse, ResponseError};
+use actix_web::{
+    dev::Body, error::JsonPayloadError, http::HeaderValue, HttpRequest, HttpResponse, ResponseError,
+};
 use reqwest::{header::CONTENT_TYPE, StatusCode};
 use serde::{Deserialize, Serialize};
 
@@ -10,6 +12,15 @@ use serde::{Deserialize, Serialize};
 
 // Local uses
 
+/// Messages of the errors returned when the signature or the public key has
+/// an incorrect length.
+const LENGTH_MISMATCH_ERRORS: &[&str] = &[
+    "PublicKey size mismatch",
+    "Signature size mismatch",
+    "packed signature length mismatch",
+    "eth signature length should be 65 bytes",
+];
+
 /// The error body that is returned in the response content.
 #[derive(Debug, Serialize, Deserialize, Default)]
 pub struct ErrorBody {
@@ -45,6 +56,26 @@ impl Error {
         Self::with_code(StatusCode::BAD_REQUEST, title)
     }
 
+    /// Creates a new Error with the UNAUTHORIZED (401) status code.
+    pub fn unauthorized(title: impl Display) -> Self {
+        Self::with_code(StatusCode::UNAUTHORIZED, title)
+    }
+
+    /// Creates a new Error with the FORBIDDEN (403) status code.
+    pub fn forbidden(title: impl Display) -> Self {
+        Self::with_code(StatusCode::FORBIDDEN, title)
+    }
+
+    /// Creates a new Error with the CONFLICT (409) status code.
+    pub fn conflict(title: impl Display) -> Self {
+        Self::with_code(StatusCode::CONFLICT, title)
+    }
+
+    /// Creates a new Error with the UNPROCESSABLE_ENTITY (422) status code.
+    pub fn unprocessable_entity(title: impl Display) -> Self {
+        Self::with_code(StatusCode::UNPROCESSABLE_ENTITY, title)
+    }
+
     /// Creates a new Error with the INTERNAL_SERVER_ERROR (500) status code.
     pub fn internal(title: impl Display) -> Self {
         Self::with_code(StatusCode::INTERNAL_SERVER_ERROR, title)
@@ -55,6 +86,11 @@ impl Error {
         Self::with_code(StatusCode::NOT_IMPLEMENTED, title)
     }
 
+    /// Creates a new Error with the SERVICE_UNAVAILABLE (503) status code.
+    pub fn service_unavailable(title: impl Display) -> Self {
+        Self::with_code(StatusCode::SERVICE_UNAVAILABLE, title)
+    }
+
     fn with_code(http_code: StatusCode, title: impl Display) -> Self {
         Self {
             http_code,
@@ -115,3 +151,24 @@ impl ResponseError for Error {
         }
     }
 }
+
+/// Handles the errors of the JSON request bodies extraction.
+///
+/// Request bodies containing a signature or a public key of an incorrect length are
+/// syntactically correct, so such errors are reported with the UNPROCESSABLE_ENTITY (422)
+/// status code and the exact error message. Other errors are handled by default.
+pub(super) fn json_error_handler(err: JsonPayloadError, _req: &HttpRequest) -> actix_web::Error {
+    if let JsonPayloadError::Deserialize(err) = &err {
+        // Strip the error position appended by `serde_json`.
+        let message = err.to_string();
+        let position = format!(" at line {} column {}", err.line(), err.column());
+        let message = message.strip_suffix(&position).unwrap_or(&message);
+
+        if LENGTH_MISMATCH_ERRORS.contains(&message) {
+            return Error::unprocessable_entity("Incorrect length of the field")
+                .detail(message)
+                .into();
+        }
+    }
+    err.into()
+}
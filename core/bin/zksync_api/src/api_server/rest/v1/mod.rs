@@ -7,41 +7,106 @@ pub use self::error::{Error, ErrorBody};
 
 // External uses
 use actix_web::{
+    error::JsonPayloadError,
     web::{self, Json},
-    Scope,
+    HttpMessage, HttpRequest, Scope,
 };
-use serde::{Deserialize, Serialize};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
 
 // Workspace uses
 use zksync_config::ConfigurationOptions;
 use zksync_types::BlockNumber;
 
 // Local uses
-use crate::api_server::tx_sender::TxSender;
+use self::{auth::RequestAuthenticator, signatures::AllowedSchemes};
+use crate::{api_server::tx_sender::TxSender, utils::replay_cache::ReplayCache};
 
+mod auth;
 mod blocks;
 pub mod client;
 mod config;
+#[cfg(feature = "dev")]
+mod dev;
 mod error;
+mod openapi;
+mod signatures;
 #[cfg(test)]
-mod test_utils;
+pub(crate) mod test_utils;
 mod tokens;
 mod transactions;
+mod version;
 
 /// Maximum limit value in the requests.
 pub const MAX_LIMIT: u32 = 100;
+/// Maximum size of the JSON request body in bytes.
+const JSON_LIMIT: usize = 32 * 1024;
 
 type JsonResult<T> = std::result::Result<web::Json<T>, Error>;
 
-pub(crate) fn api_scope(tx_sender: TxSender, env_options: ConfigurationOptions) -> Scope {
+pub(crate) fn api_scope(
+    tx_sender: TxSender,
+    env_options: ConfigurationOptions,
+    replay_cache: ReplayCache,
+) -> Scope {
     web::scope("/api/v1")
+        .app_data(json_config())
         .service(config::api_scope(&env_options))
         .service(blocks::api_scope(&env_options, tx_sender.pool.clone()))
-        .service(transactions::api_scope(tx_sender.clone()))
+        .service(transactions::api_scope(
+            tx_sender.clone(),
+            RequestAuthenticator::from_config(&env_options),
+        ))
+        .service(signatures::api_scope(
+            replay_cache,
+            AllowedSchemes::from_config(&env_options),
+        ))
         .service(tokens::api_scope(
             tx_sender.tokens,
             tx_sender.ticker_requests,
         ))
+        .service(version::api_scope())
+        .configure(|cfg| dev_services(cfg, &env_options))
+        .route("openapi.json", web::get().to(openapi::spec))
+}
+
+/// Registers the development-only endpoints, if the `dev` feature is enabled.
+#[cfg(feature = "dev")]
+fn dev_services(cfg: &mut web::ServiceConfig, env_options: &ConfigurationOptions) {
+    cfg.service(dev::api_scope(env_options));
+}
+
+#[cfg(not(feature = "dev"))]
+fn dev_services(_cfg: &mut web::ServiceConfig, _env_options: &ConfigurationOptions) {}
+
+/// Configuration of the JSON request bodies extraction shared by all the endpoints.
+pub(crate) fn json_config() -> web::JsonConfig {
+    web::JsonConfig::default()
+        .limit(JSON_LIMIT)
+        .error_handler(error::json_error_handler)
+}
+
+/// Decodes the raw JSON request body with the same checks and errors as the `Json`
+/// extractor configured by `json_config`, for the endpoints which need the raw body
+/// (e.g. to check its signature).
+fn decode_json_body<T: DeserializeOwned>(
+    req: &HttpRequest,
+    body: &[u8],
+) -> Result<T, actix_web::Error> {
+    let is_json = match req.mime_type() {
+        Ok(Some(mime)) => {
+            mime.subtype() == "json" || mime.suffix().map_or(false, |suffix| suffix == "json")
+        }
+        _ => false,
+    };
+
+    let result = if !is_json {
+        Err(JsonPayloadError::ContentType)
+    } else if body.len() > JSON_LIMIT {
+        Err(JsonPayloadError::Overflow)
+    } else {
+        serde_json::from_slice(body).map_err(JsonPayloadError::Deserialize)
+    };
+    result.map_err(|err| error::json_error_handler(err, req))
 }
 
 /// Internal pagination query representation in according to spec:
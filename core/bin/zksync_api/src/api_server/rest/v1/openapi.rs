@@ -0,0 +1,586 @@
+//! OpenAPI document describing the API.
+//!
+//! Schemas of the data transfer objects are inferred from their serialized example values,
+//! so they always follow the actual `serde` layout of the types.
+
+// Built-in uses
+
+// External uses
+use actix_web::web::Json;
+use chrono::Utc;
+use serde::Serialize;
+use serde_json::{json, Map, Value};
+
+// Workspace uses
+use zksync_crypto::{convert::FeConvert, Fr};
+use zksync_types::{
+    tx::{ExplainedField, SignatureType, TxExplanation, TxHash, TxSignature},
+    Address, PubKeyHash, Token,
+};
+
+// Local uses
+use super::{
+    auth::SIGNATURE_HEADER,
+    blocks::{BlockInfo, TransactionInfo},
+    config::Contracts,
+    signatures::{
+        AddressCheck, AddressClaim, DetachedSignature, InvalidTx, StringSignature, TxBytesCheck,
+    },
+    transactions::{TokenAmount, TxEffects, TxOutputEffect},
+    version::VersionInfo,
+    ErrorBody,
+};
+
+/// Schema of the request or response body.
+#[derive(Debug, Clone, Copy)]
+enum Schema {
+    /// Named schema from the document components.
+    Ref(&'static str),
+    /// Array of the named schemas.
+    Array(&'static str),
+    /// Named schema or `null` if the requested object doesn't exist.
+    Nullable(&'static str),
+    /// Value of the primitive type.
+    Primitive(&'static str),
+    /// Raw binary data.
+    Binary,
+    /// Arbitrary JSON value.
+    Any,
+}
+
+impl Schema {
+    fn to_json(self) -> Value {
+        let reference = |name: &str| json!({ "$ref": format!("#/components/schemas/{}", name) });
+
+        match self {
+            Schema::Ref(name) => reference(name),
+            Schema::Array(name) => json!({ "type": "array", "items": reference(name) }),
+            Schema::Nullable(name) => json!({ "nullable": true, "allOf": [reference(name)] }),
+            Schema::Primitive(ty) => json!({ "type": ty }),
+            Schema::Binary => json!({ "type": "string", "format": "binary" }),
+            Schema::Any => json!({}),
+        }
+    }
+}
+
+/// Single API endpoint.
+#[derive(Debug)]
+struct Endpoint {
+    method: &'static str,
+    /// Path relative to the API root, path parameters are enclosed in braces.
+    path: &'static str,
+    summary: &'static str,
+    /// Query parameters along with their types.
+    query: &'static [(&'static str, &'static str)],
+    /// Optional header required by the endpoint.
+    header: Option<&'static str>,
+    /// Request body and its content type.
+    request: Option<(&'static str, Schema)>,
+    response: Schema,
+}
+
+const JSON: &str = "application/json";
+const OCTET_STREAM: &str = "application/octet-stream";
+
+/// All the endpoints of the API, in the order they are registered.
+const ENDPOINTS: &[Endpoint] = &[
+    Endpoint {
+        method: "get",
+        path: "/config/contracts",
+        summary: "Addresses of the zkSync contracts",
+        query: &[],
+        header: None,
+        request: None,
+        response: Schema::Ref("Contracts"),
+    },
+    Endpoint {
+        method: "get",
+        path: "/config/network",
+        summary: "Ethereum network zkSync is deployed to",
+        query: &[],
+        header: None,
+        request: None,
+        response: Schema::Primitive("string"),
+    },
+    Endpoint {
+        method: "get",
+        path: "/config/deposit_confirmations",
+        summary: "Number of confirmations required for deposits",
+        query: &[],
+        header: None,
+        request: None,
+        response: Schema::Primitive("integer"),
+    },
+    Endpoint {
+        method: "get",
+        path: "/blocks",
+        summary: "Range of blocks",
+        query: &[
+            ("limit", "integer"),
+            ("before", "integer"),
+            ("after", "integer"),
+        ],
+        header: None,
+        request: None,
+        response: Schema::Array("BlockInfo"),
+    },
+    Endpoint {
+        method: "get",
+        path: "/blocks/{id}",
+        summary: "Block with the given number",
+        query: &[],
+        header: None,
+        request: None,
+        response: Schema::Nullable("BlockInfo"),
+    },
+    Endpoint {
+        method: "get",
+        path: "/blocks/{id}/transactions",
+        summary: "Transactions of the block with the given number",
+        query: &[],
+        header: None,
+        request: None,
+        response: Schema::Array("TransactionInfo"),
+    },
+    Endpoint {
+        method: "post",
+        path: "/transactions/submit",
+        summary: "Submit a transaction to the memory pool",
+        query: &[("fast_processing", "boolean")],
+        header: None,
+        request: Some((JSON, Schema::Ref("IncomingTx"))),
+        response: Schema::Ref("TxHash"),
+    },
+    Endpoint {
+        method: "post",
+        path: "/transactions/submit/batch",
+        summary: "Submit a batch of transactions to the memory pool",
+        query: &[],
+        header: Some(SIGNATURE_HEADER),
+        request: Some((JSON, Schema::Ref("IncomingTxBatch"))),
+        response: Schema::Array("TxHash"),
+    },
+    Endpoint {
+        method: "post",
+        path: "/transactions/simulate",
+        summary: "Preview the effects of a transaction without submitting it",
+        query: &[],
+        header: None,
+        request: Some((JSON, Schema::Ref("ZkSyncTx"))),
+        response: Schema::Ref("TxEffects"),
+    },
+    Endpoint {
+        method: "post",
+        path: "/signatures/verify_detached",
+        summary: "Verify a detached signature and return its signer",
+        query: &[],
+        header: None,
+        request: Some((JSON, Schema::Ref("DetachedSignature"))),
+        response: Schema::Nullable("PubKeyHash"),
+    },
+    Endpoint {
+        method: "post",
+        path: "/signatures/check_string_signature",
+        summary: "Verify a signature of the UTF-8 encoded string and return its signer",
+        query: &[],
+        header: None,
+        request: Some((JSON, Schema::Ref("StringSignature"))),
+        response: Schema::Nullable("PubKeyHash"),
+    },
+    Endpoint {
+        method: "post",
+        path: "/signatures/check_tx_bytes",
+        summary: "Decode a wire encoded transaction and verify its signature",
+        query: &[],
+        header: None,
+        request: Some((OCTET_STREAM, Schema::Binary)),
+        response: Schema::Ref("TxBytesCheck"),
+    },
+    Endpoint {
+        method: "post",
+        path: "/signatures/invalid_txs",
+        summary: "Check a batch of transactions and return only the invalid ones",
+        query: &[],
+        header: None,
+        request: Some((JSON, Schema::Array("ZkSyncTx"))),
+        response: Schema::Array("InvalidTx"),
+    },
+    Endpoint {
+        method: "post",
+        path: "/signatures/verify_address",
+        summary: "Derive the address from a public key and compare it with the claimed one",
+        query: &[],
+        header: None,
+        request: Some((JSON, Schema::Ref("AddressClaim"))),
+        response: Schema::Ref("AddressCheck"),
+    },
+    Endpoint {
+        method: "post",
+        path: "/signatures/explain_bytes",
+        summary: "Field by field breakdown of the transaction bytes",
+        query: &[],
+        header: None,
+        request: Some((OCTET_STREAM, Schema::Binary)),
+        response: Schema::Ref("TxExplanation"),
+    },
+    Endpoint {
+        method: "get",
+        path: "/tokens",
+        summary: "All the supported tokens",
+        query: &[],
+        header: None,
+        request: None,
+        response: Schema::Array("Token"),
+    },
+    Endpoint {
+        method: "get",
+        path: "/tokens/{id}",
+        summary: "Token with the given ID, address or symbol",
+        query: &[],
+        header: None,
+        request: None,
+        response: Schema::Nullable("Token"),
+    },
+    Endpoint {
+        method: "get",
+        path: "/tokens/{id}/price",
+        summary: "Price of the token",
+        query: &[("in", "string")],
+        header: None,
+        request: None,
+        response: Schema::Primitive("string"),
+    },
+    Endpoint {
+        method: "get",
+        path: "/version",
+        summary: "Version and build information of the server",
+        query: &[],
+        header: None,
+        request: None,
+        response: Schema::Ref("VersionInfo"),
+    },
+    Endpoint {
+        method: "get",
+        path: "/openapi.json",
+        summary: "This document",
+        query: &[],
+        header: None,
+        request: None,
+        response: Schema::Any,
+    },
+];
+
+/// Infers the JSON schema of the serialized value.
+fn infer_schema(value: &Value) -> Value {
+    match value {
+        Value::Null => json!({ "nullable": true }),
+        Value::Bool(_) => json!({ "type": "boolean" }),
+        Value::Number(number) if number.is_f64() => json!({ "type": "number" }),
+        Value::Number(_) => json!({ "type": "integer" }),
+        Value::String(_) => json!({ "type": "string" }),
+        Value::Array(items) => json!({
+            "type": "array",
+            "items": items.first().map(infer_schema).unwrap_or_else(|| json!({})),
+        }),
+        Value::Object(fields) => {
+            let properties: Map<String, Value> = fields
+                .iter()
+                .map(|(name, value)| (name.clone(), infer_schema(value)))
+                .collect();
+            json!({ "type": "object", "properties": properties })
+        }
+    }
+}
+
+/// Infers the schema of the type from its example value. Optional fields of the example
+/// should be set, otherwise their type cannot be inferred.
+fn schema_of<T: Serialize>(example: &T) -> Value {
+    let value = serde_json::to_value(example).expect("DTO should be serializable");
+    let mut schema = infer_schema(&value);
+    schema["example"] = value;
+    schema
+}
+
+/// Schemas of the transaction and the transaction submission requests. Transactions
+/// themselves are described by the `ZkSyncTx` type, which has a separate layout for each
+/// transaction type.
+fn tx_schemas() -> (Value, Value, Value) {
+    let tx = json!({
+        "type": "object",
+        "description": "zkSync transaction, tagged with its `type` field"
+    });
+    let signature = json!({
+        "type": "object",
+        "nullable": true,
+        "description": "Ethereum signature of the transaction message"
+    });
+    let tx_ref = Schema::Ref("ZkSyncTx").to_json();
+
+    (
+        tx,
+        json!({
+            "type": "object",
+            "properties": { "tx": tx_ref.clone(), "signature": signature.clone() },
+            "required": ["tx"]
+        }),
+        json!({
+            "type": "object",
+            "properties": {
+                "txs": { "type": "array", "items": tx_ref },
+                "signature": signature
+            },
+            "required": ["txs"]
+        }),
+    )
+}
+
+fn component_schemas() -> Value {
+    let tx_hash = TxHash::default();
+    let pub_key_hash = PubKeyHash::default();
+    let (tx, incoming_tx, incoming_tx_batch) = tx_schemas();
+
+    json!({
+        "ErrorBody": schema_of(&ErrorBody {
+            docs_uri: "https://zksync.io/api/v1/errors".to_owned(),
+            title: "Incorrect pagination query".to_owned(),
+            detail: "Limit should be greater than zero".to_owned(),
+            location: "src/api_server/rest/v1/mod.rs:42".to_owned(),
+            code: Some(102),
+        }),
+        "TxHash": schema_of(&tx_hash),
+        "PubKeyHash": schema_of(&pub_key_hash),
+        "Contracts": schema_of(&Contracts {
+            contract: Address::zero(),
+        }),
+        "BlockInfo": schema_of(&BlockInfo {
+            block_number: 1,
+            new_state_root: Fr::from_bytes(&[0; 32]).expect("zero is a correct field element"),
+            block_size: 10,
+            commit_tx_hash: Some(tx_hash),
+            verify_tx_hash: Some(tx_hash),
+            committed_at: Utc::now(),
+            verified_at: Some(Utc::now()),
+        }),
+        "TransactionInfo": schema_of(&TransactionInfo {
+            tx_hash,
+            block_number: 1,
+            op: json!({ "type": "Transfer" }),
+            success: Some(true),
+            fail_reason: Some("Nonce mismatch".to_owned()),
+            created_at: Utc::now(),
+        }),
+        "ZkSyncTx": tx,
+        "IncomingTx": incoming_tx,
+        "IncomingTxBatch": incoming_tx_batch,
+        "TxEffects": schema_of(&TxEffects {
+            tx_hash,
+            sender: Address::zero(),
+            outputs: vec![TxOutputEffect {
+                recipient: Address::zero(),
+                token: 0,
+                amount: 1_000u64.into(),
+            }],
+            fee: Some(TokenAmount {
+                token: 0,
+                amount: 10u64.into(),
+            }),
+            total_debit: vec![TokenAmount {
+                token: 0,
+                amount: 1_010u64.into(),
+            }],
+            resulting_nonce: 1,
+        }),
+        "DetachedSignature": schema_of(&DetachedSignature {
+            bytes: vec![0; 4],
+            signature: TxSignature::default(),
+            variant: SignatureType::default(),
+        }),
+        "StringSignature": schema_of(&StringSignature {
+            message: "Sign in to zkSync".to_owned(),
+            signature: TxSignature::default(),
+            variant: SignatureType::default(),
+        }),
+        "TxBytesCheck": schema_of(&TxBytesCheck {
+            tx_hash,
+            signer: Some(pub_key_hash.clone()),
+        }),
+        "InvalidTx": schema_of(&InvalidTx {
+            index: 0,
+            reason: "Transaction signature is incorrect".to_owned(),
+        }),
+        "AddressClaim": schema_of(&AddressClaim {
+            pub_key: TxSignature::default().pub_key,
+            claimed_address: pub_key_hash.clone(),
+        }),
+        "AddressCheck": schema_of(&AddressCheck {
+            matches: true,
+            derived_address: pub_key_hash,
+        }),
+        "TxExplanation": schema_of(&TxExplanation {
+            tx_type: "Close".to_owned(),
+            fields: vec![ExplainedField {
+                name: "type".to_owned(),
+                range: 0..1,
+                value: "4".to_owned(),
+            }],
+        }),
+        "Token": schema_of(&Token::new(0, Address::zero(), "ETH", 18)),
+        "VersionInfo": schema_of(&VersionInfo::current()),
+    })
+}
+
+fn operation(endpoint: &Endpoint) -> Value {
+    let mut parameters = Vec::new();
+    // Path parameters are taken from the path template.
+    for segment in endpoint.path.split('/') {
+        if let Some(name) = segment
+            .strip_prefix('{')
+            .and_then(|segment| segment.strip_suffix('}'))
+        {
+            parameters.push(json!({
+                "name": name,
+                "in": "path",
+                "required": true,
+                "schema": { "type": "string" }
+            }));
+        }
+    }
+    for (name, ty) in endpoint.query {
+        parameters.push(json!({
+            "name": name,
+            "in": "query",
+            "schema": { "type": ty }
+        }));
+    }
+    if let Some(header) = endpoint.header {
+        parameters.push(json!({
+            "name": header,
+            "in": "header",
+            "schema": { "type": "string" }
+        }));
+    }
+
+    let mut operation = json!({
+        "summary": endpoint.summary,
+        "parameters": parameters,
+        "responses": {
+            "200": {
+                "description": "Successful response",
+                "content": { JSON: { "schema": endpoint.response.to_json() } }
+            },
+            "default": {
+                "description": "Error response",
+                "content": { JSON: { "schema": Schema::Ref("ErrorBody").to_json() } }
+            }
+        }
+    });
+    if let Some((content_type, schema)) = endpoint.request {
+        operation["requestBody"] = json!({
+            "required": true,
+            "content": { content_type: { "schema": schema.to_json() } }
+        });
+    }
+    operation
+}
+
+/// Builds the OpenAPI document of the API.
+pub fn api_spec() -> Value {
+    let mut paths = Map::new();
+    for endpoint in ENDPOINTS {
+        let path = paths
+            .entry(endpoint.path)
+            .or_insert_with(|| Value::Object(Map::new()));
+        path[endpoint.method] = operation(endpoint);
+    }
+
+    json!({
+        "openapi": "3.0.3",
+        "info": {
+            "title": "zkSync API",
+            "version": env!("CARGO_PKG_VERSION")
+        },
+        "servers": [{ "url": "/api/v1" }],
+        "paths": paths,
+        "components": { "schemas": component_schemas() }
+    })
+}
+
+pub async fn spec() -> Json<Value> {
+    Json(api_spec())
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_web::{test, web, App};
+
+    use super::*;
+
+    #[actix_rt::test]
+    async fn test_openapi_spec() {
+        let mut app =
+            test::init_service(App::new().route("/openapi.json", web::get().to(spec))).await;
+
+        let req = test::TestRequest::get().uri("/openapi.json").to_request();
+        let spec: Value = serde_json::from_slice(&test::read_response(&mut app, req).await)
+            .expect("Spec should be a valid JSON");
+        assert_eq!(spec["openapi"], "3.0.3");
+
+        let routes = [
+            ("get", "/config/contracts"),
+            ("get", "/config/network"),
+            ("get", "/config/deposit_confirmations"),
+            ("get", "/blocks"),
+            ("get", "/blocks/{id}"),
+            ("get", "/blocks/{id}/transactions"),
+            ("post", "/transactions/submit"),
+            ("post", "/transactions/submit/batch"),
+            ("post", "/transactions/simulate"),
+            ("post", "/signatures/verify_detached"),
+            ("post", "/signatures/check_string_signature"),
+            ("post", "/signatures/check_tx_bytes"),
+            ("post", "/signatures/invalid_txs"),
+            ("post", "/signatures/verify_address"),
+            ("post", "/signatures/explain_bytes"),
+            ("get", "/tokens"),
+            ("get", "/tokens/{id}"),
+            ("get", "/tokens/{id}/price"),
+            ("get", "/version"),
+            ("get", "/openapi.json"),
+        ];
+        let paths = spec["paths"].as_object().unwrap();
+        let operations_count: usize = paths
+            .values()
+            .map(|path| path.as_object().unwrap().len())
+            .sum();
+        assert_eq!(operations_count, routes.len());
+
+        let schemas = &spec["components"]["schemas"];
+        for (method, path) in &routes {
+            let operation = &paths[*path][*method];
+            assert!(
+                operation.is_object(),
+                "{} {} is not described",
+                method,
+                path
+            );
+
+            // All the referenced schemas are defined.
+            let mut refs = operation.to_string();
+            while let Some(pos) = refs.find("#/components/schemas/") {
+                refs = refs[pos + "#/components/schemas/".len()..].to_owned();
+                let name = &refs[..refs.find('"').unwrap()];
+                assert!(schemas[name].is_object(), "{} is not defined", name);
+            }
+        }
+
+        // Schemas are inferred from the serialized types.
+        assert_eq!(
+            schemas["BlockInfo"]["properties"]["block_number"]["type"],
+            "integer"
+        );
+        assert_eq!(
+            schemas["TxBytesCheck"]["properties"]["txHash"]["type"],
+            "string"
+        );
+    }
+}
@@ -0,0 +1,871 @@
+//! Signatures part of API implementation.
+//!
+//! Endpoints of this scope are stateless and only perform cryptographic checks.
+
+// Built-in uses
+
+// External uses
+use actix_web::{
+    web::{self, Json},
+    HttpMessage, HttpRequest, Scope,
+};
+use serde::{Deserialize, Serialize};
+
+// Workspace uses
+use zksync_config::ConfigurationOptions;
+use zksync_storage::utils::{BytesToHexSerde, ZeroxPrefix};
+use zksync_types::{
+    tx::{
+        explain_bytes, invalid_txs, verify_detached, verify_string, PackedPublicKey, SignatureType,
+        TxExplanation, TxHash, TxSignature, MAX_SIGNED_MESSAGE_LEN,
+    },
+    PubKeyHash, ZkSyncTx,
+};
+
+// Local uses
+use super::{
+    client::{self, Client},
+    Error as ApiError,
+};
+use crate::utils::replay_cache::ReplayCache;
+
+/// Content type expected by the endpoints accepting the binary transactions.
+const OCTET_STREAM: &str = "application/octet-stream";
+/// Maximum number of transactions accepted by the `invalid_txs` endpoint.
+const MAX_CHECKED_TXS: usize = 50;
+
+// Data transfer objects.
+
+/// Detached zkSync signature along with the signed transaction bytes.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct DetachedSignature {
+    /// Transaction bytes as returned by the `ZkSyncTx::get_bytes` method.
+    #[serde(with = "BytesToHexSerde::<ZeroxPrefix>")]
+    pub bytes: Vec<u8>,
+    pub signature: TxSignature,
+    /// Musig variant used to sign the bytes, Rescue is used if omitted.
+    #[serde(default)]
+    pub variant: SignatureType,
+}
+
+/// zkSync signature of the string message.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct StringSignature {
+    /// Signed message. The signature is made for its UTF-8 encoding without a length prefix.
+    pub message: String,
+    pub signature: TxSignature,
+    /// Musig variant used to sign the message, Rescue is used if omitted.
+    #[serde(default)]
+    pub variant: SignatureType,
+}
+
+/// Result of the binary encoded transaction signature verification.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct TxBytesCheck {
+    /// Hash of the decoded transaction.
+    pub tx_hash: TxHash,
+    /// Signer of the transaction, or `None` if the signature is incorrect.
+    pub signer: Option<PubKeyHash>,
+}
+
+/// Invalid transaction of the checked batch.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct InvalidTx {
+    /// Index of the transaction in the batch.
+    pub index: usize,
+    /// Human-readable reason of the failure.
+    pub reason: String,
+}
+
+/// Public key along with the address claimed to be derived from it.
+// `PackedPublicKey` doesn't implement `Debug`.
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct AddressClaim {
+    pub pub_key: PackedPublicKey,
+    pub claimed_address: PubKeyHash,
+}
+
+/// Result of the address derivation check.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct AddressCheck {
+    /// Whether the claimed address matches the derived one.
+    pub matches: bool,
+    /// Address derived from the public key.
+    pub derived_address: PubKeyHash,
+}
+
+// Client implementation
+
+/// Signatures API part.
+impl Client {
+    /// Verifies the detached signature and returns its signer,
+    /// or `None` if the signature is incorrect.
+    pub async fn verify_detached(
+        &self,
+        bytes: Vec<u8>,
+        signature: TxSignature,
+        variant: SignatureType,
+    ) -> client::Result<Option<PubKeyHash>> {
+        self.post("signatures/verify_detached")
+            .body(&DetachedSignature {
+                bytes,
+                signature,
+                variant,
+            })
+            .send()
+            .await
+    }
+
+    /// Verifies the signature of the string message and returns its signer,
+    /// or `None` if the signature is incorrect.
+    pub async fn check_string_signature(
+        &self,
+        message: impl Into<String>,
+        signature: TxSignature,
+        variant: SignatureType,
+    ) -> client::Result<Option<PubKeyHash>> {
+        self.post("signatures/check_string_signature")
+            .body(&StringSignature {
+                message: message.into(),
+                signature,
+                variant,
+            })
+            .send()
+            .await
+    }
+
+    /// Decodes the transaction from its binary wire encoding and verifies its signature.
+    pub async fn check_tx_bytes(&self, wire_bytes: Vec<u8>) -> client::Result<TxBytesCheck> {
+        self.post("signatures/check_tx_bytes")
+            .bytes_body(wire_bytes)
+            .send()
+            .await
+    }
+
+    /// Checks every transaction of the batch and returns only the invalid ones.
+    ///
+    /// At most 50 transactions can be checked by a single request.
+    pub async fn invalid_txs(&self, txs: &[ZkSyncTx]) -> client::Result<Vec<InvalidTx>> {
+        self.post("signatures/invalid_txs").body(txs).send().await
+    }
+
+    /// Derives the address from the public key and compares it with the claimed one.
+    pub async fn verify_address(
+        &self,
+        pub_key: PackedPublicKey,
+        claimed_address: PubKeyHash,
+    ) -> client::Result<AddressCheck> {
+        self.post("signatures/verify_address")
+            .body(&AddressClaim {
+                pub_key,
+                claimed_address,
+            })
+            .send()
+            .await
+    }
+
+    /// Returns the field by field breakdown of the transaction bytes
+    /// (as returned by the `ZkSyncTx::get_bytes` method).
+    pub async fn explain_bytes(&self, bytes: Vec<u8>) -> client::Result<TxExplanation> {
+        self.post("signatures/explain_bytes")
+            .bytes_body(bytes)
+            .send()
+            .await
+    }
+}
+
+// Server implementation
+
+/// Signature schemes accepted by the endpoints verifying the detached signatures.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AllowedSchemes {
+    verify_detached: Vec<SignatureType>,
+    check_string_signature: Vec<SignatureType>,
+}
+
+impl Default for AllowedSchemes {
+    /// Accepts all the supported schemes on every endpoint.
+    fn default() -> Self {
+        Self::new(SignatureType::ALL.to_vec(), SignatureType::ALL.to_vec())
+    }
+}
+
+impl AllowedSchemes {
+    pub fn new(
+        verify_detached: Vec<SignatureType>,
+        check_string_signature: Vec<SignatureType>,
+    ) -> Self {
+        Self {
+            verify_detached,
+            check_string_signature,
+        }
+    }
+
+    /// Reads the allowed schemes from the configuration, endpoints without configured
+    /// schemes accept all of them.
+    ///
+    /// Panics if the configuration contains an unknown scheme name.
+    pub fn from_config(env_options: &ConfigurationOptions) -> Self {
+        fn parse(names: &Option<Vec<String>>) -> Vec<SignatureType> {
+            match names {
+                Some(names) => names
+                    .iter()
+                    .map(|name| name.parse().expect("Incorrect allowed signature scheme"))
+                    .collect(),
+                None => SignatureType::ALL.to_vec(),
+            }
+        }
+
+        Self::new(
+            parse(&env_options.api_verify_detached_schemes),
+            parse(&env_options.api_check_string_signature_schemes),
+        )
+    }
+}
+
+fn check_scheme(allowed: &[SignatureType], variant: SignatureType) -> Result<(), ApiError> {
+    if !allowed.contains(&variant) {
+        return Err(
+            ApiError::bad_request("Signature scheme not allowed").detail(format!(
+                "{:?} signatures are not accepted by this endpoint",
+                variant
+            )),
+        );
+    }
+    variant
+        .ensure_enabled()
+        .map_err(|err| ApiError::bad_request("Signature scheme disabled").detail(err))
+}
+
+/// Rejects the messages which would be accepted along with any suffix,
+/// see `MAX_SIGNED_MESSAGE_LEN`.
+fn check_message_len(message: &[u8]) -> Result<(), ApiError> {
+    if message.len() > MAX_SIGNED_MESSAGE_LEN {
+        return Err(ApiError::bad_request("Message is too long").detail(format!(
+            "Signed message cannot be longer than {} bytes",
+            MAX_SIGNED_MESSAGE_LEN
+        )));
+    }
+    Ok(())
+}
+
+// Core logic of the endpoints, callable without the HTTP server.
+
+/// Verifies the detached signature, see `Client::verify_detached`.
+fn check_detached_signature(
+    schemes: &AllowedSchemes,
+    body: &DetachedSignature,
+) -> Result<Option<PubKeyHash>, ApiError> {
+    check_scheme(&schemes.verify_detached, body.variant)?;
+    check_message_len(&body.bytes)?;
+    Ok(verify_detached(&body.bytes, &body.signature, body.variant))
+}
+
+/// Verifies the signature of the string message, see `Client::check_string_signature`.
+fn check_string_message_signature(
+    schemes: &AllowedSchemes,
+    body: &StringSignature,
+) -> Result<Option<PubKeyHash>, ApiError> {
+    check_scheme(&schemes.check_string_signature, body.variant)?;
+    check_message_len(body.message.as_bytes())?;
+    Ok(verify_string(&body.message, &body.signature, body.variant))
+}
+
+/// Decodes the wire encoded transaction and verifies its signature,
+/// see `Client::check_tx_bytes`.
+fn check_tx_signature(
+    replay_cache: &ReplayCache,
+    wire_bytes: &[u8],
+) -> Result<TxBytesCheck, ApiError> {
+    let tx = ZkSyncTx::from_wire_bytes_strict(wire_bytes)
+        .map_err(|err| ApiError::bad_request("Unable to decode transaction").detail(err))?;
+
+    let tx_hash = tx.hash();
+    let signer = tx.verify_signature();
+    // Transaction hash doesn't cover the signature, so only the correctly signed transactions
+    // are recorded. Otherwise a copy with a garbage signature would make the genuine
+    // transaction look like a duplicate.
+    if signer.is_some() && replay_cache.check_and_insert(tx_hash) {
+        return Err(ApiError::conflict("Duplicate transaction").detail(format!(
+            "Transaction {} has already been checked",
+            tx_hash.to_string()
+        )));
+    }
+
+    Ok(TxBytesCheck { tx_hash, signer })
+}
+
+/// Derives the address from the public key and compares it with the claimed one,
+/// see `Client::verify_address`.
+fn derive_address(claim: &AddressClaim) -> AddressCheck {
+    let derived_address = PubKeyHash::from_pubkey(&claim.pub_key.0);
+    AddressCheck {
+        matches: derived_address == claim.claimed_address,
+        derived_address,
+    }
+}
+
+// Handlers.
+
+async fn verify_detached_signature(
+    schemes: web::Data<AllowedSchemes>,
+    Json(body): Json<DetachedSignature>,
+) -> Result<Json<Option<PubKeyHash>>, ApiError> {
+    check_detached_signature(&schemes, &body).map(Json)
+}
+
+async fn check_string_signature(
+    schemes: web::Data<AllowedSchemes>,
+    Json(body): Json<StringSignature>,
+) -> Result<Json<Option<PubKeyHash>>, ApiError> {
+    check_string_message_signature(&schemes, &body).map(Json)
+}
+
+fn check_binary_body(req: &HttpRequest) -> Result<(), ApiError> {
+    if req.content_type() != OCTET_STREAM {
+        return Err(ApiError::bad_request("Unsupported content type")
+            .detail(format!("Expected {} request body", OCTET_STREAM)));
+    }
+    Ok(())
+}
+
+async fn check_tx_bytes(
+    replay_cache: web::Data<ReplayCache>,
+    req: HttpRequest,
+    body: web::Bytes,
+) -> Result<Json<TxBytesCheck>, ApiError> {
+    check_binary_body(&req)?;
+    check_tx_signature(&replay_cache, &body).map(Json)
+}
+
+async fn check_invalid_txs(
+    Json(txs): Json<Vec<ZkSyncTx>>,
+) -> Result<Json<Vec<InvalidTx>>, ApiError> {
+    if txs.len() > MAX_CHECKED_TXS {
+        return Err(
+            ApiError::bad_request("Too many transactions").detail(format!(
+                "At most {} transactions can be checked at once",
+                MAX_CHECKED_TXS
+            )),
+        );
+    }
+
+    // Signatures verification is CPU-bound, so it must not block the server workers.
+    let invalid = web::block(move || Ok::<_, std::convert::Infallible>(invalid_txs(&txs)))
+        .await
+        .map_err(|err| ApiError::internal("Unable to check transactions").detail(err))?;
+
+    let invalid = invalid
+        .into_iter()
+        .map(|(index, reason)| InvalidTx {
+            index,
+            reason: reason.to_string(),
+        })
+        .collect();
+    Ok(Json(invalid))
+}
+
+async fn verify_address(Json(claim): Json<AddressClaim>) -> Json<AddressCheck> {
+    Json(derive_address(&claim))
+}
+
+async fn explain_tx_bytes(
+    req: HttpRequest,
+    body: web::Bytes,
+) -> Result<Json<TxExplanation>, ApiError> {
+    check_binary_body(&req)?;
+
+    let explanation = explain_bytes(&body)
+        .map_err(|err| ApiError::bad_request("Unable to explain transaction bytes").detail(err))?;
+    Ok(Json(explanation))
+}
+
+pub fn api_scope(replay_cache: ReplayCache, schemes: AllowedSchemes) -> Scope {
+    web::scope("signatures")
+        .data(replay_cache)
+        .data(schemes)
+        .route("verify_detached", web::post().to(verify_detached_signature))
+        .route(
+            "check_string_signature",
+            web::post().to(check_string_signature),
+        )
+        .route("check_tx_bytes", web::post().to(check_tx_bytes))
+        .route("invalid_txs", web::post().to(check_invalid_txs))
+        .route("verify_address", web::post().to(verify_address))
+        .route("explain_bytes", web::post().to(explain_tx_bytes))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use zksync_crypto::priv_key_from_fs;
+    use zksync_crypto::rand::{Rng, SeedableRng, XorShiftRng};
+    use zksync_types::tx::generate_test_transactions;
+
+    use super::{
+        super::{
+            test_utils::{signed_transfer, TestServerConfig},
+            ErrorBody,
+        },
+        *,
+    };
+
+    #[actix_rt::test]
+    async fn test_signatures_scope() -> anyhow::Result<()> {
+        let (client, server) =
+            TestServerConfig::default().start_signatures_server(AllowedSchemes::default());
+
+        let (transfer, private_key) = signed_transfer();
+        let bytes = transfer.get_bytes();
+
+        assert_eq!(
+            client
+                .verify_detached(
+                    bytes.clone(),
+                    transfer.signature.clone(),
+                    SignatureType::MusigRescue
+                )
+                .await?,
+            Some(PubKeyHash::from_privkey(&private_key))
+        );
+        let result = client
+            .verify_detached(bytes, transfer.signature, SignatureType::MusigSha256)
+            .await;
+        if SignatureType::MusigSha256.is_enabled() {
+            assert_eq!(result?, None);
+        } else {
+            let err = result.unwrap_err();
+            assert!(
+                err.to_string().contains("Signature scheme disabled"),
+                "{}",
+                err
+            );
+        }
+
+        server.stop().await;
+        Ok(())
+    }
+
+    #[actix_rt::test]
+    async fn test_length_mismatch_errors() -> anyhow::Result<()> {
+        let (client, server) =
+            TestServerConfig::default().start_signatures_server(AllowedSchemes::default());
+
+        let (_, private_key) = signed_transfer();
+        let bytes = b"hello world".to_vec();
+        let body = serde_json::to_value(&DetachedSignature {
+            signature: TxSignature::sign_musig(&private_key, &bytes),
+            bytes,
+            variant: SignatureType::MusigRescue,
+        })?;
+
+        let post = |body: serde_json::Value| {
+            reqwest::Client::new()
+                .post(&client.endpoint("signatures/verify_detached"))
+                .json(&body)
+                .send()
+        };
+        // Removes the last byte of the hex-encoded field.
+        let truncate = |body: &serde_json::Value, field: &str| {
+            let mut body = body.clone();
+            let value = body["signature"][field].as_str().unwrap().to_owned();
+            body["signature"][field] = value[..value.len() - 2].into();
+            body
+        };
+
+        for &(field, message) in &[
+            ("signature", "Signature size mismatch"),
+            ("pubKey", "PublicKey size mismatch"),
+        ] {
+            let response = post(truncate(&body, field)).await?;
+            assert_eq!(response.status(), reqwest::StatusCode::UNPROCESSABLE_ENTITY);
+            let error: ErrorBody = response.json().await?;
+            assert_eq!(error.detail, message);
+        }
+
+        // Other errors are not affected.
+        let mut incorrect = body.clone();
+        incorrect["bytes"] = "not a hex".into();
+        assert_eq!(
+            post(incorrect).await?.status(),
+            reqwest::StatusCode::BAD_REQUEST
+        );
+        assert_eq!(post(body).await?.status(), reqwest::StatusCode::OK);
+
+        server.stop().await;
+        Ok(())
+    }
+
+    #[actix_rt::test]
+    async fn test_check_string_signature() -> anyhow::Result<()> {
+        let (client, server) =
+            TestServerConfig::default().start_signatures_server(AllowedSchemes::default());
+
+        let (_, private_key) = signed_transfer();
+        let message = "Sign in to zkSync";
+        let signature = TxSignature::sign_musig(&private_key, message.as_bytes());
+
+        assert_eq!(
+            client
+                .check_string_signature(message, signature.clone(), SignatureType::MusigRescue)
+                .await?,
+            Some(PubKeyHash::from_privkey(&private_key))
+        );
+        assert_eq!(
+            client
+                .check_string_signature(
+                    "Sign in to zkSync!",
+                    signature.clone(),
+                    SignatureType::MusigRescue
+                )
+                .await?,
+            None
+        );
+        let result = client
+            .check_string_signature(message, signature, SignatureType::MusigSha256)
+            .await;
+        if SignatureType::MusigSha256.is_enabled() {
+            assert_eq!(result?, None);
+        } else {
+            let err = result.unwrap_err();
+            assert!(
+                err.to_string().contains("Signature scheme disabled"),
+                "{}",
+                err
+            );
+        }
+
+        server.stop().await;
+        Ok(())
+    }
+
+    #[actix_rt::test]
+    async fn test_allowed_schemes() -> anyhow::Result<()> {
+        // Detached signatures are only accepted for SHA-256, string ones only for Rescue.
+        let schemes = AllowedSchemes::new(
+            vec![SignatureType::MusigSha256],
+            vec![SignatureType::MusigRescue],
+        );
+        let (client, server) = TestServerConfig::default().start_signatures_server(schemes);
+
+        let (_, private_key) = signed_transfer();
+        let signer = Some(PubKeyHash::from_privkey(&private_key));
+        let message = "Sign in to zkSync";
+        let rescue_signature = TxSignature::sign_musig_rescue(&private_key, message.as_bytes());
+        let sha256_signature = TxSignature::sign_musig_sha256(&private_key, message.as_bytes());
+
+        let assert_not_allowed = |result: client::Result<Option<PubKeyHash>>| {
+            let err = result.unwrap_err();
+            assert!(
+                err.to_string().contains("Signature scheme not allowed"),
+                "{}",
+                err
+            );
+        };
+
+        // Detached signature.
+        assert_not_allowed(
+            client
+                .verify_detached(
+                    message.as_bytes().to_vec(),
+                    rescue_signature.clone(),
+                    SignatureType::MusigRescue,
+                )
+                .await,
+        );
+        let result = client
+            .verify_detached(
+                message.as_bytes().to_vec(),
+                sha256_signature.clone(),
+                SignatureType::MusigSha256,
+            )
+            .await;
+        if SignatureType::MusigSha256.is_enabled() {
+            assert_eq!(result?, signer);
+        } else {
+            let err = result.unwrap_err();
+            assert!(
+                err.to_string().contains("Signature scheme disabled"),
+                "{}",
+                err
+            );
+        }
+
+        // String signature.
+        assert_eq!(
+            client
+                .check_string_signature(message, rescue_signature, SignatureType::MusigRescue)
+                .await?,
+            signer
+        );
+        assert_not_allowed(
+            client
+                .check_string_signature(message, sha256_signature, SignatureType::MusigSha256)
+                .await,
+        );
+
+        server.stop().await;
+        Ok(())
+    }
+
+    #[actix_rt::test]
+    async fn test_check_tx_bytes() -> anyhow::Result<()> {
+        let (client, server) =
+            TestServerConfig::default().start_signatures_server(AllowedSchemes::default());
+
+        let (transfer, private_key) = signed_transfer();
+        let tx = ZkSyncTx::from(transfer);
+
+        assert_eq!(
+            client.check_tx_bytes(tx.to_wire_bytes()).await?,
+            TxBytesCheck {
+                tx_hash: tx.hash(),
+                signer: Some(PubKeyHash::from_privkey(&private_key)),
+            }
+        );
+
+        // Truncated input cannot be decoded.
+        let mut wire_bytes = tx.to_wire_bytes();
+        wire_bytes.pop();
+        let err = client.check_tx_bytes(wire_bytes).await.unwrap_err();
+        assert!(
+            err.to_string().contains("Unable to decode transaction"),
+            "{}",
+            err
+        );
+
+        // Exact duplicate is rejected.
+        let err = client.check_tx_bytes(tx.to_wire_bytes()).await.unwrap_err();
+        assert!(err.to_string().contains("Duplicate transaction"), "{}", err);
+
+        // Binary body is expected.
+        let response = reqwest::Client::new()
+            .post(&client.endpoint("signatures/check_tx_bytes"))
+            .json(&tx)
+            .send()
+            .await?;
+        assert_eq!(response.status(), reqwest::StatusCode::BAD_REQUEST);
+
+        server.stop().await;
+        Ok(())
+    }
+
+    #[actix_rt::test]
+    async fn test_invalid_txs() -> anyhow::Result<()> {
+        let (client, server) =
+            TestServerConfig::default().start_signatures_server(AllowedSchemes::default());
+
+        let mut txs = generate_test_transactions(20, 1);
+        assert!(client.invalid_txs(&txs).await?.is_empty());
+
+        // Only the transaction with the incorrect signature is reported.
+        if let ZkSyncTx::Transfer(tx) = &mut txs[5] {
+            tx.signature = TxSignature::default();
+        }
+        assert_eq!(
+            client.invalid_txs(&txs).await?,
+            vec![InvalidTx {
+                index: 5,
+                reason: "Transaction signature is incorrect".to_owned(),
+            }]
+        );
+
+        // Too long lists are rejected without the check.
+        let txs = generate_test_transactions(MAX_CHECKED_TXS + 1, 1);
+        let err = client.invalid_txs(&txs).await.unwrap_err();
+        assert!(err.to_string().contains("Too many transactions"), "{}", err);
+
+        server.stop().await;
+        Ok(())
+    }
+
+    #[actix_rt::test]
+    async fn test_verify_address() -> anyhow::Result<()> {
+        let (client, server) =
+            TestServerConfig::default().start_signatures_server(AllowedSchemes::default());
+
+        let (_, private_key) = signed_transfer();
+        let other_key = priv_key_from_fs(XorShiftRng::from_seed([5, 6, 7, 8]).gen());
+        let pub_key = TxSignature::sign_musig(&private_key, b"hello").pub_key;
+        let address = PubKeyHash::from_privkey(&private_key);
+
+        assert_eq!(
+            client
+                .verify_address(pub_key.clone(), address.clone())
+                .await?,
+            AddressCheck {
+                matches: true,
+                derived_address: address.clone(),
+            }
+        );
+        assert_eq!(
+            client
+                .verify_address(pub_key, PubKeyHash::from_privkey(&other_key))
+                .await?,
+            AddressCheck {
+                matches: false,
+                derived_address: address,
+            }
+        );
+
+        server.stop().await;
+        Ok(())
+    }
+
+    #[actix_rt::test]
+    async fn test_explain_bytes() -> anyhow::Result<()> {
+        let (client, server) =
+            TestServerConfig::default().start_signatures_server(AllowedSchemes::default());
+
+        let (transfer, _) = signed_transfer();
+
+        let explanation = client.explain_bytes(transfer.get_bytes()).await?;
+        assert_eq!(explanation, explain_bytes(&transfer.get_bytes())?);
+        assert_eq!(explanation.tx_type, "Transfer");
+
+        let err = client.explain_bytes(vec![0xff]).await.unwrap_err();
+        assert!(
+            err.to_string()
+                .contains("Unable to explain transaction bytes"),
+            "{}",
+            err
+        );
+
+        server.stop().await;
+        Ok(())
+    }
+
+    // Core logic tests, performed without the HTTP server.
+
+    #[test]
+    fn derive_address_core() {
+        let (transfer, private_key) = signed_transfer();
+        let signer = PubKeyHash::from_privkey(&private_key);
+        let claim = AddressClaim {
+            pub_key: transfer.signature.pub_key.clone(),
+            claimed_address: signer.clone(),
+        };
+        assert_eq!(
+            derive_address(&claim),
+            AddressCheck {
+                matches: true,
+                derived_address: signer.clone(),
+            }
+        );
+
+        let claim = AddressClaim {
+            claimed_address: PubKeyHash::default(),
+            ..claim
+        };
+        assert_eq!(
+            derive_address(&claim),
+            AddressCheck {
+                matches: false,
+                derived_address: signer,
+            }
+        );
+    }
+
+    #[test]
+    fn check_signature_core() {
+        let (transfer, private_key) = signed_transfer();
+        let signer = PubKeyHash::from_privkey(&private_key);
+        let schemes = AllowedSchemes::default();
+        let mut body = DetachedSignature {
+            bytes: transfer.get_bytes(),
+            signature: transfer.signature.clone(),
+            variant: SignatureType::MusigRescue,
+        };
+        assert_eq!(
+            check_detached_signature(&schemes, &body).unwrap(),
+            Some(signer.clone())
+        );
+
+        body.bytes.push(0);
+        assert_eq!(check_detached_signature(&schemes, &body).unwrap(), None);
+
+        // Signature of the prefix is not accepted for the longer message.
+        let long_body = DetachedSignature {
+            signature: TxSignature::sign_musig(&private_key, &[1; MAX_SIGNED_MESSAGE_LEN]),
+            bytes: [&[1; MAX_SIGNED_MESSAGE_LEN][..], b"suffix"].concat(),
+            variant: SignatureType::MusigRescue,
+        };
+        let err = check_detached_signature(&schemes, &long_body).unwrap_err();
+        assert_eq!(err.body.title, "Message is too long");
+
+        let schemes = AllowedSchemes::new(vec![SignatureType::MusigSha256], vec![]);
+        let err = check_detached_signature(&schemes, &body).unwrap_err();
+        assert_eq!(err.http_code, reqwest::StatusCode::BAD_REQUEST);
+        assert_eq!(err.body.title, "Signature scheme not allowed");
+
+        // String signatures.
+        let message = "Sign in to zkSync".to_owned();
+        let body = StringSignature {
+            signature: TxSignature::sign_musig(&private_key, message.as_bytes()),
+            message,
+            variant: SignatureType::MusigRescue,
+        };
+        assert_eq!(
+            check_string_message_signature(&AllowedSchemes::default(), &body).unwrap(),
+            Some(signer)
+        );
+        let err = check_string_message_signature(&schemes, &body).unwrap_err();
+        assert_eq!(err.body.title, "Signature scheme not allowed");
+
+        let message = "a".repeat(MAX_SIGNED_MESSAGE_LEN);
+        let body = StringSignature {
+            signature: TxSignature::sign_musig(&private_key, message.as_bytes()),
+            message: format!("{} and some suffix", message),
+            variant: SignatureType::MusigRescue,
+        };
+        let err = check_string_message_signature(&AllowedSchemes::default(), &body).unwrap_err();
+        assert_eq!(err.body.title, "Message is too long");
+    }
+
+    #[test]
+    fn check_tx_signature_core() {
+        let replay_cache = ReplayCache::new(16, Duration::from_secs(60));
+        let (transfer, private_key) = signed_transfer();
+        let signer = PubKeyHash::from_privkey(&private_key);
+
+        // Copies with an incorrect signature don't get into the replay cache.
+        let mut forged = transfer.clone();
+        forged.signature = TxSignature::default();
+        let forged = ZkSyncTx::from(forged);
+        for _ in 0..2 {
+            assert_eq!(
+                check_tx_signature(&replay_cache, &forged.to_wire_bytes()).unwrap(),
+                TxBytesCheck {
+                    tx_hash: forged.hash(),
+                    signer: None,
+                }
+            );
+        }
+
+        let tx = ZkSyncTx::from(transfer);
+        assert_eq!(tx.hash(), forged.hash());
+        assert_eq!(
+            check_tx_signature(&replay_cache, &tx.to_wire_bytes()).unwrap(),
+            TxBytesCheck {
+                tx_hash: tx.hash(),
+                signer: Some(signer),
+            }
+        );
+
+        // The same transaction is rejected within the replay window.
+        let err = check_tx_signature(&replay_cache, &tx.to_wire_bytes()).unwrap_err();
+        assert_eq!(err.http_code, reqwest::StatusCode::CONFLICT);
+
+        let mut wire_bytes = tx.to_wire_bytes();
+        wire_bytes.pop();
+        let err = check_tx_signature(&replay_cache, &wire_bytes).unwrap_err();
+        assert_eq!(err.http_code, reqwest::StatusCode::BAD_REQUEST);
+        assert_eq!(err.body.title, "Unable to decode transaction");
+    }
+}
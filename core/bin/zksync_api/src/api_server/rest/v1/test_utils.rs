@@ -9,7 +9,11 @@ use tokio::sync::Mutex;
 
 // Workspace uses
 use zksync_config::ConfigurationOptions;
-use zksync_crypto::rand::{SeedableRng, XorShiftRng};
+use zksync_crypto::{
+    priv_key_from_fs,
+    rand::{Rng, SeedableRng, XorShiftRng},
+    PrivateKey,
+};
 use zksync_storage::test_data::{
     dummy_ethereum_tx_hash, gen_acc_random_updates, gen_unique_operation,
     gen_unique_operation_with_txs, BLOCK_SIZE_CHUNKS,
@@ -19,11 +23,16 @@ use zksync_test_account::ZkSyncAccount;
 use zksync_types::{ethereum::OperationType, helpers::apply_updates, AccountMap, Action};
 use zksync_types::{
     operations::{ChangePubKeyOp, TransferToNewOp},
-    ExecutedOperations, ExecutedTx, ZkSyncOp, ZkSyncTx,
+    Address, ExecutedOperations, ExecutedTx, Transfer, ZkSyncOp, ZkSyncTx,
 };
 
 // Local uses
-use super::client::Client;
+use super::{
+    client::Client,
+    json_config,
+    signatures::{self, AllowedSchemes},
+};
+use crate::utils::replay_cache::ReplayCache;
 
 #[derive(Debug, Clone)]
 pub struct TestServerConfig {
@@ -47,7 +56,11 @@ impl TestServerConfig {
     {
         let this = self.clone();
         let server = actix_web::test::start(move || {
-            App::new().service(web::scope("/api/v1").service(scope_factory(&this)))
+            App::new().service(
+                web::scope("/api/v1")
+                    .app_data(json_config())
+                    .service(scope_factory(&this)),
+            )
         });
 
         let mut url = server.url("");
@@ -57,6 +70,18 @@ impl TestServerConfig {
         (client, server)
     }
 
+    /// Starts the server with the signatures scope, which accepts the given signature schemes.
+    pub fn start_signatures_server(
+        &self,
+        schemes: AllowedSchemes,
+    ) -> (Client, actix_web::test::TestServer) {
+        let replay_cache = ReplayCache::new(
+            self.env_options.api_requests_caches_size,
+            self.env_options.api_replay_window,
+        );
+        self.start_server(move |_cfg| signatures::api_scope(replay_cache.clone(), schemes.clone()))
+    }
+
     /// Creates several transactions and the corresponding executed operations.
     pub fn gen_zk_txs(fee: u64) -> Vec<(ZkSyncTx, ExecutedOperations)> {
         let from = ZkSyncAccount::rand();
@@ -258,3 +283,21 @@ impl TestServerConfig {
         Ok(())
     }
 }
+
+/// Creates a transfer signed by the deterministic test key, returning it along with the key.
+pub fn signed_transfer() -> (Transfer, PrivateKey) {
+    let mut rng = XorShiftRng::from_seed([1, 2, 3, 4]);
+    let private_key = priv_key_from_fs(rng.gen());
+    let transfer = Transfer::new_signed(
+        1,
+        Address::from(rng.gen::<[u8; 20]>()),
+        Address::from(rng.gen::<[u8; 20]>()),
+        0,
+        1_000u64.into(),
+        10u64.into(),
+        0,
+        &private_key,
+    )
+    .unwrap();
+    (transfer, private_key)
+}
@@ -5,16 +5,27 @@
 // External uses
 use actix_web::{
     web::{self, Json},
-    Scope,
+    HttpRequest, Scope,
 };
+use num::BigUint;
 use serde::{Deserialize, Serialize};
 
 // Workspace uses
-use zksync_types::{tx::TxEthSignature, tx::TxHash, ZkSyncTx};
+use zksync_types::{
+    tx::{net_deltas, total_fees, TxEthSignature, TxHash},
+    Address, Nonce, TokenId, ZkSyncTx,
+};
+use zksync_utils::BigUintSerdeAsRadix10Str;
 
 // Local uses
-use super::{client::Client, client::ClientError, Error as ApiError, JsonResult};
-use crate::api_server::tx_sender::{SubmitError, TxSender};
+use super::{
+    auth::RequestAuthenticator, client::Client, client::ClientError, decode_json_body,
+    Error as ApiError, JsonResult,
+};
+use crate::{
+    api_server::tx_sender::{SubmitError, TxSender},
+    tx_error::TxAddError,
+};
 
 #[derive(Debug, Clone, Copy)]
 pub enum SumbitErrorCode {
@@ -23,6 +34,9 @@ pub enum SumbitErrorCode {
     UnsupportedFastProcessing = 103,
     IncorrectTx = 104,
     TxAdd = 105,
+    ZeroAmount = 106,
+    MultiTransferUnsupported = 107,
+    FeeRecipientUnsupported = 108,
 
     Internal = 110,
     CommunicationCoreServer = 111,
@@ -37,6 +51,9 @@ impl SumbitErrorCode {
             SubmitError::UnsupportedFastProcessing => Self::UnsupportedFastProcessing,
             SubmitError::IncorrectTx(_) => Self::IncorrectTx,
             SubmitError::TxAdd(_) => Self::TxAdd,
+            SubmitError::ZeroAmount => Self::ZeroAmount,
+            SubmitError::MultiTransferUnsupported => Self::MultiTransferUnsupported,
+            SubmitError::FeeRecipientUnsupported => Self::FeeRecipientUnsupported,
             SubmitError::CommunicationCoreServer(_) => Self::CommunicationCoreServer,
             SubmitError::Internal(_) => Self::Internal,
             SubmitError::Other(_) => Self::Other,
@@ -54,6 +71,8 @@ impl From<SubmitError> for ApiError {
 
         if let SubmitError::Internal(err) = &inner {
             ApiError::internal(err)
+        } else if let SubmitError::TxAdd(TxAddError::VerificationTimeout) = &inner {
+            ApiError::service_unavailable(inner)
         } else {
             ApiError::bad_request(inner)
         }
@@ -65,11 +84,16 @@ impl From<SubmitError> for ApiError {
 #[derive(Clone)]
 struct ApiTransactionsData {
     tx_sender: TxSender,
+    /// Authenticator for the protected endpoints, disabled if not set.
+    authenticator: Option<RequestAuthenticator>,
 }
 
 impl ApiTransactionsData {
-    fn new(tx_sender: TxSender) -> Self {
-        Self { tx_sender }
+    fn new(tx_sender: TxSender, authenticator: Option<RequestAuthenticator>) -> Self {
+        Self {
+            tx_sender,
+            authenticator,
+        }
     }
 }
 
@@ -94,6 +118,103 @@ struct IncomingTxBatch {
     signature: Option<TxEthSignature>,
 }
 
+/// Amount of the token.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct TokenAmount {
+    pub token: TokenId,
+    #[serde(with = "BigUintSerdeAsRadix10Str")]
+    pub amount: BigUint,
+}
+
+/// Funds sent by the transaction to a single recipient.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct TxOutputEffect {
+    /// Recipient of the funds. For withdrawals it is an Ethereum address.
+    pub recipient: Address,
+    pub token: TokenId,
+    #[serde(with = "BigUintSerdeAsRadix10Str")]
+    pub amount: BigUint,
+}
+
+/// Effects of the transaction derived from the transaction itself, without
+/// taking the state of the accounts into account.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct TxEffects {
+    pub tx_hash: TxHash,
+    /// Account the transaction is sent on behalf of (see `ZkSyncTx::account`).
+    pub sender: Address,
+    /// Funds sent by the transaction. Forced exit amount depends on the account
+    /// state, so it's not listed.
+    pub outputs: Vec<TxOutputEffect>,
+    /// Fee paid for the transaction, if any.
+    pub fee: Option<TokenAmount>,
+    /// Total amount debited from the sender in every token, including the fee.
+    pub total_debit: Vec<TokenAmount>,
+    /// Nonce of the sender after the transaction execution.
+    pub resulting_nonce: Nonce,
+}
+
+impl TxEffects {
+    fn new(tx: &ZkSyncTx) -> Result<Self, ApiError> {
+        let txs = std::slice::from_ref(tx);
+        let sender = tx.account();
+
+        let outputs = match tx {
+            ZkSyncTx::Transfer(tx) => vec![TxOutputEffect {
+                recipient: tx.to,
+                token: tx.token,
+                amount: tx.amount.clone(),
+            }],
+            ZkSyncTx::Withdraw(tx) => vec![TxOutputEffect {
+                recipient: tx.to,
+                token: tx.token,
+                amount: tx.amount.clone(),
+            }],
+            ZkSyncTx::MultiTransfer(tx) => tx
+                .outputs
+                .iter()
+                .map(|output| TxOutputEffect {
+                    recipient: output.to,
+                    token: output.token,
+                    amount: output.amount.clone().into(),
+                })
+                .collect(),
+            ZkSyncTx::Close(_) | ZkSyncTx::ChangePubKey(_) | ZkSyncTx::ForcedExit(_) => Vec::new(),
+        };
+
+        let fee = total_fees(txs)
+            .into_iter()
+            .next()
+            .map(|(token, amount)| TokenAmount { token, amount });
+
+        // Sender balance can only decrease, so the negated deltas are always non-negative.
+        let mut total_debit: Vec<_> = net_deltas(txs)
+            .into_iter()
+            .filter(|((account, _), _)| *account == sender)
+            .filter_map(|((_, token), delta)| {
+                let amount = (-delta).to_biguint()?;
+                Some(TokenAmount { token, amount })
+            })
+            .collect();
+        total_debit.sort_by_key(|amount| amount.token);
+
+        let resulting_nonce = tx
+            .nonce()
+            .checked_add(1)
+            .ok_or_else(|| ApiError::unprocessable_entity("Nonce of the transaction is too big"))?;
+
+        Ok(Self {
+            tx_hash: tx.hash(),
+            sender,
+            outputs,
+            fee,
+            total_debit,
+            resulting_nonce,
+        })
+    }
+}
+
 // Client implementation
 
 /// Transactions API part.
@@ -123,6 +244,11 @@ impl Client {
             .send()
             .await
     }
+
+    /// Previews the effects of the transaction without submitting it.
+    pub async fn simulate_tx(&self, tx: ZkSyncTx) -> Result<TxEffects, ClientError> {
+        self.post("transactions/simulate").body(&tx).send().await
+    }
 }
 
 // Server implementation
@@ -143,8 +269,15 @@ async fn submit_tx(
 
 async fn submit_tx_batch(
     data: web::Data<ApiTransactionsData>,
-    Json(body): Json<IncomingTxBatch>,
-) -> JsonResult<Vec<TxHash>> {
+    req: HttpRequest,
+    body: web::Bytes,
+) -> Result<Json<Vec<TxHash>>, actix_web::Error> {
+    // The signature is computed over the raw body, so it should be checked before parsing.
+    if let Some(authenticator) = &data.authenticator {
+        authenticator.authenticate(&req, &body)?;
+    }
+
+    let body: IncomingTxBatch = decode_json_body(&req, &body)?;
     let txs = body.txs.into_iter().zip(std::iter::repeat(None)).collect();
 
     let tx_hashes = data
@@ -156,13 +289,18 @@ async fn submit_tx_batch(
     Ok(Json(tx_hashes))
 }
 
-pub fn api_scope(tx_sender: TxSender) -> Scope {
-    let data = ApiTransactionsData::new(tx_sender);
+async fn simulate_tx(Json(tx): Json<ZkSyncTx>) -> JsonResult<TxEffects> {
+    TxEffects::new(&tx).map(Json)
+}
+
+pub fn api_scope(tx_sender: TxSender, authenticator: Option<RequestAuthenticator>) -> Scope {
+    let data = ApiTransactionsData::new(tx_sender, authenticator);
 
     web::scope("transactions")
         .data(data)
         .route("submit", web::post().to(submit_tx))
         .route("submit/batch", web::post().to(submit_tx_batch))
+        .route("simulate", web::post().to(simulate_tx))
 }
 
 #[cfg(test)]
@@ -172,9 +310,12 @@ mod tests {
     use bigdecimal::BigDecimal;
     use futures::{channel::mpsc, prelude::*};
     use num::BigUint;
-    use zksync_types::SignedZkSyncTx;
+    use zksync_types::{SignedZkSyncTx, Transfer};
 
-    use super::{super::test_utils::TestServerConfig, *};
+    use super::{
+        super::{auth::SIGNATURE_HEADER, test_utils::TestServerConfig, JSON_LIMIT},
+        *,
+    };
     use crate::{
         core_api_client::CoreApiClient,
         fee_ticker::{Fee, OutputFeeType::Withdraw, TickerRequest},
@@ -256,6 +397,12 @@ mod tests {
 
     impl TestServer {
         async fn new() -> anyhow::Result<(Client, Self)> {
+            Self::with_authenticator(None).await
+        }
+
+        async fn with_authenticator(
+            authenticator: Option<RequestAuthenticator>,
+        ) -> anyhow::Result<(Client, Self)> {
             let (core_client, core_server) = submit_txs_loopback();
 
             let cfg = TestServerConfig::default();
@@ -265,13 +412,16 @@ mod tests {
             let fee_ticker = dummy_fee_ticker();
 
             let (api_client, api_server) = cfg.start_server(move |cfg| {
-                api_scope(TxSender::with_client(
-                    core_client.clone(),
-                    cfg.pool.clone(),
-                    sign_verifier.clone(),
-                    fee_ticker.clone(),
-                    &cfg.env_options,
-                ))
+                api_scope(
+                    TxSender::with_client(
+                        core_client.clone(),
+                        cfg.pool.clone(),
+                        sign_verifier.clone(),
+                        fee_ticker.clone(),
+                        &cfg.env_options,
+                    ),
+                    authenticator.clone(),
+                )
             });
 
             Ok((
@@ -323,6 +473,25 @@ mod tests {
             .to_string()
             .contains("Transaction fee is too low"));
 
+        // Submit transaction with the custom fee recipient.
+        let mut transfer = Transfer::new(
+            1,
+            Address::repeat_byte(0x01),
+            Address::repeat_byte(0x02),
+            0,
+            1_000u64.into(),
+            10u64.into(),
+            5,
+            None,
+        );
+        transfer.fee_recipient = Some(Address::repeat_byte(0x03));
+        assert!(client
+            .submit_tx(transfer.into(), None, None)
+            .await
+            .unwrap_err()
+            .to_string()
+            .contains("Custom fee recipient is not supported yet"));
+
         // Submit correct transactions batch.
         let (txs, tx_hashes): (Vec<_>, Vec<_>) = TestServerConfig::gen_zk_txs(1_00)
             .into_iter()
@@ -347,4 +516,169 @@ mod tests {
         server.stop().await;
         Ok(())
     }
+
+    #[actix_rt::test]
+    async fn test_simulate_tx() -> anyhow::Result<()> {
+        let (client, server) = TestServer::new().await?;
+
+        let from = Address::repeat_byte(0x01);
+        let to = Address::repeat_byte(0x02);
+        let tx: ZkSyncTx =
+            Transfer::new(1, from, to, 0, 1_000u64.into(), 10u64.into(), 5, None).into();
+
+        assert_eq!(
+            client.simulate_tx(tx.clone()).await?,
+            TxEffects {
+                tx_hash: tx.hash(),
+                sender: from,
+                outputs: vec![TxOutputEffect {
+                    recipient: to,
+                    token: 0,
+                    amount: 1_000u64.into(),
+                }],
+                fee: Some(TokenAmount {
+                    token: 0,
+                    amount: 10u64.into(),
+                }),
+                total_debit: vec![TokenAmount {
+                    token: 0,
+                    amount: 1_010u64.into(),
+                }],
+                resulting_nonce: 6,
+            }
+        );
+
+        // The nonce of the sender can't be incremented further.
+        let tx: ZkSyncTx = Transfer::new(
+            1,
+            from,
+            to,
+            0,
+            1_000u64.into(),
+            10u64.into(),
+            Nonce::max_value(),
+            None,
+        )
+        .into();
+        assert!(client
+            .simulate_tx(tx)
+            .await
+            .unwrap_err()
+            .to_string()
+            .contains("Nonce of the transaction is too big"));
+
+        server.stop().await;
+        Ok(())
+    }
+
+    #[actix_rt::test]
+    async fn test_submit_tx_batch_authentication() -> anyhow::Result<()> {
+        let authenticator = RequestAuthenticator::new("secret");
+        let (client, server) = TestServer::with_authenticator(Some(authenticator.clone())).await?;
+
+        let (txs, tx_hashes): (Vec<_>, Vec<_>) = TestServerConfig::gen_zk_txs(1_00)
+            .into_iter()
+            .map(|(tx, _op)| {
+                let tx_hash = tx.hash();
+                (tx, tx_hash)
+            })
+            .unzip();
+
+        let signature: TxEthSignature = serde_json::from_value(
+            serde_json::json!({
+                "type": "EthereumSignature",
+                "signature": "0x080d5db7ab0ef71a31c2919cbe48e5a8c0b28812f8fefffff9231ba8b6d7396773780b783e65d214db162d1471854916f8608c84eba6ea0fbcbe19f9a8b9a8311b",
+            })
+        ).unwrap();
+        let body = serde_json::to_vec(&IncomingTxBatch {
+            txs,
+            signature: Some(signature),
+        })?;
+
+        let url = client.endpoint("transactions/submit/batch");
+        let http_client = reqwest::Client::new();
+
+        // Request without signature should be rejected.
+        let response = http_client
+            .post(&url)
+            .header(reqwest::header::CONTENT_TYPE, "application/json")
+            .body(body.clone())
+            .send()
+            .await?;
+        assert_eq!(response.status(), reqwest::StatusCode::UNAUTHORIZED);
+
+        // Request signed with the wrong secret should be rejected.
+        let response = http_client
+            .post(&url)
+            .header(reqwest::header::CONTENT_TYPE, "application/json")
+            .header(
+                SIGNATURE_HEADER,
+                RequestAuthenticator::new("wrong").sign(&body),
+            )
+            .body(body.clone())
+            .send()
+            .await?;
+        assert_eq!(response.status(), reqwest::StatusCode::UNAUTHORIZED);
+
+        // Correctly signed request should be accepted.
+        let response = http_client
+            .post(&url)
+            .header(reqwest::header::CONTENT_TYPE, "application/json")
+            .header(SIGNATURE_HEADER, authenticator.sign(&body))
+            .body(body)
+            .send()
+            .await?;
+        assert!(response.status().is_success());
+        assert_eq!(response.json::<Vec<TxHash>>().await?, tx_hashes);
+
+        server.stop().await;
+        Ok(())
+    }
+
+    #[actix_rt::test]
+    async fn test_submit_tx_batch_json_errors() -> anyhow::Result<()> {
+        let (client, server) = TestServer::new().await?;
+
+        let txs: Vec<_> = TestServerConfig::gen_zk_txs(1)
+            .into_iter()
+            .map(|(tx, _op)| tx)
+            .collect();
+        let body = serde_json::to_value(&IncomingTxBatch {
+            txs,
+            signature: None,
+        })?;
+
+        let url = client.endpoint("transactions/submit/batch");
+        let post = |content_type: &str, body: Vec<u8>| {
+            reqwest::Client::new()
+                .post(&url)
+                .header(reqwest::header::CONTENT_TYPE, content_type)
+                .body(body)
+                .send()
+        };
+
+        // Body is checked the same way as by the `Json` extractor.
+        let response = post("text/plain", serde_json::to_vec(&body)?).await?;
+        assert_eq!(response.status(), reqwest::StatusCode::BAD_REQUEST);
+
+        let response = post("application/json", b"{\"txs\":".to_vec()).await?;
+        assert_eq!(response.status(), reqwest::StatusCode::BAD_REQUEST);
+
+        let mut truncated = body.clone();
+        let signature = truncated["txs"][0]["signature"]["signature"]
+            .as_str()
+            .unwrap()
+            .to_owned();
+        truncated["txs"][0]["signature"]["signature"] = signature[..signature.len() - 2].into();
+        let response = post("application/json", serde_json::to_vec(&truncated)?).await?;
+        assert_eq!(response.status(), reqwest::StatusCode::UNPROCESSABLE_ENTITY);
+
+        let mut oversized = serde_json::to_vec(&body)?;
+        oversized.resize(JSON_LIMIT + 1, b' ');
+        let response = post("application/json", oversized).await?;
+        assert_eq!(response.status(), reqwest::StatusCode::PAYLOAD_TOO_LARGE);
+
+        server.stop().await;
+        Ok(())
+    }
 }
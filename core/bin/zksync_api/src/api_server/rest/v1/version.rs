@@ -0,0 +1,84 @@
+//! Version part of API implementation.
+
+// Built-in uses
+
+// External uses
+use actix_web::{web, Scope};
+use chrono::{DateTime, TimeZone, Utc};
+use serde::{Deserialize, Serialize};
+
+// Workspace uses
+
+// Local uses
+use super::{
+    client::{self, Client},
+    Json,
+};
+
+// Data transfer objects.
+
+/// Information about the deployed server binary.
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct VersionInfo {
+    /// Version of the `zksync_api` package.
+    pub version: String,
+    /// Hash of the commit the binary was built from, or `unknown`.
+    pub git_sha: String,
+    pub build_time: DateTime<Utc>,
+}
+
+impl VersionInfo {
+    /// Returns the build information embedded by the build script.
+    pub fn current() -> Self {
+        let build_timestamp = env!("ZKSYNC_BUILD_TIMESTAMP")
+            .parse()
+            .expect("build timestamp should be an integer");
+
+        Self {
+            version: env!("CARGO_PKG_VERSION").to_owned(),
+            git_sha: env!("ZKSYNC_GIT_SHA").to_owned(),
+            build_time: Utc.timestamp(build_timestamp, 0),
+        }
+    }
+}
+
+// Client implementation
+
+/// Version API part.
+impl Client {
+    pub async fn version(&self) -> client::Result<VersionInfo> {
+        self.get("version").send().await
+    }
+}
+
+// Server implementation
+
+async fn version(data: web::Data<VersionInfo>) -> Json<VersionInfo> {
+    Json(data.get_ref().clone())
+}
+
+pub fn api_scope() -> Scope {
+    web::scope("version")
+        .data(VersionInfo::current())
+        .route("", web::get().to(version))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{super::test_utils::TestServerConfig, *};
+
+    #[actix_rt::test]
+    async fn test_version_scope() -> anyhow::Result<()> {
+        let cfg = TestServerConfig::default();
+        let (client, server) = cfg.start_server(|_cfg| api_scope());
+
+        let info = client.version().await?;
+        assert_eq!(info, VersionInfo::current());
+        assert_eq!(info.version, env!("CARGO_PKG_VERSION"));
+        assert!(!info.git_sha.is_empty());
+
+        server.stop().await;
+        Ok(())
+    }
+}
@@ -19,6 +19,10 @@ pub enum RpcErrorCodes {
     AccountCloseDisabled = 301,
     OperationsLimitReached = 302,
     UnsupportedFastProcessing = 303,
+    ZeroAmount = 304,
+    MultiTransferUnsupported = 305,
+    VerificationTimeout = 306,
+    FeeRecipientUnsupported = 307,
 }
 
 impl From<TxAddError> for RpcErrorCodes {
@@ -37,6 +41,7 @@ impl From<TxAddError> for RpcErrorCodes {
             TxAddError::EmptyBatch => Self::Other,
             TxAddError::BatchTooBig => Self::Other,
             TxAddError::BatchWithdrawalsOverload => Self::Other,
+            TxAddError::VerificationTimeout => Self::VerificationTimeout,
         }
     }
 }
@@ -68,6 +73,21 @@ impl From<SubmitError> for jsonrpc_core::Error {
                 message,
                 data: None,
             },
+            SubmitError::ZeroAmount => Self {
+                code: RpcErrorCodes::ZeroAmount.into(),
+                message: "Transaction amount must not be zero.".to_string(),
+                data: None,
+            },
+            SubmitError::MultiTransferUnsupported => Self {
+                code: RpcErrorCodes::MultiTransferUnsupported.into(),
+                message: "MultiTransfer tx is not supported yet.".to_string(),
+                data: None,
+            },
+            SubmitError::FeeRecipientUnsupported => Self {
+                code: RpcErrorCodes::FeeRecipientUnsupported.into(),
+                message: "Custom fee recipient is not supported yet.".to_string(),
+                data: None,
+            },
             SubmitError::TxAdd(inner) => Self {
                 code: RpcErrorCodes::from(inner).into(),
                 message: inner.to_string(),
@@ -42,6 +42,8 @@ pub struct TxSender {
     /// Mimimum age of the account for `ForcedExit` operations to be allowed.
     pub forced_exit_minimum_account_age: chrono::Duration,
     pub enforce_pubkey_change_fee: bool,
+    /// Whether transfers and withdrawals with zero amount should be rejected.
+    pub reject_zero_amount_txs: bool,
 }
 
 #[derive(Debug, Error)]
@@ -54,6 +56,12 @@ pub enum SubmitError {
     UnsupportedFastProcessing,
     #[error("Incorrect transaction: {0}.")]
     IncorrectTx(String),
+    #[error("Transaction amount must not be zero.")]
+    ZeroAmount,
+    #[error("MultiTransfer tx is not supported yet.")]
+    MultiTransferUnsupported,
+    #[error("Custom fee recipient is not supported yet.")]
+    FeeRecipientUnsupported,
     #[error("Transaction adding error: {0}.")]
     TxAdd(TxAddError),
 
@@ -120,6 +128,7 @@ impl TxSender {
         config_options: &ConfigurationOptions,
     ) -> Self {
         let enforce_pubkey_change_fee = config_options.enforce_pubkey_change_fee;
+        let reject_zero_amount_txs = config_options.reject_zero_amount_txs;
         let forced_exit_minimum_account_age =
             chrono::Duration::from_std(config_options.forced_exit_minimum_account_age)
                 .expect("Unable to convert std::Duration to chrono::Duration");
@@ -133,6 +142,7 @@ impl TxSender {
 
             enforce_pubkey_change_fee,
             forced_exit_minimum_account_age,
+            reject_zero_amount_txs,
         }
     }
 
@@ -146,6 +156,18 @@ impl TxSender {
             return Err(SubmitError::AccountCloseDisabled);
         }
 
+        if tx.is_multi_transfer() {
+            return Err(SubmitError::MultiTransferUnsupported);
+        }
+
+        if tx.fee_recipient().is_some() {
+            return Err(SubmitError::FeeRecipientUnsupported);
+        }
+
+        if self.reject_zero_amount_txs && is_zero_amount(&tx) {
+            return Err(SubmitError::ZeroAmount);
+        }
+
         if let ZkSyncTx::ForcedExit(forced_exit) = &tx {
             self.check_forced_exit(forced_exit).await?;
         }
@@ -227,6 +249,18 @@ impl TxSender {
             return Err(SubmitError::AccountCloseDisabled);
         }
 
+        if txs.iter().any(|tx| tx.0.is_multi_transfer()) {
+            return Err(SubmitError::MultiTransferUnsupported);
+        }
+
+        if txs.iter().any(|tx| tx.0.fee_recipient().is_some()) {
+            return Err(SubmitError::FeeRecipientUnsupported);
+        }
+
+        if self.reject_zero_amount_txs && txs.iter().any(|tx| is_zero_amount(&tx.0)) {
+            return Err(SubmitError::ZeroAmount);
+        }
+
         // Checking fees data
         let mut required_total_usd_fee = BigDecimal::from(0);
         let mut provided_total_usd_fee = BigDecimal::from(0);
@@ -424,6 +458,15 @@ impl TxSender {
     }
 }
 
+/// Returns `true` if the transaction is a transfer or withdrawal of a zero amount.
+fn is_zero_amount(tx: &ZkSyncTx) -> bool {
+    match tx {
+        ZkSyncTx::Transfer(tx) => tx.is_zero_amount(),
+        ZkSyncTx::Withdraw(tx) => tx.is_zero_amount(),
+        _ => false,
+    }
+}
+
 async fn send_verify_request_and_recv(
     request: VerifyTxSignatureRequest,
     mut req_channel: mpsc::Sender<VerifyTxSignatureRequest>,
@@ -5,7 +5,13 @@
 //! transactions signatures.
 
 // Built-in uses
-use std::time::Instant;
+use std::{
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+    time::{Duration, Instant},
+};
 
 // External uses
 use futures::{
@@ -16,7 +22,9 @@ use tokio::runtime::{Builder, Handle};
 // Workspace uses
 use zksync_types::{tx::TxEthSignature, SignedZkSyncTx, ZkSyncTx};
 // Local uses
-use crate::{eth_checker::EthereumChecker, tx_error::TxAddError};
+use crate::{
+    eth_checker::EthereumChecker, tx_error::TxAddError, utils::shared_lru_cache::SharedLruCache,
+};
 use zksync_config::ConfigurationOptions;
 use zksync_types::tx::EthSignData;
 use zksync_utils::panic_notify::ThreadPanicNotify;
@@ -41,14 +49,25 @@ pub struct VerifiedTx(TxVariant);
 impl VerifiedTx {
     /// Checks the (batch of) transaction(s) correctness by verifying its
     /// Ethereum signature (if required) and `ZKSync` signature.
+    ///
+    /// `ZKSync` signature verification is aborted if it takes longer than `timeout`.
     pub async fn verify(
         request: &mut VerifyTxSignatureRequest,
         eth_checker: &EthereumChecker<web3::transports::Http>,
+        cache: &VerificationCache,
+        timeout: Duration,
     ) -> Result<Self, TxAddError> {
         verify_eth_signature(request, eth_checker).await?;
-        verify_tx_correctness(&mut request.tx)?;
 
-        Ok(Self(request.tx.clone()))
+        let mut tx = request.tx.clone();
+        let cache = cache.clone();
+        let tx = run_with_deadline(
+            move || verify_tx_correctness(&mut tx, &cache).map(|_| tx),
+            timeout,
+        )
+        .await?;
+
+        Ok(Self(tx))
     }
 
     /// Creates a verified wrapper without actually verifying the original data.
@@ -197,17 +216,85 @@ async fn verify_eth_signature_txs_batch(
     Ok(())
 }
 
+/// Thread-safe LRU cache of the zkSync transactions signature checks results.
+///
+/// The same transaction may be verified several times (e.g. when a client retries
+/// the submission), and the cache allows to skip the repeated signature verification.
+/// Transactions are identified by the signed message along with the signature, so any
+/// change in the signed data results in a cache miss, while the unsigned metadata
+/// (e.g. `label`) doesn't affect the check.
+///
+/// Only the restored signer is cached: the rest of the correctness checks depend on the
+/// data which is not covered by the signed bytes, so they are performed every time.
+#[derive(Debug, Clone)]
+pub struct VerificationCache {
+    /// Checked transactions holding the restored signers.
+    results: SharedLruCache<Vec<u8>, ZkSyncTx>,
+    hits: Arc<AtomicU64>,
+    misses: Arc<AtomicU64>,
+}
+
+impl VerificationCache {
+    /// Creates a new cache that holds at most `capacity` results.
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            results: SharedLruCache::new(capacity),
+            hits: Arc::default(),
+            misses: Arc::default(),
+        }
+    }
+
+    /// Returns the number of checks which reused the cached signer.
+    pub fn hits(&self) -> u64 {
+        self.hits.load(Ordering::Relaxed)
+    }
+
+    /// Returns the number of checks that required the signature verification.
+    pub fn misses(&self) -> u64 {
+        self.misses.load(Ordering::Relaxed)
+    }
+
+    /// Checks the transaction correctness via `ZkSyncTx::check_correctness`, reusing
+    /// the cached signer if it is available.
+    ///
+    /// On a cache hit the signer restored by the previous check is copied into `tx`
+    /// (see `ZkSyncTx::copy_cached_signer`), so only the signature verification is skipped.
+    pub fn check_correctness(&self, tx: &mut ZkSyncTx) -> bool {
+        let mut key = tx.signing_message();
+        key.extend_from_slice(&tx.signature().to_bytes());
+
+        let hit =
+            matches!(self.results.get(&key), Some(checked) if tx.copy_cached_signer(&checked));
+        if hit {
+            self.hits.fetch_add(1, Ordering::Relaxed);
+            metrics::counter!("signature_checker.verification_cache.hits", 1);
+        } else {
+            self.misses.fetch_add(1, Ordering::Relaxed);
+            metrics::counter!("signature_checker.verification_cache.misses", 1);
+        }
+
+        let valid = tx.check_correctness();
+        if !hit {
+            self.results.insert(key, tx.clone());
+        }
+        valid
+    }
+}
+
 /// Verifies the correctness of the ZKSync transaction(s) (including the
 /// signature check).
-fn verify_tx_correctness(tx: &mut TxVariant) -> Result<(), TxAddError> {
+fn verify_tx_correctness(tx: &mut TxVariant, cache: &VerificationCache) -> Result<(), TxAddError> {
     match tx {
         TxVariant::Tx(tx) => {
-            if !tx.tx.check_correctness() {
+            if !cache.check_correctness(&mut tx.tx) {
                 return Err(TxAddError::IncorrectTx);
             }
         }
         TxVariant::Batch(batch, _) => {
-            if batch.iter_mut().any(|tx| !tx.tx.check_correctness()) {
+            if batch
+                .iter_mut()
+                .any(|tx| !cache.check_correctness(&mut tx.tx))
+            {
                 return Err(TxAddError::IncorrectTx);
             }
         }
@@ -215,6 +302,29 @@ fn verify_tx_correctness(tx: &mut TxVariant) -> Result<(), TxAddError> {
     Ok(())
 }
 
+/// Runs the CPU-bound verification routine on the blocking threadpool, returning
+/// `TxAddError::VerificationTimeout` if it doesn't finish within `timeout`.
+///
+/// Note that the routine itself cannot be interrupted, so the thread remains busy until
+/// it finishes, but the request is answered in time and its worker is not blocked.
+async fn run_with_deadline<F, T>(verify: F, timeout: Duration) -> Result<T, TxAddError>
+where
+    F: FnOnce() -> Result<T, TxAddError> + Send + 'static,
+    T: Send + 'static,
+{
+    match tokio::time::timeout(timeout, tokio::task::spawn_blocking(verify)).await {
+        Ok(Ok(result)) => result,
+        Ok(Err(join_err)) => {
+            log::error!("Signature verification task failed: {}", join_err);
+            Err(TxAddError::Other)
+        }
+        Err(_elapsed) => {
+            metrics::counter!("signature_checker.verification_timeouts", 1);
+            Err(TxAddError::VerificationTimeout)
+        }
+    }
+}
+
 /// Request for the signature check.
 #[derive(Debug)]
 pub struct VerifyTxSignatureRequest {
@@ -234,6 +344,8 @@ pub fn start_sign_checker_detached(
     let web3 = web3::Web3::new(transport);
 
     let eth_checker = EthereumChecker::new(web3, config_options.contract_eth_addr);
+    let cache = VerificationCache::new(config_options.signature_verification_cache_size);
+    let timeout = config_options.signature_verification_timeout;
 
     /// Main signature check requests handler.
     /// Basically it receives the requests through the channel and verifies signatures,
@@ -242,11 +354,14 @@ pub fn start_sign_checker_detached(
         handle: Handle,
         mut input: mpsc::Receiver<VerifyTxSignatureRequest>,
         eth_checker: EthereumChecker<web3::transports::Http>,
+        cache: VerificationCache,
+        timeout: Duration,
     ) {
         while let Some(mut request) = input.next().await {
             let eth_checker = eth_checker.clone();
+            let cache = cache.clone();
             handle.spawn(async move {
-                let resp = VerifiedTx::verify(&mut request, &eth_checker).await;
+                let resp = VerifiedTx::verify(&mut request, &eth_checker, &cache, timeout).await;
 
                 request.response.send(resp).unwrap_or_default();
             });
@@ -264,7 +379,118 @@ pub fn start_sign_checker_detached(
                 .build()
                 .expect("failed to build runtime for signature processor");
             let handle = runtime.handle().clone();
-            runtime.block_on(checker_routine(handle, input, eth_checker));
+            runtime.block_on(checker_routine(handle, input, eth_checker, cache, timeout));
         })
         .expect("failed to start signature checker thread");
 }
+
+#[cfg(test)]
+mod tests {
+    use num::BigUint;
+    use zksync_types::{
+        helpers::{closest_packable_token_amount, is_token_amount_packable},
+        Transfer,
+    };
+
+    use super::*;
+    use crate::api_server::rest::v1::test_utils::signed_transfer;
+
+    #[test]
+    fn verification_cache_eviction() {
+        let (transfer, private_key) = signed_transfer();
+        let mut first = ZkSyncTx::from(transfer);
+        let (mut second, _) = first.with_nonce(1).sign_and_check(&private_key);
+
+        let cache = VerificationCache::new(1);
+
+        assert!(cache.check_correctness(&mut first));
+        assert_eq!((cache.hits(), cache.misses()), (0, 1));
+
+        // Second check of the same transaction is answered by the cache.
+        assert!(cache.check_correctness(&mut first));
+        assert_eq!((cache.hits(), cache.misses()), (1, 1));
+
+        // Unsigned metadata doesn't affect the check.
+        let mut labeled = first.clone();
+        if let ZkSyncTx::Transfer(tx) = &mut labeled {
+            tx.label = Some("retry".to_owned());
+        }
+        assert!(cache.check_correctness(&mut labeled));
+        assert_eq!((cache.hits(), cache.misses()), (2, 1));
+
+        // Cache can hold only one result, so the first one is evicted.
+        assert!(cache.check_correctness(&mut second));
+        assert!(cache.check_correctness(&mut first));
+        assert_eq!((cache.hits(), cache.misses()), (2, 3));
+
+        // Failed signature checks are cached as well.
+        let mut incorrect = match &second {
+            ZkSyncTx::Transfer(transfer) => ZkSyncTx::from(Transfer::new(
+                transfer.account_id,
+                transfer.from,
+                transfer.to,
+                transfer.token,
+                2_000u64.into(),
+                transfer.fee.clone(),
+                transfer.nonce,
+                Some(transfer.signature.clone()),
+            )),
+            _ => unreachable!(),
+        };
+        assert!(!cache.check_correctness(&mut incorrect));
+        assert!(!cache.check_correctness(&mut incorrect));
+        assert_eq!((cache.hits(), cache.misses()), (3, 4));
+    }
+
+    #[test]
+    fn verification_cache_reruns_checks() {
+        let (transfer, private_key) = signed_transfer();
+
+        // Unpackable amount has the same signed bytes as the closest packable one.
+        let unpackable = BigUint::from(10_000_000_000_001u64);
+        assert!(!is_token_amount_packable(&unpackable));
+        let packable = Transfer::new_signed(
+            transfer.account_id,
+            transfer.from,
+            transfer.to,
+            transfer.token,
+            closest_packable_token_amount(&unpackable),
+            transfer.fee.clone(),
+            transfer.nonce,
+            &private_key,
+        )
+        .unwrap();
+        let mut incorrect = packable.clone();
+        incorrect.amount = unpackable;
+        assert_eq!(incorrect.get_bytes(), packable.get_bytes());
+
+        let cache = VerificationCache::new(1);
+        assert!(cache.check_correctness(&mut ZkSyncTx::from(packable)));
+
+        // Signer is reused, but the amount is still checked.
+        assert!(!cache.check_correctness(&mut ZkSyncTx::from(incorrect)));
+        assert_eq!((cache.hits(), cache.misses()), (1, 1));
+    }
+
+    #[tokio::test]
+    async fn verification_deadline() {
+        let timeout = Duration::from_millis(100);
+
+        let fast = run_with_deadline(|| Ok(42), timeout).await;
+        assert_eq!(fast.unwrap(), 42);
+
+        let failed = run_with_deadline(|| Err::<(), _>(TxAddError::IncorrectTx), timeout).await;
+        assert!(matches!(failed, Err(TxAddError::IncorrectTx)));
+
+        // Mocked verifier spinning on a pathological input.
+        let slow = run_with_deadline(
+            || {
+                std::thread::sleep(Duration::from_secs(1));
+                Ok(())
+            },
+            timeout,
+        )
+        .await;
+        assert!(matches!(slow, Err(TxAddError::VerificationTimeout)));
+    }
+}
@@ -1,3 +1,4 @@
 pub mod metrics_counter;
+pub mod replay_cache;
 pub mod shared_lru_cache;
 pub mod token_db_cache;
@@ -0,0 +1,79 @@
+use lru_cache::LruCache;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use zksync_types::tx::TxHash;
+
+/// `ReplayCache` remembers the hashes of the recently seen transactions in order
+/// to reject exact duplicates.
+///
+/// A transaction is considered a duplicate if the same hash was seen less than `window`
+/// ago. The cache is bounded: if it is full, the least recently seen hash is evicted.
+/// Cloned instances share the same storage.
+#[derive(Clone, Debug)]
+pub struct ReplayCache {
+    seen: Arc<Mutex<LruCache<TxHash, Instant>>>,
+    window: Duration,
+}
+
+impl ReplayCache {
+    pub fn new(capacity: usize, window: Duration) -> Self {
+        Self {
+            seen: Arc::new(Mutex::new(LruCache::new(capacity))),
+            window,
+        }
+    }
+
+    /// Records the transaction hash and returns `true` if it was already seen within the window.
+    pub fn check_and_insert(&self, hash: TxHash) -> bool {
+        self.check_and_insert_at(hash, Instant::now())
+    }
+
+    fn check_and_insert_at(&self, hash: TxHash, now: Instant) -> bool {
+        let mut seen = self.seen.lock().unwrap();
+
+        let duplicate = seen
+            .get_mut(&hash)
+            .map(|seen_at| now.duration_since(*seen_at) < self.window)
+            .unwrap_or(false);
+        // Duplicates don't prolong the window.
+        if !duplicate {
+            seen.insert(hash, now);
+        }
+        duplicate
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hash(byte: u8) -> TxHash {
+        TxHash::from_slice(&[byte; 32]).unwrap()
+    }
+
+    #[test]
+    fn replay_window() {
+        let window = Duration::from_secs(10);
+        let cache = ReplayCache::new(2, window);
+        let start = Instant::now();
+
+        assert!(!cache.check_and_insert_at(hash(1), start));
+        assert!(cache.check_and_insert_at(hash(1), start + window / 2));
+        // Once the window has passed, the transaction is accepted again.
+        assert!(!cache.check_and_insert_at(hash(1), start + window));
+        assert!(cache.check_and_insert_at(hash(1), start + window));
+    }
+
+    #[test]
+    fn replay_capacity() {
+        let cache = ReplayCache::new(2, Duration::from_secs(10));
+        let now = Instant::now();
+
+        assert!(!cache.check_and_insert_at(hash(1), now));
+        assert!(!cache.check_and_insert_at(hash(2), now));
+        assert!(!cache.check_and_insert_at(hash(3), now));
+        // The oldest hash was evicted.
+        assert!(!cache.check_and_insert_at(hash(1), now));
+        assert!(cache.check_and_insert_at(hash(3), now));
+    }
+}
@@ -4,6 +4,10 @@
 pub use web3::types::{Address, Log, TransactionReceipt, H160, H256, U128, U256};
 
 /// Unique identifier of the token in the zkSync network.
+///
+/// Token ID is encoded as 2 big-endian bytes in the transaction data, but only IDs up to
+/// `zksync_crypto::params::max_token_id()` can be processed by the circuit, so transactions
+/// with greater token IDs are rejected as incorrect.
 pub type TokenId = u16;
 /// Unique identifier of the account in the zkSync network.
 pub type AccountId = u32;
@@ -1,5 +1,5 @@
 // Built-in deps
-use std::{env, net::SocketAddr, str::FromStr, time::Duration};
+use std::{env, net::SocketAddr, path::PathBuf, str::FromStr, time::Duration};
 // External uses
 use url::Url;
 // Workspace uses
@@ -152,6 +152,10 @@ pub struct ConfigurationOptions {
     pub prover_server_address: SocketAddr,
     pub confirmations_for_eth_event: u64,
     pub api_requests_caches_size: usize,
+    /// Capacity of the LRU cache with the zkSync transactions verification results.
+    pub signature_verification_cache_size: usize,
+    /// Maximum time allowed for the zkSync signature verification of a single request.
+    pub signature_verification_timeout: Duration,
     pub available_block_chunk_sizes: Vec<usize>,
     pub max_number_of_withdrawals_per_block: usize,
     pub eth_watch_poll_interval: Duration,
@@ -165,6 +169,24 @@ pub struct ConfigurationOptions {
     pub ticker_fast_processing_coeff: f64,
     pub forced_exit_minimum_account_age: Duration,
     pub enforce_pubkey_change_fee: bool,
+    /// Whether transfers and withdrawals with zero amount should be rejected by the API.
+    pub reject_zero_amount_txs: bool,
+    /// Shared secret used to authenticate requests to the protected API endpoints.
+    /// Authentication is disabled if not set.
+    pub api_requests_signing_secret: Option<String>,
+    /// Path to the file in which raw REST API request bodies are recorded.
+    /// Audit is disabled if not set.
+    pub api_audit_log: Option<PathBuf>,
+    /// Whether to emit a structured access log line for every REST API request.
+    pub api_access_log: bool,
+    /// Time window in which an exact duplicate of the already checked transaction is rejected.
+    pub api_replay_window: Duration,
+    /// Names of the signature schemes accepted by the `signatures/verify_detached` REST API
+    /// endpoint. All the enabled schemes are accepted if not set.
+    pub api_verify_detached_schemes: Option<Vec<String>>,
+    /// Names of the signature schemes accepted by the `signatures/check_string_signature`
+    /// REST API endpoint. All the enabled schemes are accepted if not set.
+    pub api_check_string_signature_schemes: Option<Vec<String>>,
 }
 
 impl ConfigurationOptions {
@@ -204,6 +226,13 @@ impl ConfigurationOptions {
             prover_server_address: parse_env("PROVER_SERVER_BIND"),
             confirmations_for_eth_event: parse_env("CONFIRMATIONS_FOR_ETH_EVENT"),
             api_requests_caches_size: parse_env("API_REQUESTS_CACHES_SIZE"),
+            signature_verification_cache_size: parse_env_if_exists(
+                "SIGNATURE_VERIFICATION_CACHE_SIZE",
+            )
+            .unwrap_or(10_000),
+            signature_verification_timeout: Duration::from_millis(
+                parse_env_if_exists("SIGNATURE_VERIFICATION_TIMEOUT_MS").unwrap_or(5_000),
+            ),
             available_block_chunk_sizes,
             max_number_of_withdrawals_per_block: parse_env("MAX_NUMBER_OF_WITHDRAWALS_PER_BLOCK"),
             eth_watch_poll_interval: Duration::from_millis(parse_env::<u64>(
@@ -219,10 +248,32 @@ impl ConfigurationOptions {
             forced_exit_minimum_account_age,
             enforce_pubkey_change_fee: parse_env_if_exists("ENFORCE_PUBKEY_CHANGE_FEE")
                 .unwrap_or(true),
+            reject_zero_amount_txs: parse_env_if_exists("REJECT_ZERO_AMOUNT_TXS").unwrap_or(false),
+            api_requests_signing_secret: parse_env_if_exists("API_REQUESTS_SIGNING_SECRET"),
+            api_audit_log: parse_env_if_exists("API_AUDIT_LOG"),
+            api_access_log: parse_env_if_exists("API_ACCESS_LOG").unwrap_or(false),
+            api_replay_window: Duration::from_secs(
+                parse_env_if_exists("API_REPLAY_WINDOW_SECS").unwrap_or(60),
+            ),
+            api_verify_detached_schemes: parse_list_if_exists("API_VERIFY_DETACHED_SCHEMES"),
+            api_check_string_signature_schemes: parse_list_if_exists(
+                "API_CHECK_STRING_SIGNATURE_SCHEMES",
+            ),
         }
     }
 }
 
+/// Parses the optional comma-separated list from the environment variable.
+fn parse_list_if_exists(name: &str) -> Option<Vec<String>> {
+    env::var(name).ok().map(|value| {
+        value
+            .split(',')
+            .map(|item| item.trim().to_owned())
+            .filter(|item| !item.is_empty())
+            .collect()
+    })
+}
+
 /// Possible block chunks sizes and corresponding setup powers of two,
 /// this is only parameters needed to create verifying contract.
 #[derive(Debug)]
@@ -8,7 +8,7 @@ use zksync_types::{
 };
 
 use crate::{
-    handler::TxHandler,
+    handler::{check_signer, TxHandler},
     state::{CollectedFee, OpSuccess, ZkSyncState},
 };
 
@@ -23,10 +23,11 @@ impl TxHandler<ChangePubKey> for ZkSyncState {
             tx.eth_signature.is_none() || tx.verify_eth_signature() == Some(account.address),
             "ChangePubKey Ethereum signature is incorrect"
         );
-        ensure!(
-            tx.verify_signature() == Some(tx.new_pk_hash.clone()),
-            "ChangePubKey zkSync signature is incorrect"
-        );
+        check_signer(
+            tx.verify_signature(),
+            &tx.new_pk_hash,
+            "ChangePubKey zkSync signature is incorrect",
+        )?;
         ensure!(
             account_id == tx.account_id,
             "ChangePubKey account id is incorrect"
@@ -5,7 +5,7 @@ use zksync_types::{AccountUpdate, AccountUpdates, ForcedExit, ForcedExitOp, PubK
 use zksync_utils::BigUintSerdeWrapper;
 
 use crate::{
-    handler::TxHandler,
+    handler::{check_signer, TxHandler},
     state::{CollectedFee, OpSuccess, ZkSyncState},
 };
 
@@ -17,10 +17,11 @@ impl TxHandler<ForcedExit> for ZkSyncState {
         let initiator_account = self
             .get_account(tx.initiator_account_id)
             .ok_or_else(|| format_err!("Initiator account does not exist"))?;
-        ensure!(
-            tx.verify_signature() == Some(initiator_account.pub_key_hash),
-            "ForcedExit signature is incorrect"
-        );
+        check_signer(
+            tx.verify_signature(),
+            &initiator_account.pub_key_hash,
+            "ForcedExit signature is incorrect",
+        )?;
 
         // Check the token ID correctness.
         ensure!(
@@ -1,5 +1,6 @@
 use crate::state::{CollectedFee, OpSuccess};
-use zksync_types::AccountUpdates;
+use anyhow::bail;
+use zksync_types::{tx::SignatureError, AccountUpdates, PubKeyHash};
 
 mod change_pubkey;
 mod close;
@@ -33,3 +34,27 @@ pub trait TxHandler<Tx> {
         op: &Self::Op,
     ) -> Result<(Option<CollectedFee>, AccountUpdates), anyhow::Error>;
 }
+
+/// Checks that the transaction was signed by the owner of the `expected` key.
+///
+/// If the signature itself is valid but was made by another key, both the recovered
+/// and the expected `PubKeyHash` values are appended to `error_msg`, so a change in the
+/// `PubKeyHash` derivation can be detected immediately.
+fn check_signer(
+    recovered: Option<PubKeyHash>,
+    expected: &PubKeyHash,
+    error_msg: &str,
+) -> Result<(), anyhow::Error> {
+    match recovered {
+        Some(recovered) if &recovered == expected => Ok(()),
+        Some(recovered) => bail!(
+            "{}: {}",
+            error_msg,
+            SignatureError::SignerMismatch {
+                recovered,
+                expected: expected.clone(),
+            }
+        ),
+        None => bail!("{}", error_msg),
+    }
+}
@@ -7,7 +7,7 @@ use zksync_types::{
 };
 
 use crate::{
-    handler::TxHandler,
+    handler::{check_signer, TxHandler},
     state::{CollectedFee, OpSuccess, TransferOutcome, ZkSyncState},
 };
 
@@ -30,10 +30,11 @@ impl TxHandler<Transfer> for ZkSyncState {
             from_account.pub_key_hash != PubKeyHash::default(),
             "Account is locked"
         );
-        ensure!(
-            tx.verify_signature() == Some(from_account.pub_key_hash),
-            "Transfer signature is incorrect"
-        );
+        check_signer(
+            tx.verify_signature(),
+            &from_account.pub_key_hash,
+            "Transfer signature is incorrect",
+        )?;
         ensure!(from == tx.account_id, "Transfer account id is incorrect");
 
         let outcome = if let Some((to, _)) = self.get_account_by_address(&tx.to) {
@@ -4,7 +4,7 @@ use zksync_crypto::params::{self, max_account_id};
 use zksync_types::{AccountUpdate, AccountUpdates, PubKeyHash, Withdraw, WithdrawOp, ZkSyncOp};
 
 use crate::{
-    handler::TxHandler,
+    handler::{check_signer, TxHandler},
     state::{CollectedFee, OpSuccess, ZkSyncState},
 };
 
@@ -23,10 +23,11 @@ impl TxHandler<Withdraw> for ZkSyncState {
             account.pub_key_hash != PubKeyHash::default(),
             "Account is locked"
         );
-        ensure!(
-            tx.verify_signature() == Some(account.pub_key_hash),
-            "withdraw signature is incorrect"
-        );
+        check_signer(
+            tx.verify_signature(),
+            &account.pub_key_hash,
+            "withdraw signature is incorrect",
+        )?;
         ensure!(
             account_id == tx.account_id,
             "Withdraw account id is incorrect"
@@ -245,6 +245,7 @@ impl ZkSyncState {
             ZkSyncTx::Close(tx) => self.apply_tx(*tx),
             ZkSyncTx::ChangePubKey(tx) => self.apply_tx(*tx),
             ZkSyncTx::ForcedExit(tx) => self.apply_tx(*tx),
+            ZkSyncTx::MultiTransfer(_) => anyhow::bail!("MultiTransfer op is not supported"),
         }
     }
 
@@ -318,6 +319,7 @@ impl ZkSyncState {
             ZkSyncTx::ChangePubKey(tx) => self.create_op(*tx).map(Into::into),
             ZkSyncTx::Close(_) => anyhow::bail!("Close op is disabled"),
             ZkSyncTx::ForcedExit(tx) => self.create_op(*tx).map(Into::into),
+            ZkSyncTx::MultiTransfer(_) => anyhow::bail!("MultiTransfer op is not supported"),
         }
     }
 
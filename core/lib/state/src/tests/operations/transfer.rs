@@ -230,3 +230,38 @@ fn invalid_account_id() {
 
     tb.test_tx_fail(transfer.into(), "Transfer account id is incorrect")
 }
+
+/// Check Transfer failure if the signature is valid, but made by the key
+/// of another account
+#[test]
+fn signer_mismatch() {
+    let token_id = 0;
+    let amount = BigUint::from(100u32);
+    let fee = BigUint::from(10u32);
+
+    let mut tb = PlasmaTestBuilder::new();
+
+    let (account_id, account, _sk) = tb.add_account(Unlocked);
+    let (_, to_account, to_sk) = tb.add_account(Unlocked);
+    tb.set_balance(account_id, token_id, &amount + &fee);
+
+    let transfer = Transfer::new_signed(
+        account_id,
+        account.address,
+        to_account.address,
+        token_id,
+        amount,
+        fee,
+        account.nonce,
+        &to_sk,
+    )
+    .unwrap();
+
+    tb.test_tx_fail(
+        transfer.into(),
+        &format!(
+            "Transfer signature is incorrect: Signature is valid, but it was made by {:?} instead of {:?}",
+            to_account.pub_key_hash, account.pub_key_hash
+        ),
+    )
+}
@@ -162,6 +162,10 @@ impl NewExecutedTransaction {
                     serde_json::from_value(tx["target"].clone()).unwrap(),
                     serde_json::from_value(tx["target"].clone()).unwrap(),
                 ),
+                // Transaction has several recipients, so there is no single `to` account.
+                ZkSyncTx::MultiTransfer(_) => {
+                    (serde_json::from_value(tx["from"].clone()).unwrap(), None)
+                }
             };
 
         let from_account: Vec<u8> = hex::decode(cut_prefix(&from_account_hex)).unwrap();
@@ -2,7 +2,7 @@ use criterion::{black_box, criterion_group, BatchSize, Bencher, Criterion, Throu
 use zksync_basic_types::H256;
 use zksync_crypto::franklin_crypto::eddsa::PrivateKey;
 use zksync_crypto::rand::{Rng, SeedableRng, XorShiftRng};
-use zksync_types::tx::{PackedEthSignature, TxSignature};
+use zksync_types::tx::{generate_test_transactions, PackedEthSignature, TxSignature};
 
 fn bench_signature_zksync_musig_verify(b: &mut Bencher<'_>) {
     let mut rng = XorShiftRng::from_seed([1, 2, 3, 4]);
@@ -25,6 +25,28 @@ fn bench_signature_zksync_musig_verify(b: &mut Bencher<'_>) {
     );
 }
 
+/// Verification of the structurally invalid signature, which is rejected
+/// before the actual musig check.
+fn bench_signature_zksync_musig_verify_malformed(b: &mut Bencher<'_>) {
+    let mut rng = XorShiftRng::from_seed([1, 2, 3, 4]);
+    const WITHDRAW_TX_LEN: usize = 65;
+
+    let message = rng
+        .gen_iter::<u8>()
+        .take(WITHDRAW_TX_LEN)
+        .collect::<Vec<_>>();
+
+    let setup = || (TxSignature::default(), message.clone());
+
+    b.iter_batched(
+        setup,
+        |(signature, msg)| {
+            black_box(signature.verify_musig(&msg));
+        },
+        BatchSize::SmallInput,
+    );
+}
+
 fn bench_signature_verify_eth_packed(b: &mut Bencher<'_>) {
     let mut rng = XorShiftRng::from_seed([1, 2, 3, 4]);
     const TYPICAL_ETH_SIGNATURE_LEN: usize = 150;
@@ -72,6 +94,19 @@ fn bench_signature_seckp_recover(b: &mut Bencher<'_>) {
     );
 }
 
+/// Number of transactions verified in a single iteration of the batch benchmark.
+const BATCH_SIZE: usize = 100;
+
+fn bench_signature_zksync_batch_verify(b: &mut Bencher<'_>) {
+    let txs = generate_test_transactions(BATCH_SIZE, 1);
+
+    b.iter(|| {
+        for tx in &txs {
+            black_box(tx.signature().verify_musig(&tx.signing_message()));
+        }
+    });
+}
+
 pub fn bench_signatures(c: &mut Criterion) {
     let mut group = c.benchmark_group("Signature verify");
     group.throughput(Throughput::Elements(1));
@@ -79,6 +114,10 @@ pub fn bench_signatures(c: &mut Criterion) {
         "bench_signature_verify_zksync_musig",
         bench_signature_zksync_musig_verify,
     );
+    group.bench_function(
+        "bench_signature_verify_zksync_musig_malformed",
+        bench_signature_zksync_musig_verify_malformed,
+    );
     group.bench_function(
         "bench_signature_verify_eth_packed",
         bench_signature_verify_eth_packed,
@@ -88,6 +127,14 @@ pub fn bench_signatures(c: &mut Criterion) {
         bench_signature_seckp_recover,
     );
     group.finish();
+
+    let mut group = c.benchmark_group("Signature batch verify");
+    group.throughput(Throughput::Elements(BATCH_SIZE as u64));
+    group.bench_function(
+        "bench_signature_verify_zksync_batch",
+        bench_signature_zksync_batch_verify,
+    );
+    group.finish();
 }
 
 criterion_group!(signature_benches, bench_signatures);
@@ -342,4 +342,62 @@ mod test {
 
         assert_eq!(account_map_updated_back, account_map_initial);
     }
+
+    #[test]
+    fn test_pubkey_hash_serde() {
+        // `PubKeyHash` is serialized as a prefixed hex string, same as the other binary fields.
+        let pubkey_hash = PubKeyHash::from_bytes(&[0xab; 20]).unwrap();
+        let expected = "\"sync:abababababababababababababababababababab\"";
+
+        let serialized = serde_json::to_string(&pubkey_hash).unwrap();
+        assert_eq!(serialized, expected);
+
+        let deserialized: PubKeyHash = serde_json::from_str(expected).unwrap();
+        assert_eq!(deserialized, pubkey_hash);
+
+        // Raw byte arrays are not accepted.
+        let raw_bytes = serde_json::to_value(&[0xabu8; 20]).unwrap();
+        assert!(serde_json::from_value::<PubKeyHash>(raw_bytes).is_err());
+    }
+
+    #[test]
+    fn test_pubkey_hash_derivation() {
+        use zksync_crypto::{
+            priv_key_from_fs, public_key_from_private,
+            rand::{Rng, SeedableRng, XorShiftRng},
+        };
+
+        let mut rng = XorShiftRng::from_seed([1, 2, 3, 4]);
+        let public_key = public_key_from_private(&priv_key_from_fs(rng.gen()));
+
+        let hash = PubKeyHash::pubkey_hash(&public_key);
+        assert_eq!(hash.len(), 32);
+        // The hash is deterministic for the fixed key.
+        assert_eq!(hash, PubKeyHash::pubkey_hash(&public_key));
+
+        // `PubKeyHash` is the truncated hash.
+        let pubkey_hash = PubKeyHash::from_pubkey(&public_key);
+        assert_eq!(
+            pubkey_hash.data[..],
+            hash[hash.len() - pubkey_hash.data.len()..]
+        );
+    }
+
+    #[test]
+    fn test_pubkey_hash_from_privkey() {
+        use zksync_crypto::{
+            priv_key_from_fs, public_key_from_private,
+            rand::{Rng, SeedableRng, XorShiftRng},
+        };
+
+        let mut rng = XorShiftRng::from_seed([1, 2, 3, 4]);
+        for _ in 0..3 {
+            let private_key = priv_key_from_fs(rng.gen());
+            let public_key = public_key_from_private(&private_key);
+            assert_eq!(
+                PubKeyHash::from_privkey(&private_key),
+                PubKeyHash::from_pubkey(&public_key)
+            );
+        }
+    }
 }
@@ -6,15 +6,21 @@ use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use zksync_crypto::franklin_crypto::bellman::pairing::ff;
 
 use zksync_crypto::circuit::utils::pub_key_hash_bytes;
-use zksync_crypto::merkle_tree::rescue_hasher::BabyRescueHasher;
+use zksync_crypto::convert::FeConvert;
+use zksync_crypto::merkle_tree::{hasher::Hasher, rescue_hasher::BabyRescueHasher};
 use zksync_crypto::{public_key_from_private, Fr, PrivateKey, PublicKey};
 
+use crate::helpers::strip_hex_prefix;
+
 /// Hash of the account's owner public key.
 ///
 /// This is an essential type used within zkSync network to authorize transaction author
 /// to perform an operation.
 ///
 /// `PubKeyHash` is calculated as the Rescue hash of the public key byte sequence.
+///
+/// In JSON `PubKeyHash` is represented by its hexadecimal form (see `PubKeyHash::to_hex`)
+/// rather than by the raw byte array.
 #[derive(Clone, PartialEq, Default, Eq, Hash, PartialOrd, Ord)]
 pub struct PubKeyHash {
     pub data: [u8; params::FR_ADDRESS_LEN],
@@ -53,7 +59,7 @@ impl PubKeyHash {
     }
 
     /// Decodes `PubKeyHash` from its hexadecimal form.
-    /// Input string must have a `sync:` prefix.
+    /// Input string must have either a `sync:` or a `0x` prefix.
     ///
     /// # Example
     ///
@@ -63,10 +69,22 @@ impl PubKeyHash {
     ///
     /// let pubkey_hash = PubKeyHash::from_hex("sync:0000000000000000000000000000000000000000").unwrap();
     /// assert_eq!(pubkey_hash, PubKeyHash::zero());
+    ///
+    /// let pubkey_hash = PubKeyHash::from_hex("0x0000000000000000000000000000000000000000").unwrap();
+    /// assert_eq!(pubkey_hash, PubKeyHash::zero());
     /// ```
     pub fn from_hex(s: &str) -> Result<Self, anyhow::Error> {
-        ensure!(s.starts_with("sync:"), "PubKeyHash should start with sync:");
-        let bytes = hex::decode(&s[5..])?;
+        let hex = if let Some(hex) = s.strip_prefix("sync:") {
+            hex
+        } else {
+            let hex = strip_hex_prefix(s);
+            ensure!(
+                hex.len() != s.len(),
+                "PubKeyHash should start with sync: or 0x"
+            );
+            hex
+        };
+        let bytes = hex::decode(hex)?;
         Self::from_bytes(&bytes)
     }
 
@@ -78,6 +96,19 @@ impl PubKeyHash {
         })
     }
 
+    /// Computes the Rescue hash of the public key coordinates and returns it
+    /// as a big-endian byte sequence of the field element.
+    ///
+    /// `PubKeyHash` of the key consists of the lowest `NEW_PUBKEY_HASH_WIDTH` bits of this
+    /// hash, i.e. `PubKeyHash::from_pubkey` data is equal to the last `FR_ADDRESS_LEN` bytes
+    /// of the returned value.
+    pub fn pubkey_hash(public_key: &PublicKey) -> Vec<u8> {
+        let (pub_x, pub_y) = public_key.0.into_xy();
+        params::RESCUE_HASHER
+            .hash_elements(vec![pub_x, pub_y])
+            .to_bytes()
+    }
+
     /// Creates a `PubKeyHash` from the public key.
     pub fn from_pubkey(public_key: &PublicKey) -> Self {
         let mut pk_hash =
@@ -92,6 +123,9 @@ impl PubKeyHash {
     }
 
     /// Creates a `PubKeyHash` from the private key.
+    ///
+    /// Can be used to check that the account of a transaction matches the key
+    /// before signing it.
     pub fn from_privkey(private_key: &PrivateKey) -> Self {
         let pub_key = public_key_from_private(&private_key);
         Self::from_pubkey(&pub_key)
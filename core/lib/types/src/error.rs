@@ -0,0 +1,106 @@
+//! Common error type of the zkSync types.
+//!
+//! Functions of this crate return specialized errors (e.g. `SignatureError`), each of which
+//! can be converted into the `Error` enum, so downstream crates can match on a single type.
+
+use num::BigUint;
+use thiserror::Error;
+use zksync_basic_types::Address;
+
+use crate::{
+    tx::{FeeError, SignatureError, WireError, WithdrawError},
+    Nonce,
+};
+
+/// Aggregated error of the zkSync types.
+#[derive(Debug, Clone, PartialEq, Error)]
+pub enum Error {
+    #[error("Unable to decode transaction: {0}")]
+    Decode(#[from] WireError),
+    #[error("Transaction is incorrect: {0}")]
+    Validation(#[from] FeeError),
+    #[error("Withdrawal is rejected: {0}")]
+    Withdrawal(#[from] WithdrawError),
+    #[error("Transaction signature verification failed: {0}")]
+    Signature(#[from] SignatureError),
+    #[error("Incorrect nonce: expected {expected}, got {actual}")]
+    Nonce { expected: Nonce, actual: Nonce },
+    #[error("Nonces of {count} transactions starting from {start} overflow")]
+    NonceOverflow { start: Nonce, count: usize },
+    #[error("Value {value} of the field {field} is too big to be packed")]
+    Unpackable { field: &'static str, value: BigUint },
+    #[error("Value {value} of the field {field} cannot be packed without precision loss")]
+    Imprecise { field: &'static str, value: BigUint },
+    #[error("Transactions from the account {0:?} are not allowed")]
+    NotAllowed(Address),
+    #[error("Sender address of the transaction is not known")]
+    UnknownSender,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn error_messages() {
+        let cases = vec![
+            (
+                Error::from(WireError::UnknownTxType(42)),
+                "Unable to decode transaction: Unknown transaction type 42",
+            ),
+            (
+                Error::from(FeeError::ZeroAmount),
+                "Transaction is incorrect: Fee cannot be expressed relative to the zero amount",
+            ),
+            (
+                Error::from(WithdrawError::ZeroAddress),
+                "Withdrawal is rejected: Withdrawal to the zero address burns the funds",
+            ),
+            (
+                Error::from(SignatureError::IncorrectSignature),
+                "Transaction signature verification failed: \
+                 Signature is incorrect for every supported signature scheme",
+            ),
+            (
+                Error::Nonce {
+                    expected: 1,
+                    actual: 2,
+                },
+                "Incorrect nonce: expected 1, got 2",
+            ),
+            (
+                Error::NonceOverflow {
+                    start: Nonce::max_value(),
+                    count: 2,
+                },
+                "Nonces of 2 transactions starting from 4294967295 overflow",
+            ),
+            (
+                Error::Unpackable {
+                    field: "fee",
+                    value: BigUint::from(1u64) << 128,
+                },
+                "Value 340282366920938463463374607431768211456 of the field fee is too big to be packed",
+            ),
+            (
+                Error::Imprecise {
+                    field: "amount",
+                    value: BigUint::from(1_234_567_890_123u64),
+                },
+                "Value 1234567890123 of the field amount cannot be packed without precision loss",
+            ),
+            (
+                Error::NotAllowed(Address::repeat_byte(0x11)),
+                "Transactions from the account 0x1111111111111111111111111111111111111111 are not allowed",
+            ),
+            (
+                Error::UnknownSender,
+                "Sender address of the transaction is not known",
+            ),
+        ];
+
+        for (error, message) in cases {
+            assert_eq!(error.to_string(), message);
+        }
+    }
+}
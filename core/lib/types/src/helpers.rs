@@ -78,6 +78,16 @@ pub fn unpack_fee_amount(data: &[u8]) -> Option<BigUint> {
     .and_then(BigUint::from_u128)
 }
 
+/// Attempts to unpack the packed token amounts (e.g. outputs of the `MultiTransfer`)
+/// and returns their sum.
+///
+/// Returns `None` if any of the amounts cannot be unpacked.
+pub fn sum_packed_amounts(amounts: &[&[u8]]) -> Option<BigUint> {
+    amounts.iter().try_fold(BigUint::from(0u32), |sum, amount| {
+        Some(sum + unpack_token_amount(amount)?)
+    })
+}
+
 /// Returns the closest possible packable token amount.
 /// Returned amount is always less or equal to the provided amount.
 pub fn closest_packable_fee_amount(amount: &BigUint) -> BigUint {
@@ -92,6 +102,14 @@ pub fn closest_packable_token_amount(amount: &BigUint) -> BigUint {
     unpack_token_amount(&fee_packed).expect("token amount repacking")
 }
 
+/// Strips the optional `0x` (or `0X`) prefix from the hexadecimal string.
+pub fn strip_hex_prefix(value: &str) -> &str {
+    value
+        .strip_prefix("0x")
+        .or_else(|| value.strip_prefix("0X"))
+        .unwrap_or(value)
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -132,6 +150,27 @@ mod test {
         ));
     }
 
+    #[test]
+    fn sum_packed() {
+        let amounts = [
+            BigUint::from(1_000u32),
+            BigUint::from(25_000_000u32),
+            closest_packable_token_amount(&BigUint::from(123_456_789_123_456_789u64)),
+        ];
+        let packed: Vec<_> = amounts.iter().map(pack_token_amount).collect();
+        let packed: Vec<&[u8]> = packed.iter().map(Vec::as_slice).collect();
+
+        let expected: BigUint = amounts.iter().sum();
+        assert_eq!(sum_packed_amounts(&packed), Some(expected));
+        assert_eq!(
+            sum_packed_amounts(&packed[..1]),
+            Some(BigUint::from(1_000u32))
+        );
+        assert_eq!(sum_packed_amounts(&[]), Some(BigUint::from(0u32)));
+        // Incorrect length of the packed amount.
+        assert_eq!(sum_packed_amounts(&[&packed[0][1..]]), None);
+    }
+
     #[test]
     fn pack_to_closest_packable() {
         let fee = BigUint::from(1_234_123_424u32);
@@ -39,6 +39,7 @@
 pub mod account;
 pub mod block;
 pub mod config;
+pub mod error;
 pub mod ethereum;
 pub mod gas_counter;
 pub mod helpers;
@@ -60,7 +61,9 @@ pub use self::operations::{
 };
 pub use self::priority_ops::{Deposit, FullExit, PriorityOp, ZkSyncPriorityOp};
 pub use self::tokens::{Token, TokenGenesisListItem, TokenLike, TokenPrice, TxFeeTypes};
-pub use self::tx::{ForcedExit, SignedZkSyncTx, Transfer, Withdraw, ZkSyncTx};
+pub use self::tx::{
+    ForcedExit, MultiTransfer, SignedZkSyncTx, Transfer, TransferOutput, Withdraw, ZkSyncTx,
+};
 
 #[doc(hidden)]
 pub use self::{operations::CloseOp, tx::Close};
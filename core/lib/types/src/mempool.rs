@@ -1,8 +1,20 @@
+use std::cmp::Ordering;
+use std::convert::TryFrom;
+use std::io::{self, Read, Write};
+
+use num::BigUint;
+
 use super::{
-    tx::{TxEthSignature, TxHash},
-    SignedZkSyncTx,
+    helpers::closest_packable_fee_amount,
+    tx::{read_framed, write_framed, TxEthSignature, TxHash},
+    SignedZkSyncTx, ZkSyncTx,
 };
 
+/// Magic bytes at the start of the mempool snapshot written by `save_mempool`.
+pub const MEMPOOL_SNAPSHOT_MAGIC: [u8; 4] = *b"ZKMP";
+/// Version of the mempool snapshot format written by `save_mempool`.
+pub const MEMPOOL_SNAPSHOT_VERSION: u16 = 1;
+
 /// A collection of transactions that must be executed together.
 /// All the transactions in the batch must be included into the same block,
 /// and either succeed or fail all together.
@@ -47,3 +59,290 @@ impl SignedTxVariant {
         }
     }
 }
+
+/// Compares transactions according to the mempool priority policy:
+/// transactions with higher fee go first, transactions with the same fee
+/// are ordered by nonce ascending.
+///
+/// Fees are compared in their packed form (as they will be included into the block),
+/// and transactions without fee are treated as having zero fee.
+///
+/// `Ordering::Less` means that `a` has higher priority than `b`.
+pub fn mempool_cmp(a: &ZkSyncTx, b: &ZkSyncTx) -> Ordering {
+    fn packed_fee(tx: &ZkSyncTx) -> BigUint {
+        tx.fee()
+            .map(closest_packable_fee_amount)
+            .unwrap_or_default()
+    }
+
+    packed_fee(b)
+        .cmp(&packed_fee(a))
+        .then_with(|| a.nonce().cmp(&b.nonce()))
+}
+
+/// Selects the transactions to be included into a block with limited size.
+///
+/// Transactions are considered in the mempool priority order (see `mempool_cmp`), and
+/// every transaction which still fits into the remaining `budget` (measured in bytes
+/// of `ZkSyncTx::serialized_size`) is taken, so smaller transactions may fill the space
+/// left after larger ones were skipped.
+///
+/// Returns indices of the selected transactions in `txs`, in the priority order.
+pub fn pack_into_budget(txs: &[ZkSyncTx], budget: usize) -> Vec<usize> {
+    let mut indices: Vec<usize> = (0..txs.len()).collect();
+    indices.sort_by(|&a, &b| mempool_cmp(&txs[a], &txs[b]));
+
+    let mut remaining = budget;
+    indices
+        .into_iter()
+        .filter(|&idx| {
+            let size = txs[idx].serialized_size();
+            if size <= remaining {
+                remaining -= size;
+                true
+            } else {
+                false
+            }
+        })
+        .collect()
+}
+
+/// Replace-by-fee policy: checks whether the `incoming` transaction should replace
+/// the `existing` one in the mempool.
+///
+/// Replacement is only allowed for the conflicting transactions (see `ZkSyncTx::conflicts_with`),
+/// and the fee of the incoming transaction must be at least `min_bump` higher than
+/// the fee of the existing one. Transactions without fee are never replaced.
+pub fn should_replace(existing: &ZkSyncTx, incoming: &ZkSyncTx, min_bump: &BigUint) -> bool {
+    if !existing.conflicts_with(incoming) {
+        return false;
+    }
+
+    match (existing.fee(), incoming.fee()) {
+        (Some(existing_fee), Some(incoming_fee)) => incoming_fee >= &(existing_fee + min_bump),
+        _ => false,
+    }
+}
+
+/// Writes the snapshot of the mempool transactions, e.g. to restore them after the restart
+/// with `load_mempool`.
+///
+/// The snapshot consists of the `MEMPOOL_SNAPSHOT_MAGIC` bytes, 2-byte big-endian
+/// `MEMPOOL_SNAPSHOT_VERSION`, 4-byte big-endian number of the transactions and the
+/// transactions themselves, each framed as by `write_framed`.
+///
+/// Returns the `InvalidInput` error if the number of transactions doesn't fit into `u32`.
+pub fn save_mempool<W: Write>(txs: &[ZkSyncTx], mut w: W) -> io::Result<()> {
+    w.write_all(&MEMPOOL_SNAPSHOT_MAGIC)?;
+    w.write_all(&MEMPOOL_SNAPSHOT_VERSION.to_be_bytes())?;
+    let txs_count = u32::try_from(txs.len()).map_err(|_| {
+        io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "Too many transactions for the mempool snapshot",
+        )
+    })?;
+    w.write_all(&txs_count.to_be_bytes())?;
+    for tx in txs {
+        write_framed(&mut w, tx)?;
+    }
+    w.flush()
+}
+
+/// Reads the mempool snapshot written by `save_mempool`, transactions are returned
+/// in the same order.
+///
+/// Returns an error of the `InvalidData` kind if the snapshot has an unexpected header
+/// or contains a malformed transaction.
+pub fn load_mempool<R: Read>(mut r: R) -> io::Result<Vec<ZkSyncTx>> {
+    let mut magic = [0u8; 4];
+    r.read_exact(&mut magic)?;
+    if magic != MEMPOOL_SNAPSHOT_MAGIC {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "Not a mempool snapshot",
+        ));
+    }
+
+    let mut version = [0u8; 2];
+    r.read_exact(&mut version)?;
+    let version = u16::from_be_bytes(version);
+    if version != MEMPOOL_SNAPSHOT_VERSION {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("Unsupported mempool snapshot version {}", version),
+        ));
+    }
+
+    let mut count = [0u8; 4];
+    r.read_exact(&mut count)?;
+    let count = u32::from_be_bytes(count);
+
+    // The count is not trusted for the preallocation, the snapshot may be truncated.
+    let mut txs = Vec::new();
+    for _ in 0..count {
+        txs.push(read_framed(&mut r)?);
+    }
+    Ok(txs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Address, Transfer, Withdraw};
+
+    fn transfer(fee: impl Into<BigUint>, nonce: u32) -> ZkSyncTx {
+        Transfer::new(
+            0,
+            Address::from([1u8; 20]),
+            Address::from([2u8; 20]),
+            0,
+            100u64.into(),
+            fee.into(),
+            nonce,
+            None,
+        )
+        .into()
+    }
+
+    #[test]
+    fn mempool_ordering() {
+        let mut txs = vec![
+            transfer(10u64, 2),
+            transfer(30u64, 5),
+            transfer(10u64, 1),
+            transfer(20u64, 0),
+            transfer(30u64, 4),
+        ];
+        txs.sort_by(mempool_cmp);
+
+        let order: Vec<_> = txs
+            .iter()
+            .map(|tx| (tx.fee().cloned().unwrap(), tx.nonce()))
+            .collect();
+        assert_eq!(
+            order,
+            vec![
+                (30u64.into(), 4),
+                (30u64.into(), 5),
+                (20u64.into(), 0),
+                (10u64.into(), 1),
+                (10u64.into(), 2),
+            ]
+        );
+    }
+
+    #[test]
+    fn mempool_ordering_uses_packed_fee() {
+        // Both fees are packed into the same value, so the nonce decides.
+        let fee = 1_234_123_424u64;
+        let packed_fee = closest_packable_fee_amount(&fee.into());
+        assert_ne!(packed_fee, BigUint::from(fee));
+
+        let a = transfer(fee, 1);
+        let b = transfer(packed_fee, 0);
+        assert_eq!(mempool_cmp(&b, &a), Ordering::Less);
+        assert_eq!(mempool_cmp(&a, &b), Ordering::Greater);
+    }
+
+    #[test]
+    fn packing_into_budget() {
+        let close = ZkSyncTx::from(crate::tx::Close {
+            account: Address::from([1u8; 20]),
+            nonce: 3,
+            signature: Default::default(),
+        });
+        let txs = vec![
+            transfer(10u64, 2),
+            transfer(30u64, 1),
+            close.clone(),
+            transfer(20u64, 0),
+        ];
+        let transfer_size = txs[0].serialized_size();
+        let close_size = close.serialized_size();
+        assert!(close_size < transfer_size);
+
+        // Everything fits.
+        let total = 3 * transfer_size + close_size;
+        assert_eq!(pack_into_budget(&txs, total), vec![1, 3, 0, 2]);
+        // Transactions with the highest fee are selected first.
+        assert_eq!(pack_into_budget(&txs, 2 * transfer_size), vec![1, 3]);
+        // Transaction which doesn't fit is skipped, while the smaller one still fits.
+        assert_eq!(
+            pack_into_budget(&txs, 2 * transfer_size + close_size),
+            vec![1, 3, 2]
+        );
+        assert_eq!(pack_into_budget(&txs, close_size), vec![2]);
+        assert!(pack_into_budget(&txs, close_size - 1).is_empty());
+
+        for budget in 0..=total {
+            let size: usize = pack_into_budget(&txs, budget)
+                .into_iter()
+                .map(|idx| txs[idx].serialized_size())
+                .sum();
+            assert!(size <= budget);
+        }
+    }
+
+    #[test]
+    fn replace_by_fee() {
+        let min_bump = BigUint::from(10u64);
+        let existing = transfer(20u64, 1);
+
+        // Sufficient fee bump.
+        assert!(should_replace(&existing, &transfer(30u64, 1), &min_bump));
+        assert!(should_replace(&existing, &transfer(50u64, 1), &min_bump));
+        // Insufficient fee bump.
+        assert!(!should_replace(&existing, &transfer(29u64, 1), &min_bump));
+        assert!(!should_replace(&existing, &transfer(10u64, 1), &min_bump));
+        // Transactions don't conflict.
+        assert!(!should_replace(&existing, &transfer(50u64, 2), &min_bump));
+        assert!(!should_replace(&existing, &existing, &min_bump));
+    }
+
+    #[test]
+    fn mempool_snapshot_roundtrip() {
+        let withdraw = Withdraw::new(
+            3,
+            Address::from([3u8; 20]),
+            Address::from([4u8; 20]),
+            1,
+            500u64.into(),
+            10u64.into(),
+            7,
+            None,
+        );
+        let txs = vec![
+            transfer(10u64, 2),
+            ZkSyncTx::from(withdraw),
+            transfer(30u64, 5),
+        ];
+
+        let mut snapshot = Vec::new();
+        save_mempool(&txs, &mut snapshot).unwrap();
+        assert_eq!(snapshot[..4], MEMPOOL_SNAPSHOT_MAGIC);
+
+        let restored = load_mempool(&snapshot[..]).unwrap();
+        let hashes = |txs: &[ZkSyncTx]| txs.iter().map(ZkSyncTx::hash).collect::<Vec<_>>();
+        assert_eq!(hashes(&restored), hashes(&txs));
+
+        // Empty mempool.
+        let mut empty = Vec::new();
+        save_mempool(&[], &mut empty).unwrap();
+        assert!(load_mempool(&empty[..]).unwrap().is_empty());
+
+        // Incorrect header.
+        let mut bad_magic = snapshot.clone();
+        bad_magic[0] ^= 1;
+        let err = load_mempool(&bad_magic[..]).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+
+        let mut bad_version = snapshot.clone();
+        bad_version[5] += 1;
+        let err = load_mempool(&bad_version[..]).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+
+        // Truncated snapshot.
+        let err = load_mempool(&snapshot[..snapshot.len() - 1]).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+    }
+}
@@ -0,0 +1,308 @@
+//! Helpers operating on sequences of transactions.
+
+// Built-in uses
+use std::collections::{HashMap, HashSet};
+
+// External uses
+use num::{BigInt, BigUint};
+use parity_crypto::digest::sha256;
+use thiserror::Error;
+
+// Workspace uses
+use zksync_basic_types::{Address, TokenId};
+use zksync_crypto::franklin_crypto::eddsa::PrivateKey;
+use zksync_crypto::params::{max_account_id, max_token_id};
+use zksync_crypto::rand::{Rng, SeedableRng, XorShiftRng};
+
+// Local uses
+use super::{Transfer, TxSignature, ZkSyncTx};
+use crate::error::Error;
+use crate::helpers::{closest_packable_fee_amount, closest_packable_token_amount};
+use crate::Nonce;
+
+/// Computes the net balance change for every `(account, token)` pair affected by the
+/// provided transactions.
+///
+/// - `Transfer` subtracts `amount + fee` from the sender and adds `amount` to the recipient.
+/// - `Withdraw` subtracts `amount + fee` from the account: funds leave the L2.
+/// - `ChangePubKey` subtracts `fee` in the fee token from the account.
+/// - `MultiTransfer` is handled as a sequence of transfers with a single `fee`.
+/// - `ForcedExit` and `Close` are skipped: the withdrawn amount of a `ForcedExit` depends
+///   on the account state, and its initiator is only known by the account ID.
+///
+/// Fees are collected by the operator, so for a batch without withdrawals the deltas
+/// sum up to the negated total fee per token.
+pub fn net_deltas(txs: &[ZkSyncTx]) -> HashMap<(Address, TokenId), BigInt> {
+    let mut deltas: HashMap<(Address, TokenId), BigInt> = HashMap::new();
+    let mut add = |account: Address, token: TokenId, delta: BigInt| {
+        *deltas.entry((account, token)).or_default() += delta;
+    };
+
+    for tx in txs {
+        match tx {
+            ZkSyncTx::Transfer(transfer) => {
+                let amount = BigInt::from(transfer.amount.clone());
+                let fee = BigInt::from(transfer.fee.clone());
+                add(transfer.from, transfer.token, -(amount.clone() + fee));
+                add(transfer.to, transfer.token, amount);
+            }
+            ZkSyncTx::Withdraw(withdraw) => {
+                let amount = BigInt::from(withdraw.amount.clone());
+                let fee = BigInt::from(withdraw.fee.clone());
+                add(withdraw.from, withdraw.token, -(amount + fee));
+            }
+            ZkSyncTx::ChangePubKey(change_pubkey) => {
+                let fee = BigInt::from(change_pubkey.fee.clone());
+                add(change_pubkey.account, change_pubkey.fee_token, -fee);
+            }
+            ZkSyncTx::MultiTransfer(multi_transfer) => {
+                for output in &multi_transfer.outputs {
+                    let amount = BigInt::from(output.amount.clone().into_inner());
+                    add(multi_transfer.from, output.token, -amount.clone());
+                    add(output.to, output.token, amount);
+                }
+                let fee = BigInt::from(multi_transfer.fee.clone());
+                add(multi_transfer.from, multi_transfer.fee_token, -fee);
+            }
+            ZkSyncTx::ForcedExit(_) | ZkSyncTx::Close(_) => {}
+        }
+    }
+
+    deltas
+}
+
+/// Computes the total fee paid by the provided transactions in every fee token.
+///
+/// Fees paid in different tokens cannot be summed up, so the result is always a map
+/// from the fee token to the total fee, even if all the transactions pay fees in the same
+/// token. `Close` transactions have no fee and are skipped.
+pub fn total_fees(txs: &[ZkSyncTx]) -> HashMap<TokenId, BigUint> {
+    let mut fees: HashMap<TokenId, BigUint> = HashMap::new();
+
+    for tx in txs {
+        let (token, fee) = match tx {
+            ZkSyncTx::Transfer(transfer) => (transfer.token, &transfer.fee),
+            ZkSyncTx::Withdraw(withdraw) => (withdraw.token, &withdraw.fee),
+            ZkSyncTx::ChangePubKey(change_pubkey) => (change_pubkey.fee_token, &change_pubkey.fee),
+            ZkSyncTx::ForcedExit(forced_exit) => (forced_exit.token, &forced_exit.fee),
+            ZkSyncTx::MultiTransfer(multi_transfer) => {
+                (multi_transfer.fee_token, &multi_transfer.fee)
+            }
+            ZkSyncTx::Close(_) => continue,
+        };
+        *fees.entry(token).or_default() += fee;
+    }
+
+    fees
+}
+
+/// Returns the addresses of all the accounts involved in the provided transactions,
+/// in the order of their first appearance and without duplicates.
+///
+/// The involved accounts are the sender and the recipients of the transaction. For a
+/// `Withdraw` the recipient is the L1 address the funds are sent to. The `ForcedExit`
+/// initiator is only known by the account ID, so only the target is included.
+pub fn unique_accounts(txs: &[ZkSyncTx]) -> Vec<Address> {
+    let mut seen = HashSet::new();
+    let mut accounts = Vec::new();
+    let mut add = |account: Address| {
+        if seen.insert(account) {
+            accounts.push(account);
+        }
+    };
+
+    for tx in txs {
+        match tx {
+            ZkSyncTx::Transfer(transfer) => {
+                add(transfer.from);
+                add(transfer.to);
+            }
+            ZkSyncTx::Withdraw(withdraw) => {
+                add(withdraw.from);
+                add(withdraw.to);
+            }
+            ZkSyncTx::Close(close) => add(close.account),
+            ZkSyncTx::ChangePubKey(change_pubkey) => add(change_pubkey.account),
+            ZkSyncTx::ForcedExit(forced_exit) => add(forced_exit.target),
+            ZkSyncTx::MultiTransfer(multi_transfer) => {
+                add(multi_transfer.from);
+                for output in &multi_transfer.outputs {
+                    add(output.to);
+                }
+            }
+        }
+    }
+
+    accounts
+}
+
+/// Returns all the tokens referenced by the transactions, including the fee tokens.
+pub fn referenced_tokens(txs: &[ZkSyncTx]) -> HashSet<TokenId> {
+    let mut tokens = HashSet::new();
+    for tx in txs {
+        match tx {
+            ZkSyncTx::Transfer(transfer) => {
+                tokens.insert(transfer.token);
+            }
+            ZkSyncTx::Withdraw(withdraw) => {
+                tokens.insert(withdraw.token);
+            }
+            ZkSyncTx::Close(_) => {}
+            ZkSyncTx::ChangePubKey(change_pubkey) => {
+                tokens.insert(change_pubkey.fee_token);
+            }
+            ZkSyncTx::ForcedExit(forced_exit) => {
+                tokens.insert(forced_exit.token);
+            }
+            ZkSyncTx::MultiTransfer(multi_transfer) => {
+                tokens.insert(multi_transfer.fee_token);
+                tokens.extend(multi_transfer.outputs.iter().map(|output| output.token));
+            }
+        }
+    }
+    tokens
+}
+
+/// Reason why the transaction of a batch is invalid, see `invalid_txs`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Error)]
+pub enum InvalidTxReason {
+    #[error("Transaction signature is incorrect")]
+    IncorrectSignature,
+    #[error("Transaction is malformed")]
+    Malformed,
+}
+
+/// Checks every transaction of the batch in isolation (see `ZkSyncTx::check_correctness`)
+/// and returns the indices of the invalid ones along with the reason of the failure.
+///
+/// Valid transactions are not reported, so the result is empty if the whole batch is correct.
+/// Transactions that are both malformed and incorrectly signed are reported as having an
+/// incorrect signature.
+pub fn invalid_txs(txs: &[ZkSyncTx]) -> Vec<(usize, InvalidTxReason)> {
+    txs.iter()
+        .enumerate()
+        .filter_map(|(index, tx)| {
+            // Correctness check caches the signature verification result, so it needs a copy.
+            let mut tx = tx.clone();
+            if tx.check_correctness() {
+                return None;
+            }
+
+            let reason = if tx.verify_signature().is_none() {
+                InvalidTxReason::IncorrectSignature
+            } else {
+                InvalidTxReason::Malformed
+            };
+            Some((index, reason))
+        })
+        .collect()
+}
+
+/// Returns the message to be signed to authorize the whole batch with a single signature,
+/// see `verify_batch_signature`.
+///
+/// The message is the SHA-256 hash of the concatenated `ZkSyncTx::get_bytes` of the
+/// transactions. The concatenation itself cannot be signed directly: the Rescue hash of
+/// the musig signature only covers the first 92 bytes of the message.
+pub fn batch_signing_message(txs: &[ZkSyncTx]) -> Vec<u8> {
+    let bytes: Vec<u8> = txs.iter().flat_map(ZkSyncTx::get_bytes).collect();
+    sha256(&bytes).to_vec()
+}
+
+/// Verifies the single zkSync signature authorizing the whole batch of transactions,
+/// made for the `batch_signing_message` of the transactions.
+///
+/// Signatures of the transactions themselves are not checked.
+pub fn verify_batch_signature(txs: &[ZkSyncTx], sign: &TxSignature) -> bool {
+    sign.verify_musig(&batch_signing_message(txs)).is_some()
+}
+
+/// Assigns consecutive nonces to the transactions of the same account, starting from `start`
+/// (e.g. the current nonce of the account).
+///
+/// Changing the nonce invalidates the transaction signature, so the transactions must be
+/// signed after the nonces are assigned. Returns an error without changing any transaction
+/// if the last nonce doesn't fit into `Nonce`.
+pub fn assign_nonces(txs: &mut [ZkSyncTx], start: Nonce) -> Result<(), Error> {
+    if let Some(last_offset) = txs.len().checked_sub(1) {
+        if u64::from(start) + last_offset as u64 > u64::from(Nonce::max_value()) {
+            return Err(Error::NonceOverflow {
+                start,
+                count: txs.len(),
+            });
+        }
+    }
+
+    for (offset, tx) in txs.iter_mut().enumerate() {
+        tx.set_nonce(start + offset as Nonce);
+    }
+    Ok(())
+}
+
+/// Generates `n` correctly signed transfers, e.g. for the load testing or benchmarks.
+///
+/// Every transfer is signed by its own pseudo-random key. The result is deterministic:
+/// the same `seed` always produces the same transactions. Note that the senders don't
+/// correspond to any real accounts, so transactions are only valid in isolation.
+pub fn generate_test_transactions(n: usize, seed: u64) -> Vec<ZkSyncTx> {
+    // `XorShiftRng` cannot be seeded with zeros, so the seed is mixed with a constant.
+    let mut rng =
+        XorShiftRng::from_seed([(seed >> 32) as u32, seed as u32, 0x9e37_79b9, 0x7f4a_7c15]);
+
+    (0..n)
+        .map(|_| {
+            let private_key = PrivateKey(rng.gen());
+            let amount = closest_packable_token_amount(&rng.gen_range(1u64, 1_000_000).into());
+            let fee = closest_packable_fee_amount(&rng.gen_range(1u64, 1_000).into());
+
+            Transfer::new_signed(
+                rng.gen::<u32>().min(max_account_id()),
+                Address::from(rng.gen::<[u8; 20]>()),
+                Address::from(rng.gen::<[u8; 20]>()),
+                rng.gen::<u16>().min(max_token_id()),
+                amount,
+                fee,
+                rng.gen(),
+                &private_key,
+            )
+            .expect("generated transfer should be correct")
+            .into()
+        })
+        .collect()
+}
+
+/// Computes the root of the binary Merkle tree built over the hashes of the transactions,
+/// e.g. as a commitment to the transactions of a block.
+///
+/// The leaves are the `ZkSyncTx::hash` values, every inner node is the SHA-256 hash of the
+/// concatenation of its children. If a level has an odd number of nodes, the last node is
+/// paired with itself. The root of a single transaction is its hash, and the root of an
+/// empty list is all zeros.
+pub fn tx_merkle_root(txs: &[ZkSyncTx]) -> [u8; 32] {
+    let mut level: Vec<[u8; 32]> = txs
+        .iter()
+        .map(|tx| {
+            let mut leaf = [0u8; 32];
+            leaf.copy_from_slice(tx.hash().as_ref());
+            leaf
+        })
+        .collect();
+    if level.is_empty() {
+        return [0u8; 32];
+    }
+
+    while level.len() > 1 {
+        level = level
+            .chunks(2)
+            .map(|pair| {
+                let left = &pair[0];
+                let right = pair.get(1).unwrap_or(left);
+
+                let mut node = [0u8; 32];
+                node.copy_from_slice(&sha256(&[&left[..], &right[..]].concat()));
+                node
+            })
+            .collect();
+    }
+    level[0]
+}
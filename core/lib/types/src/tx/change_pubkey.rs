@@ -47,7 +47,7 @@ pub struct ChangePubKey {
     /// to the account address.
     pub eth_signature: Option<PackedEthSignature>,
     #[serde(skip)]
-    cached_signer: VerifiedSignatureCache,
+    pub(super) cached_signer: VerifiedSignatureCache,
 }
 
 impl ChangePubKey {
@@ -0,0 +1,400 @@
+//! Human-readable breakdown of the transaction bytes.
+//!
+//! Intended for diagnostics: if the signature made by the client doesn't verify, comparing
+//! the explanation of its signed bytes with the expected transaction shows the mismatching field.
+
+use std::ops::Range;
+
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+
+use super::{
+    wire::{FieldReader, WireError},
+    ChangePubKey, Close, ForcedExit, MultiTransfer, Transfer, TxHash, Withdraw,
+};
+use crate::PubKeyHash;
+
+/// Single decoded field of the transaction bytes.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ExplainedField {
+    /// Name of the transaction field, e.g. `amount` or `outputs[0].to`.
+    pub name: String,
+    /// Range of the field in the explained bytes.
+    pub range: Range<usize>,
+    /// Decoded value of the field.
+    pub value: String,
+}
+
+/// Breakdown of the transaction bytes into fields.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TxExplanation {
+    /// Name of the transaction type, e.g. `Transfer`.
+    pub tx_type: String,
+    /// Decoded fields in the order of their appearance in the bytes.
+    pub fields: Vec<ExplainedField>,
+}
+
+/// Structured description of the transaction to be signed, e.g. by a hardware wallet.
+///
+/// Contains everything the signing device (or its companion app) needs to show the user
+/// what is being signed, see `ZkSyncTx::to_signing_request`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SigningRequest {
+    /// Name of the transaction type, e.g. `Transfer`.
+    pub tx_type: String,
+    /// Transaction bytes, as returned by `ZkSyncTx::get_bytes`. Serialized as a hex string
+    /// with the `0x` prefix.
+    ///
+    /// These are the bytes to be signed for all the transactions except `MultiTransfer`,
+    /// for which their hash is signed, see `ZkSyncTx::signing_message`.
+    #[serde(serialize_with = "serialize_hex", deserialize_with = "deserialize_hex")]
+    pub bytes: Vec<u8>,
+    /// Hash of the transaction.
+    pub tx_hash: TxHash,
+    /// Breakdown of the `bytes` into fields.
+    pub fields: Vec<ExplainedField>,
+}
+
+fn serialize_hex<S: Serializer>(bytes: &[u8], serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_str(&format!("0x{}", hex::encode(bytes)))
+}
+
+fn deserialize_hex<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<u8>, D::Error> {
+    let string = String::deserialize(deserializer)?;
+    let hex = string
+        .strip_prefix("0x")
+        .ok_or_else(|| de::Error::custom("hex string should start with 0x"))?;
+    hex::decode(hex).map_err(de::Error::custom)
+}
+
+/// Encoding of a fixed-size field of the transaction bytes.
+///
+/// All the integers are encoded as big-endian.
+#[derive(Debug, Clone, Copy)]
+enum FieldKind {
+    U8,
+    U16,
+    U32,
+    Address,
+    PubKeyHash,
+    /// Packed token amount.
+    TokenAmount,
+    /// Unpacked 16-byte amount of the `Withdraw`.
+    FullAmount,
+    /// Packed fee amount.
+    Fee,
+}
+
+impl FieldKind {
+    fn len(self) -> usize {
+        match self {
+            FieldKind::U8 => 1,
+            FieldKind::U16 => 2,
+            FieldKind::U32 => 4,
+            FieldKind::Address | FieldKind::PubKeyHash => 20,
+            FieldKind::TokenAmount => 5,
+            FieldKind::FullAmount => 16,
+            FieldKind::Fee => 2,
+        }
+    }
+}
+
+type FieldsLayout = &'static [(&'static str, FieldKind)];
+
+const TRANSFER_FIELDS: FieldsLayout = &[
+    ("type", FieldKind::U8),
+    ("account_id", FieldKind::U32),
+    ("from", FieldKind::Address),
+    ("to", FieldKind::Address),
+    ("token", FieldKind::U16),
+    ("amount", FieldKind::TokenAmount),
+    ("fee", FieldKind::Fee),
+    ("nonce", FieldKind::U32),
+];
+
+const WITHDRAW_FIELDS: FieldsLayout = &[
+    ("type", FieldKind::U8),
+    ("account_id", FieldKind::U32),
+    ("from", FieldKind::Address),
+    ("to", FieldKind::Address),
+    ("token", FieldKind::U16),
+    ("amount", FieldKind::FullAmount),
+    ("fee", FieldKind::Fee),
+    ("nonce", FieldKind::U32),
+];
+
+const CLOSE_FIELDS: FieldsLayout = &[
+    ("type", FieldKind::U8),
+    ("account", FieldKind::Address),
+    ("nonce", FieldKind::U32),
+];
+
+const CHANGE_PUBKEY_FIELDS: FieldsLayout = &[
+    ("type", FieldKind::U8),
+    ("account_id", FieldKind::U32),
+    ("account", FieldKind::Address),
+    ("new_pk_hash", FieldKind::PubKeyHash),
+    ("fee_token", FieldKind::U16),
+    ("fee", FieldKind::Fee),
+    ("nonce", FieldKind::U32),
+];
+
+const FORCED_EXIT_FIELDS: FieldsLayout = &[
+    ("type", FieldKind::U8),
+    ("initiator_account_id", FieldKind::U32),
+    ("target", FieldKind::Address),
+    ("token", FieldKind::U16),
+    ("fee", FieldKind::Fee),
+    ("nonce", FieldKind::U32),
+];
+
+/// Fields preceding the outputs list of the `MultiTransfer`.
+const MULTI_TRANSFER_HEADER: FieldsLayout = &[
+    ("type", FieldKind::U8),
+    ("account_id", FieldKind::U32),
+    ("from", FieldKind::Address),
+    ("outputs_count", FieldKind::U8),
+];
+
+fn field_ranges(fields: FieldsLayout) -> Vec<(&'static str, Range<usize>)> {
+    let mut offset = 0;
+    fields
+        .iter()
+        .map(|&(name, kind)| {
+            let range = offset..offset + kind.len();
+            offset = range.end;
+            (name, range)
+        })
+        .collect()
+}
+
+impl Transfer {
+    /// Positions of the fields in the `Transfer::get_bytes` encoding.
+    ///
+    /// Optional fields (idempotency key and fee recipient) follow if present.
+    pub fn field_layout() -> Vec<(&'static str, Range<usize>)> {
+        field_ranges(TRANSFER_FIELDS)
+    }
+}
+
+impl Withdraw {
+    /// Positions of the fields in the `Withdraw::get_bytes` encoding.
+    ///
+    /// Optional fields (idempotency key and fee recipient) follow if present.
+    pub fn field_layout() -> Vec<(&'static str, Range<usize>)> {
+        field_ranges(WITHDRAW_FIELDS)
+    }
+}
+
+impl Close {
+    /// Positions of the fields in the `Close::get_bytes` encoding.
+    pub fn field_layout() -> Vec<(&'static str, Range<usize>)> {
+        field_ranges(CLOSE_FIELDS)
+    }
+}
+
+impl ChangePubKey {
+    /// Positions of the fields in the `ChangePubKey::get_bytes` encoding.
+    pub fn field_layout() -> Vec<(&'static str, Range<usize>)> {
+        field_ranges(CHANGE_PUBKEY_FIELDS)
+    }
+}
+
+impl ForcedExit {
+    /// Positions of the fields in the `ForcedExit::get_bytes` encoding.
+    pub fn field_layout() -> Vec<(&'static str, Range<usize>)> {
+        field_ranges(FORCED_EXIT_FIELDS)
+    }
+}
+
+impl MultiTransfer {
+    /// Positions of the fields preceding the outputs in the `MultiTransfer::get_bytes`
+    /// encoding.
+    ///
+    /// Outputs (`TransferOutput::BYTES` each) follow, then the `fee_token` (2 bytes),
+    /// the `fee` (2 bytes) and the `nonce` (4 bytes).
+    pub fn field_layout() -> Vec<(&'static str, Range<usize>)> {
+        field_ranges(MULTI_TRANSFER_HEADER)
+    }
+}
+
+/// Records the fields read from the transaction bytes along with their offsets.
+struct Explainer<'a> {
+    reader: FieldReader<'a>,
+    len: usize,
+    fields: Vec<ExplainedField>,
+}
+
+impl<'a> Explainer<'a> {
+    fn new(bytes: &'a [u8]) -> Self {
+        Self {
+            reader: FieldReader::new(bytes),
+            len: bytes.len(),
+            fields: Vec::new(),
+        }
+    }
+
+    fn offset(&self) -> usize {
+        self.len - self.reader.remaining()
+    }
+
+    fn field<T>(
+        &mut self,
+        name: impl Into<String>,
+        read: impl FnOnce(&mut FieldReader<'a>) -> Result<T, WireError>,
+        display: impl FnOnce(&T) -> String,
+    ) -> Result<T, WireError> {
+        let start = self.offset();
+        let value = read(&mut self.reader)?;
+        self.fields.push(ExplainedField {
+            name: name.into(),
+            range: start..self.offset(),
+            value: display(&value),
+        });
+        Ok(value)
+    }
+
+    fn u8(&mut self, name: impl Into<String>) -> Result<u8, WireError> {
+        self.field(name, FieldReader::u8, u8::to_string)
+    }
+
+    fn u16(&mut self, name: impl Into<String>) -> Result<u16, WireError> {
+        self.field(name, FieldReader::u16, u16::to_string)
+    }
+
+    fn u32(&mut self, name: impl Into<String>) -> Result<u32, WireError> {
+        self.field(name, FieldReader::u32, u32::to_string)
+    }
+
+    fn address(&mut self, name: impl Into<String>) -> Result<(), WireError> {
+        self.field(name, FieldReader::address, |address| {
+            format!("{:?}", address)
+        })?;
+        Ok(())
+    }
+
+    fn pub_key_hash(&mut self, name: impl Into<String>) -> Result<(), WireError> {
+        self.field(
+            name,
+            |reader| Ok(PubKeyHash::from_bytes(reader.take(20)?).unwrap()),
+            PubKeyHash::to_hex,
+        )?;
+        Ok(())
+    }
+
+    /// `field` is the name of the field reported in case of an error.
+    fn token_amount(
+        &mut self,
+        name: impl Into<String>,
+        field: &'static str,
+    ) -> Result<(), WireError> {
+        self.field(
+            name,
+            |reader| reader.token_amount(field),
+            ToString::to_string,
+        )?;
+        Ok(())
+    }
+
+    fn full_amount(&mut self, name: impl Into<String>) -> Result<(), WireError> {
+        self.field(
+            name,
+            |reader| {
+                let mut amount = [0u8; 16];
+                amount.copy_from_slice(reader.take(16)?);
+                Ok(u128::from_be_bytes(amount))
+            },
+            u128::to_string,
+        )?;
+        Ok(())
+    }
+
+    fn fee(&mut self) -> Result<(), WireError> {
+        self.field("fee", FieldReader::fee_amount, ToString::to_string)?;
+        Ok(())
+    }
+
+    /// Reads the fields of the layout. The leading transaction type is expected
+    /// to be read already.
+    fn layout(&mut self, fields: FieldsLayout) -> Result<(), WireError> {
+        for &(name, kind) in &fields[1..] {
+            match kind {
+                FieldKind::U8 => {
+                    self.u8(name)?;
+                }
+                FieldKind::U16 => {
+                    self.u16(name)?;
+                }
+                FieldKind::U32 => {
+                    self.u32(name)?;
+                }
+                FieldKind::Address => self.address(name)?,
+                FieldKind::PubKeyHash => self.pub_key_hash(name)?,
+                FieldKind::TokenAmount => self.token_amount(name, name)?,
+                FieldKind::FullAmount => self.full_amount(name)?,
+                FieldKind::Fee => self.fee()?,
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Decodes the transaction bytes (as returned by `ZkSyncTx::get_bytes`) field by field.
+///
+/// Returns an error if the bytes do not correspond to any supported transaction layout.
+pub fn explain_bytes(bytes: &[u8]) -> Result<TxExplanation, WireError> {
+    let mut explainer = Explainer::new(bytes);
+
+    let tx_type = explainer.u8("type")?;
+    let tx_type_name = match tx_type {
+        Transfer::TX_TYPE => {
+            explainer.layout(TRANSFER_FIELDS)?;
+            "Transfer"
+        }
+        Withdraw::TX_TYPE => {
+            explainer.layout(WITHDRAW_FIELDS)?;
+            "Withdraw"
+        }
+        Close::TX_TYPE => {
+            explainer.layout(CLOSE_FIELDS)?;
+            "Close"
+        }
+        ChangePubKey::TX_TYPE => {
+            explainer.layout(CHANGE_PUBKEY_FIELDS)?;
+            "ChangePubKey"
+        }
+        ForcedExit::TX_TYPE => {
+            explainer.layout(FORCED_EXIT_FIELDS)?;
+            "ForcedExit"
+        }
+        MultiTransfer::TX_TYPE => {
+            // The number of outputs is read separately, since the outputs depend on it.
+            let header = &MULTI_TRANSFER_HEADER[..MULTI_TRANSFER_HEADER.len() - 1];
+            explainer.layout(header)?;
+            let outputs_count = explainer.u8("outputs_count")?;
+            for i in 0..outputs_count {
+                explainer.address(format!("outputs[{}].to", i))?;
+                explainer.u16(format!("outputs[{}].token", i))?;
+                explainer.token_amount(format!("outputs[{}].amount", i), "output amount")?;
+            }
+            explainer.u16("fee_token")?;
+            explainer.fee()?;
+            explainer.u32("nonce")?;
+            "MultiTransfer"
+        }
+        _ => return Err(WireError::UnknownTxType(tx_type)),
+    };
+
+    if explainer.reader.remaining() != 0 {
+        return Err(WireError::InvalidLength {
+            tx_type,
+            len: bytes.len(),
+        });
+    }
+
+    Ok(TxExplanation {
+        tx_type: tx_type_name.to_owned(),
+        fields: explainer.fields,
+    })
+}
@@ -0,0 +1,37 @@
+//! Minimum fees of the transactions.
+
+use std::collections::HashMap;
+
+use num::BigUint;
+
+/// Minimum fee required for each transaction type.
+///
+/// Transaction types are identified by their `TX_TYPE` constants (e.g. `Transfer::TX_TYPE`).
+/// Types missing from the table don't have a minimum fee. The table is not a part of the
+/// protocol: it is up to the caller (e.g. the server configuration) to fill it.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct FeeTable {
+    min_fees: HashMap<u8, BigUint>,
+}
+
+impl FeeTable {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the minimum fee of the transaction type, replacing the previous value.
+    pub fn with_min_fee(mut self, tx_type: u8, min_fee: BigUint) -> Self {
+        self.set_min_fee(tx_type, min_fee);
+        self
+    }
+
+    /// Sets the minimum fee of the transaction type, replacing the previous value.
+    pub fn set_min_fee(&mut self, tx_type: u8, min_fee: BigUint) {
+        self.min_fees.insert(tx_type, min_fee);
+    }
+
+    /// Returns the minimum fee of the transaction type, if it is set.
+    pub fn min_fee(&self, tx_type: u8) -> Option<&BigUint> {
+        self.min_fees.get(&tx_type)
+    }
+}
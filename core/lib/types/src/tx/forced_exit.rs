@@ -43,7 +43,7 @@ pub struct ForcedExit {
     /// Transaction zkSync signature.
     pub signature: TxSignature,
     #[serde(skip)]
-    cached_signer: VerifiedSignatureCache,
+    pub(super) cached_signer: VerifiedSignatureCache,
 }
 
 impl ForcedExit {
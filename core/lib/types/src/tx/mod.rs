@@ -1,11 +1,17 @@
 //! zkSync network L2 transactions.
 
+mod batch;
 mod change_pubkey;
 mod close;
+mod explain;
+mod fee_table;
 mod forced_exit;
+mod multi_transfer;
 mod primitives;
+mod rlp_encoding;
 mod transfer;
 mod utils;
+mod wire;
 mod withdraw;
 mod zksync_tx;
 
@@ -16,18 +22,42 @@ mod tests;
 #[doc(hidden)]
 pub use self::close::Close;
 pub use self::{
+    batch::{
+        assign_nonces, batch_signing_message, generate_test_transactions, invalid_txs, net_deltas,
+        referenced_tokens, total_fees, tx_merkle_root, unique_accounts, verify_batch_signature,
+        InvalidTxReason,
+    },
     change_pubkey::ChangePubKey,
+    explain::{explain_bytes, ExplainedField, SigningRequest, TxExplanation},
+    fee_table::FeeTable,
     forced_exit::ForcedExit,
-    transfer::Transfer,
-    withdraw::Withdraw,
-    zksync_tx::{EthSignData, SignedZkSyncTx, ZkSyncTx},
+    multi_transfer::{MultiTransfer, TransferOutput},
+    transfer::{Direction, FeeError, Transfer, MAX_LABEL_LEN},
+    wire::{read_framed, write_framed, WireError, MAX_FRAME_LEN},
+    withdraw::{Withdraw, WithdrawError},
+    zksync_tx::{
+        AccountType, EthSignData, SignedZkSyncTx, VerificationPolicy, VerifyOutcome, ZkSyncTx,
+    },
 };
 
 // Re-export primitives associated with transactions.
 pub use self::primitives::{
-    eip1271_signature::EIP1271Signature, eth_signature::TxEthSignature,
-    packed_eth_signature::PackedEthSignature, packed_public_key::PackedPublicKey,
-    packed_signature::PackedSignature, signature::TxSignature, tx_hash::TxHash,
+    address_cache::AddressCache,
+    amount::Amount,
+    eip1271_signature::EIP1271Signature,
+    eth_signature::TxEthSignature,
+    packed_eth_signature::PackedEthSignature,
+    packed_public_key::PackedPublicKey,
+    packed_signature::PackedSignature,
+    precomputed_verifier::PrecomputedVerifier,
+    signature::{
+        verify_detached, verify_string, verify_with_external_pubkey, SignatureError, SignatureType,
+        TxSignature, MAX_SIGNED_MESSAGE_LEN,
+    },
+    tx_hash::TxHash,
 };
 
+#[cfg(feature = "verify_trace")]
+pub use self::primitives::signature::VerifyTrace;
+
 pub(crate) use self::primitives::signature_cache::VerifiedSignatureCache;
@@ -0,0 +1,236 @@
+use crate::{
+    helpers::{is_fee_amount_packable, pack_fee_amount, pack_token_amount},
+    AccountId, Nonce, TokenId,
+};
+use num::BigUint;
+use parity_crypto::digest::sha256;
+
+use crate::account::PubKeyHash;
+use crate::Engine;
+use anyhow::{bail, ensure};
+use serde::{Deserialize, Serialize};
+use zksync_basic_types::Address;
+use zksync_crypto::franklin_crypto::eddsa::PrivateKey;
+use zksync_crypto::params::{max_account_id, max_token_id};
+use zksync_utils::BigUintSerdeAsRadix10Str;
+
+use super::{Amount, TxSignature, VerifiedSignatureCache};
+
+/// Single payment of the `MultiTransfer` transaction.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TransferOutput {
+    /// Address of account to transfer funds to.
+    pub to: Address,
+    /// Type of token for transfer.
+    pub token: TokenId,
+    /// Amount of funds to transfer.
+    pub amount: Amount,
+}
+
+impl TransferOutput {
+    /// Encoded size of the output: recipient, token and packed amount.
+    pub const BYTES: usize = 20 + 2 + 5;
+
+    pub fn new(to: Address, token: TokenId, amount: Amount) -> Self {
+        Self { to, token, amount }
+    }
+
+    /// Key of the output in the canonical order of the `MultiTransfer` outputs.
+    fn canonical_key(&self) -> (&[u8], TokenId, &BigUint) {
+        (self.to.as_bytes(), self.token, &self.amount)
+    }
+}
+
+/// `MultiTransfer` transaction moves funds from one zkSync account to several recipients
+/// within a single signed transaction, so the fee is paid only once.
+///
+/// Note that this transaction is not supported by the zkSync circuit yet, so it
+/// cannot be executed by the network.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MultiTransfer {
+    /// zkSync network account ID of the transaction initiator.
+    pub account_id: AccountId,
+    /// Address of account to transfer funds from.
+    pub from: Address,
+    /// List of payments performed by the transaction.
+    ///
+    /// Outputs are kept in the canonical order (see `MultiTransfer::canonicalize_outputs`),
+    /// so the signed bytes don't depend on the order in which the payments were specified.
+    pub outputs: Vec<TransferOutput>,
+    /// Token in which fee will be paid.
+    pub fee_token: TokenId,
+    /// Fee for the transaction.
+    #[serde(with = "BigUintSerdeAsRadix10Str")]
+    pub fee: BigUint,
+    /// Current account nonce.
+    pub nonce: Nonce,
+    /// Transaction zkSync signature.
+    pub signature: TxSignature,
+    #[serde(skip)]
+    pub(super) cached_signer: VerifiedSignatureCache,
+}
+
+impl MultiTransfer {
+    /// Unique identifier of the transaction type in zkSync network.
+    pub const TX_TYPE: u8 = 9;
+    /// Maximum number of outputs in a single transaction.
+    pub const MAX_OUTPUTS: usize = 16;
+
+    /// Creates transaction from all the required fields.
+    ///
+    /// Outputs are sorted in the canonical order, so the `signature` (if any) must be made
+    /// for the canonicalized transaction.
+    ///
+    /// While `signature` field is mandatory for new transactions, it may be `None`
+    /// in some cases (e.g. when restoring the network state from the L1 contract data).
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        account_id: AccountId,
+        from: Address,
+        mut outputs: Vec<TransferOutput>,
+        fee_token: TokenId,
+        fee: BigUint,
+        nonce: Nonce,
+        signature: Option<TxSignature>,
+    ) -> Self {
+        Self::canonicalize_outputs(&mut outputs);
+        let mut tx = Self {
+            account_id,
+            from,
+            outputs,
+            fee_token,
+            fee,
+            nonce,
+            signature: signature.clone().unwrap_or_default(),
+            cached_signer: VerifiedSignatureCache::NotCached,
+        };
+        if signature.is_some() {
+            tx.cached_signer = VerifiedSignatureCache::Cached(tx.verify_signature());
+        }
+        tx
+    }
+
+    /// Creates a signed transaction using private key and
+    /// checks for the transaction correcteness.
+    #[allow(clippy::too_many_arguments)]
+    pub fn new_signed(
+        account_id: AccountId,
+        from: Address,
+        outputs: Vec<TransferOutput>,
+        fee_token: TokenId,
+        fee: BigUint,
+        nonce: Nonce,
+        private_key: &PrivateKey<Engine>,
+    ) -> Result<Self, anyhow::Error> {
+        ensure!(
+            outputs.len() <= Self::MAX_OUTPUTS,
+            "MultiTransfer cannot contain more than {} outputs",
+            Self::MAX_OUTPUTS
+        );
+
+        let mut tx = Self::new(account_id, from, outputs, fee_token, fee, nonce, None);
+        tx.signature = TxSignature::sign_musig(private_key, &tx.signing_message());
+        if !tx.check_correctness() {
+            bail!("MultiTransfer is incorrect, check amounts");
+        }
+        Ok(tx)
+    }
+
+    /// Sorts the outputs in the canonical order: by the recipient address bytes, then by
+    /// the token, then by the amount.
+    pub fn canonicalize_outputs(outputs: &mut [TransferOutput]) {
+        outputs.sort_by(|a, b| a.canonical_key().cmp(&b.canonical_key()));
+    }
+
+    /// Checks whether the outputs of the transaction are in the canonical order.
+    pub fn has_canonical_outputs(&self) -> bool {
+        self.outputs
+            .windows(2)
+            .all(|pair| pair[0].canonical_key() <= pair[1].canonical_key())
+    }
+
+    /// Encodes the transaction data as the byte sequence according to the zkSync protocol.
+    ///
+    /// The outputs list is prefixed with the one-byte number of outputs.
+    pub fn get_bytes(&self) -> Vec<u8> {
+        let mut out = Vec::new();
+        out.extend_from_slice(&[Self::TX_TYPE]);
+        out.extend_from_slice(&self.account_id.to_be_bytes());
+        out.extend_from_slice(&self.from.as_bytes());
+        // Amount of outputs is checked in `check_correctness`, so truncation here can only
+        // produce the bytes of an incorrect transaction.
+        out.push(self.outputs.len() as u8);
+        for output in &self.outputs {
+            out.extend_from_slice(&output.to.as_bytes());
+            out.extend_from_slice(&output.token.to_be_bytes());
+            out.extend_from_slice(&pack_token_amount(&output.amount));
+        }
+        out.extend_from_slice(&self.fee_token.to_be_bytes());
+        out.extend_from_slice(&pack_fee_amount(&self.fee));
+        out.extend_from_slice(&self.nonce.to_be_bytes());
+        out
+    }
+
+    /// Returns the message signed by the zkSync signature of the transaction: the SHA-256
+    /// hash of the `get_bytes` encoding.
+    ///
+    /// Unlike the other transactions, the bytes themselves cannot be signed: the Rescue hash
+    /// of the musig signature only covers the first 92 bytes of the message, which is less
+    /// than the encoding of three outputs.
+    pub fn signing_message(&self) -> Vec<u8> {
+        sha256(&self.get_bytes()).to_vec()
+    }
+
+    /// Verifies the transaction correctness:
+    ///
+    /// - `account_id` field must be within supported range.
+    /// - `fee_token` field must be within supported range.
+    /// - `fee` field must represent a packable value.
+    /// - there should be at least one and at most `MAX_OUTPUTS` outputs.
+    /// - outputs must be in the canonical order.
+    /// - `token` field of every output must be within supported range.
+    /// - `amount` field of every output must represent a packable value (guaranteed by `Amount`).
+    /// - recipient of every output must not be `Adddress::zero()`.
+    /// - zkSync signature must correspond to the PubKeyHash of the account.
+    pub fn check_correctness(&mut self) -> bool {
+        let outputs_valid = self
+            .outputs
+            .iter()
+            .all(|output| output.token <= max_token_id() && output.to != Address::zero());
+
+        let mut valid = outputs_valid
+            && !self.outputs.is_empty()
+            && self.outputs.len() <= Self::MAX_OUTPUTS
+            && self.has_canonical_outputs()
+            && self.fee <= BigUint::from(u128::max_value())
+            && is_fee_amount_packable(&self.fee)
+            && self.account_id <= max_account_id()
+            && self.fee_token <= max_token_id();
+        if valid {
+            let signer = self.verify_signature();
+            valid = valid && signer.is_some();
+            self.cached_signer = VerifiedSignatureCache::Cached(signer);
+        };
+        valid
+    }
+
+    /// Returns `true` if the fee token differs from the token of any output.
+    pub fn is_cross_token_fee(&self) -> bool {
+        self.outputs
+            .iter()
+            .any(|output| output.token != self.fee_token)
+    }
+
+    /// Restores the `PubKeyHash` from the transaction signature.
+    pub fn verify_signature(&self) -> Option<PubKeyHash> {
+        if let VerifiedSignatureCache::Cached(cached_signer) = &self.cached_signer {
+            cached_signer.clone()
+        } else if let Some(pub_key) = self.signature.verify_musig(&self.signing_message()) {
+            Some(PubKeyHash::from_pubkey(&pub_key))
+        } else {
+            None
+        }
+    }
+}
@@ -0,0 +1,63 @@
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+
+use crate::account::PubKeyHash;
+use crate::tx::PackedPublicKey;
+
+/// Thread-safe cache of the `PubKeyHash` values derived from the public keys.
+///
+/// Deriving `PubKeyHash` requires computing a Rescue hash of the public key, while
+/// the same keys are used to sign many transactions, so servers can cache the derivation
+/// results. The cache is bounded: once `capacity` keys are stored, new keys are derived
+/// without being cached.
+#[derive(Debug)]
+pub struct AddressCache {
+    entries: Mutex<HashMap<Vec<u8>, PubKeyHash>>,
+    capacity: usize,
+    hits: AtomicU64,
+}
+
+impl AddressCache {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            entries: Mutex::new(HashMap::new()),
+            capacity,
+            hits: AtomicU64::new(0),
+        }
+    }
+
+    /// Returns the `PubKeyHash` of the public key, deriving and caching it on miss.
+    pub fn resolve(&self, pub_key: &PackedPublicKey) -> PubKeyHash {
+        let key = pub_key
+            .serialize_packed()
+            .expect("packing public key into a buffer");
+
+        if let Some(address) = self.entries.lock().unwrap().get(&key) {
+            self.hits.fetch_add(1, Ordering::Relaxed);
+            return address.clone();
+        }
+
+        // The hash is computed outside of the lock, so concurrent misses may compute it twice.
+        let address = PubKeyHash::from_pubkey(&pub_key.0);
+        let mut entries = self.entries.lock().unwrap();
+        if entries.len() < self.capacity {
+            entries.insert(key, address.clone());
+        }
+        address
+    }
+
+    /// Number of the cached public keys.
+    pub fn len(&self) -> usize {
+        self.entries.lock().unwrap().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Number of the `resolve` calls served from the cache.
+    pub fn hits(&self) -> u64 {
+        self.hits.load(Ordering::Relaxed)
+    }
+}
@@ -0,0 +1,74 @@
+use std::ops::Deref;
+
+use num::BigUint;
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+use zksync_utils::BigUintSerdeAsRadix10Str;
+
+use crate::{error::Error, helpers::is_token_amount_packable};
+
+/// Token amount which is guaranteed to be representable in the transaction bytes.
+///
+/// Unlike the plain `BigUint` amount, which has to be checked (or normalized) before
+/// signing, `Amount` can only be created from a value that fits into `u128` and can be
+/// packed without the precision loss.
+///
+/// Serialized as a decimal string, same as the `BigUint` amounts of transactions.
+///
+/// `Transfer` and `Withdraw` keep the plain `BigUint` amounts. The withdrawal amount
+/// is stored in the pubdata as a full `u128` and is never packed, so the packability
+/// invariant doesn't apply to it. Transfers are created unchecked by the circuit, state
+/// and SDK code (including tests for the unpackable amounts), and are validated via
+/// `Transfer::check_correctness` before being accepted.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct Amount(BigUint);
+
+impl Amount {
+    /// Creates an amount, checking that the value can be packed.
+    pub fn new(value: BigUint) -> Result<Self, Error> {
+        if value > BigUint::from(u128::max_value()) {
+            return Err(Error::Unpackable {
+                field: "amount",
+                value,
+            });
+        }
+        if !is_token_amount_packable(&value) {
+            return Err(Error::Imprecise {
+                field: "amount",
+                value,
+            });
+        }
+        Ok(Self(value))
+    }
+
+    /// Returns the underlying value.
+    pub fn into_inner(self) -> BigUint {
+        self.0
+    }
+}
+
+impl Deref for Amount {
+    type Target = BigUint;
+
+    fn deref(&self) -> &BigUint {
+        &self.0
+    }
+}
+
+impl From<Amount> for BigUint {
+    fn from(amount: Amount) -> BigUint {
+        amount.0
+    }
+}
+
+impl Serialize for Amount {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        BigUintSerdeAsRadix10Str::serialize(&self.0, serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Amount {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let value = BigUintSerdeAsRadix10Str::deserialize(deserializer)?;
+        Amount::new(value).map_err(de::Error::custom)
+    }
+}
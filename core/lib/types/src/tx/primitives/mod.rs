@@ -1,8 +1,11 @@
+pub mod address_cache;
+pub mod amount;
 pub mod eip1271_signature;
 pub mod eth_signature;
 pub mod packed_eth_signature;
 pub mod packed_public_key;
 pub mod packed_signature;
+pub mod precomputed_verifier;
 pub mod signature;
 pub mod signature_cache;
 pub mod tx_hash;
@@ -7,6 +7,8 @@ use zksync_crypto::franklin_crypto::{
 use zksync_crypto::params::JUBJUB_PARAMS;
 use zksync_crypto::Engine;
 
+use crate::helpers::strip_hex_prefix;
+
 #[derive(Clone)]
 pub struct PackedPublicKey(pub PublicKey<Engine>);
 
@@ -17,13 +19,37 @@ impl PackedPublicKey {
         Ok(packed_point.to_vec())
     }
 
+    /// Restores the public key from its packed representation.
+    ///
+    /// The identity point is rejected: it is not a valid public key for any private key,
+    /// and accepting it would allow anyone to probe the verification logic with a
+    /// degenerate key.
     pub fn deserialize_packed(bytes: &[u8]) -> Result<Self, anyhow::Error> {
         ensure!(bytes.len() == 32, "PublicKey size mismatch");
 
-        Ok(PackedPublicKey(PublicKey::<Engine>(
-            edwards::Point::read(&*bytes, &JUBJUB_PARAMS as &AltJubjubBn256)
-                .map_err(|e| format_err!("Failed to restore point: {}", e.to_string()))?,
-        )))
+        let point = edwards::Point::read(&*bytes, &JUBJUB_PARAMS as &AltJubjubBn256)
+            .map_err(|e| format_err!("Failed to restore point: {}", e.to_string()))?;
+        ensure!(
+            point != edwards::Point::zero(),
+            "PublicKey must not be the identity point"
+        );
+
+        Ok(PackedPublicKey(PublicKey::<Engine>(point)))
+    }
+
+    /// Encodes the packed public key as unpadded URL-safe base64, a compact alternative
+    /// to the hex representation used in JSON.
+    pub fn to_base64url(&self) -> std::io::Result<String> {
+        Ok(base64::encode_config(
+            self.serialize_packed()?,
+            base64::URL_SAFE_NO_PAD,
+        ))
+    }
+
+    /// Restores the public key from the unpadded URL-safe base64 of its packed representation.
+    pub fn from_base64url(string: &str) -> Result<Self, anyhow::Error> {
+        let bytes = base64::decode_config(string, base64::URL_SAFE_NO_PAD)?;
+        Self::deserialize_packed(&bytes)
     }
 }
 
@@ -48,7 +74,8 @@ impl<'de> Deserialize<'de> for PackedPublicKey {
     {
         use serde::de::Error;
         String::deserialize(deserializer).and_then(|string| {
-            let bytes = hex::decode(&string).map_err(|e| Error::custom(e.to_string()))?;
+            let bytes =
+                hex::decode(strip_hex_prefix(&string)).map_err(|e| Error::custom(e.to_string()))?;
             PackedPublicKey::deserialize_packed(&bytes).map_err(|e| Error::custom(e.to_string()))
         })
     }
@@ -1,4 +1,4 @@
-use crate::Engine;
+use crate::{helpers::strip_hex_prefix, Engine};
 use anyhow::{ensure, format_err};
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use zksync_crypto::franklin_crypto::{
@@ -42,6 +42,21 @@ impl PackedSignature {
 
         Ok(Self(Signature { r, s }))
     }
+
+    /// Encodes the packed signature as unpadded URL-safe base64, a compact alternative
+    /// to the hex representation used in JSON.
+    pub fn to_base64url(&self) -> std::io::Result<String> {
+        Ok(base64::encode_config(
+            self.serialize_packed()?,
+            base64::URL_SAFE_NO_PAD,
+        ))
+    }
+
+    /// Restores the signature from the unpadded URL-safe base64 of its packed representation.
+    pub fn from_base64url(string: &str) -> Result<Self, anyhow::Error> {
+        let bytes = base64::decode_config(string, base64::URL_SAFE_NO_PAD)?;
+        Self::deserialize_packed(&bytes)
+    }
 }
 
 impl Serialize for PackedSignature {
@@ -65,7 +80,8 @@ impl<'de> Deserialize<'de> for PackedSignature {
     {
         use serde::de::Error;
         String::deserialize(deserializer).and_then(|string| {
-            let bytes = hex::decode(&string).map_err(|e| Error::custom(e.to_string()))?;
+            let bytes =
+                hex::decode(strip_hex_prefix(&string)).map_err(|e| Error::custom(e.to_string()))?;
             PackedSignature::deserialize_packed(&bytes).map_err(|e| Error::custom(e.to_string()))
         })
     }
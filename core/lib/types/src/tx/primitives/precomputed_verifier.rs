@@ -0,0 +1,85 @@
+use std::collections::HashMap;
+
+use crate::account::PubKeyHash;
+use crate::tx::{PackedPublicKey, TxSignature};
+
+use super::signature::ct_eq_bytes;
+
+/// Data precomputed for a single known signer.
+#[derive(Debug, Clone)]
+struct PrecomputedKey {
+    pub_key_hash: PubKeyHash,
+}
+
+/// Verifier of the zkSync (Rescue musig) signatures made by the set of known signers.
+///
+/// For every ingested public key the verifier checks once that the key is well-formed and
+/// derives its `PubKeyHash`, so the repeated verifications skip these steps. Signatures of
+/// the keys that weren't ingested are verified using the direct path (see
+/// `TxSignature::verify_with_pubkey`), so the result is always the same as for the latter.
+#[derive(Debug, Default, Clone)]
+pub struct PrecomputedVerifier {
+    keys: HashMap<Vec<u8>, PrecomputedKey>,
+}
+
+impl PrecomputedVerifier {
+    pub fn new<'a>(pub_keys: impl IntoIterator<Item = &'a PackedPublicKey>) -> Self {
+        let mut verifier = Self::default();
+        for pub_key in pub_keys {
+            verifier.add_key(pub_key);
+        }
+        verifier
+    }
+
+    /// Precomputes the verification data for the public key.
+    ///
+    /// Malformed keys are ignored: signatures made with them never verify.
+    pub fn add_key(&mut self, pub_key: &PackedPublicKey) {
+        if !TxSignature::is_well_formed_key(pub_key) {
+            return;
+        }
+
+        let key = pub_key
+            .serialize_packed()
+            .expect("packing public key into a buffer");
+        self.keys.entry(key).or_insert_with(|| PrecomputedKey {
+            pub_key_hash: PubKeyHash::from_pubkey(&pub_key.0),
+        });
+    }
+
+    /// Number of the ingested public keys.
+    pub fn len(&self) -> usize {
+        self.keys.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.keys.is_empty()
+    }
+
+    /// Verifies that `sign` is a correct signature of `msg` made by `pub_key`.
+    ///
+    /// Returns the `PubKeyHash` of the signer, or `None` if the signature is incorrect
+    /// or the embedded public key differs from `pub_key`.
+    pub fn verify(
+        &self,
+        pub_key: &PackedPublicKey,
+        sign: &TxSignature,
+        msg: &[u8],
+    ) -> Option<PubKeyHash> {
+        let key = pub_key.serialize_packed().ok()?;
+        let precomputed = match self.keys.get(&key) {
+            Some(precomputed) => precomputed,
+            None => {
+                return sign
+                    .verify_with_pubkey(msg, pub_key)
+                    .map(|pub_key| PubKeyHash::from_pubkey(&pub_key))
+            }
+        };
+
+        let embedded = sign.pub_key.serialize_packed().ok()?;
+        if !ct_eq_bytes(&embedded, &key) || !sign.verify_musig_rescue_with_checked_key(msg) {
+            return None;
+        }
+        Some(precomputed.pub_key_hash.clone())
+    }
+}
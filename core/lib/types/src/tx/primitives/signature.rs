@@ -1,18 +1,138 @@
+use std::str::FromStr;
+use std::time::{Duration, Instant};
+
 use zksync_crypto::public_key_from_private;
 
-use crate::Engine;
+use crate::{Engine, PubKeyHash};
 use anyhow::ensure;
 use serde::{Deserialize, Serialize};
+use thiserror::Error;
 use zksync_crypto::franklin_crypto::{
+    alt_babyjubjub::edwards,
     eddsa::{PrivateKey, PublicKey, Seed},
     jubjub::FixedGenerators,
     rescue::RescueEngine,
 };
-use zksync_crypto::params::{JUBJUB_PARAMS, RESCUE_PARAMS};
+use zksync_crypto::params::{JUBJUB_PARAMS, PAD_MSG_BEFORE_HASH_BITS_LEN, RESCUE_PARAMS};
 use zksync_crypto::primitives::rescue_hash_tx_msg;
 
+use crate::helpers::strip_hex_prefix;
 use crate::tx::{PackedPublicKey, PackedSignature};
 
+/// Maximum length of the message fully covered by the musig signature.
+///
+/// The Rescue hash of the signed message only takes its first `PAD_MSG_BEFORE_HASH_BITS_LEN`
+/// bits into account, so all the messages sharing the first `MAX_SIGNED_MESSAGE_LEN` bytes
+/// have the same signature. Functions verifying the externally supplied messages reject
+/// the longer ones.
+pub const MAX_SIGNED_MESSAGE_LEN: usize = PAD_MSG_BEFORE_HASH_BITS_LEN / 8;
+
+/// Musig signature scheme variants supported for the zkSync transactions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum SignatureType {
+    /// Musig over the Rescue hash of the message. This is the scheme used by default.
+    MusigRescue,
+    /// Musig over the SHA-256 hash of the message.
+    ///
+    /// Verification of this scheme is only available with the `musig_sha256` feature.
+    MusigSha256,
+}
+
+impl SignatureType {
+    /// All supported signature schemes, in the order they are tried during verification.
+    pub const ALL: [SignatureType; 2] = [SignatureType::MusigRescue, SignatureType::MusigSha256];
+
+    /// Returns all the supported signature schemes in the order they should be tried
+    /// during verification: the `preferred` scheme (if any) goes first, the rest follow
+    /// in the default order.
+    pub fn verification_order(preferred: Option<SignatureType>) -> Vec<SignatureType> {
+        let mut order = Vec::with_capacity(Self::ALL.len());
+        order.extend(preferred);
+        order.extend(
+            Self::ALL
+                .iter()
+                .copied()
+                .filter(|&variant| Some(variant) != preferred),
+        );
+        order
+    }
+}
+
+impl FromStr for SignatureType {
+    type Err = anyhow::Error;
+
+    /// Parses the scheme from its name, same as used in the serialized form (e.g. `MusigRescue`).
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "MusigRescue" => Ok(SignatureType::MusigRescue),
+            "MusigSha256" => Ok(SignatureType::MusigSha256),
+            _ => anyhow::bail!("Unknown signature scheme {}", s),
+        }
+    }
+}
+
+impl Default for SignatureType {
+    fn default() -> Self {
+        Self::MusigRescue
+    }
+}
+
+impl SignatureType {
+    fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// Returns `false` if the verification of the scheme was compiled out,
+    /// e.g. `MusigSha256` without the `musig_sha256` feature.
+    pub fn is_enabled(self) -> bool {
+        match self {
+            SignatureType::MusigRescue => true,
+            SignatureType::MusigSha256 => cfg!(feature = "musig_sha256"),
+        }
+    }
+
+    /// Same as `is_enabled`, but returns the `SignatureError::SchemeDisabled` error
+    /// for the disabled schemes.
+    pub fn ensure_enabled(self) -> Result<(), SignatureError> {
+        if !self.is_enabled() {
+            return Err(SignatureError::SchemeDisabled(self));
+        }
+        Ok(())
+    }
+}
+
+/// Intermediate values of the signature verification, see `TxSignature::verify_traced`.
+#[cfg(feature = "verify_trace")]
+#[derive(Debug, Clone, PartialEq)]
+pub struct VerifyTrace {
+    /// Rescue hash of the message, which is what is actually signed.
+    pub hashed_msg: Vec<u8>,
+    /// Packed `R` point of the signature.
+    pub r: [u8; 32],
+    /// Little-endian representation of the `s` scalar of the signature.
+    pub s: [u8; 32],
+    /// Result of the structural check, see `TxSignature::is_well_formed`.
+    pub well_formed: bool,
+    /// Whether the signature is correct.
+    pub valid: bool,
+}
+
+/// Error returned when the transaction signature cannot be verified.
+#[derive(Debug, Clone, PartialEq, Error)]
+pub enum SignatureError {
+    #[error("Signature is incorrect for every supported signature scheme")]
+    IncorrectSignature,
+    /// Signature is correct, but the signer isn't the expected one.
+    /// May indicate a change in the `PubKeyHash` derivation.
+    #[error("Signature is valid, but it was made by {recovered:?} instead of {expected:?}")]
+    SignerMismatch {
+        recovered: PubKeyHash,
+        expected: PubKeyHash,
+    },
+    #[error("Signature scheme {0:?} is disabled")]
+    SchemeDisabled(SignatureType),
+}
+
 /// zkSync transaction signature.
 ///
 /// Represents a MuSig Rescue signature for the message.
@@ -21,6 +141,12 @@ use crate::tx::{PackedPublicKey, PackedSignature};
 pub struct TxSignature {
     pub pub_key: PackedPublicKey,
     pub signature: PackedSignature,
+    /// Signature scheme used to create the signature, Rescue if omitted.
+    ///
+    /// Only serves as a hint for the verification: it isn't a part of the signed
+    /// message and isn't included into the packed representation.
+    #[serde(default, skip_serializing_if = "SignatureType::is_default")]
+    pub scheme: SignatureType,
 }
 
 impl TxSignature {
@@ -49,6 +175,36 @@ impl TxSignature {
         Self {
             pub_key: PackedPublicKey(public_key_from_private(pk)),
             signature: PackedSignature(signature),
+            scheme: SignatureType::MusigRescue,
+        }
+    }
+
+    /// Signs the message via provided private key using the SHA-256 musig variant.
+    pub fn sign_musig_sha256(pk: &PrivateKey<Engine>, msg: &[u8]) -> Self {
+        let seed = Seed::deterministic_seed(&pk, msg);
+        let signature = pk.musig_sha256_sign(
+            msg,
+            &seed,
+            FixedGenerators::SpendingKeyGenerator,
+            &JUBJUB_PARAMS,
+        );
+
+        Self {
+            pub_key: PackedPublicKey(public_key_from_private(pk)),
+            signature: PackedSignature(signature),
+            scheme: SignatureType::MusigSha256,
+        }
+    }
+
+    /// Signs the message via provided private key using the requested signature scheme.
+    pub fn sign_musig_with_type(
+        pk: &PrivateKey<Engine>,
+        msg: &[u8],
+        variant: SignatureType,
+    ) -> Self {
+        match variant {
+            SignatureType::MusigRescue => Self::sign_musig_rescue(pk, msg),
+            SignatureType::MusigSha256 => Self::sign_musig_sha256(pk, msg),
         }
     }
 
@@ -60,16 +216,134 @@ impl TxSignature {
         self.verify_musig_rescue(msg)
     }
 
+    /// Cheap structural check performed before the actual signature verification.
+    ///
+    /// Signature is malformed if either the public key or the `R` point of the signature has
+    /// a small order (e.g. is the identity point): such points are never produced by signing,
+    /// so the verification would fail anyway. The points being on the curve and the scalar
+    /// being in range are already guaranteed by the deserialization.
+    pub fn is_well_formed(&self) -> bool {
+        Self::is_well_formed_key(&self.pub_key) && self.has_well_formed_r()
+    }
+
+    /// Checks that the public key doesn't have a small order, see `is_well_formed`.
+    pub(crate) fn is_well_formed_key(pub_key: &PackedPublicKey) -> bool {
+        (pub_key.0).0.mul_by_cofactor(&JUBJUB_PARAMS) != edwards::Point::zero()
+    }
+
+    fn has_well_formed_r(&self) -> bool {
+        self.signature.0.r.mul_by_cofactor(&JUBJUB_PARAMS) != edwards::Point::zero()
+    }
+
+    /// Same as `verify_musig`, but records the intermediate values of the verification
+    /// for debugging purposes.
+    ///
+    /// Only available with the `verify_trace` feature.
+    #[cfg(feature = "verify_trace")]
+    pub fn verify_traced(&self, msg: &[u8]) -> VerifyTrace {
+        let packed = self
+            .signature
+            .serialize_packed()
+            .expect("packing signature into a buffer");
+        let mut r = [0u8; 32];
+        r.copy_from_slice(&packed[..32]);
+        let mut s = [0u8; 32];
+        s.copy_from_slice(&packed[32..]);
+
+        let hashed_msg = rescue_hash_tx_msg(msg);
+        let well_formed = self.is_well_formed();
+        let valid = well_formed
+            && self.pub_key.0.verify_musig_rescue(
+                &hashed_msg,
+                &self.signature.0,
+                FixedGenerators::SpendingKeyGenerator,
+                &RESCUE_PARAMS,
+                &JUBJUB_PARAMS,
+            );
+
+        VerifyTrace {
+            hashed_msg,
+            r,
+            s,
+            well_formed,
+            valid,
+        }
+    }
+
+    /// Same as `verify_musig`, but also measures the time spent on the verification,
+    /// e.g. to report the verification latency metrics.
+    ///
+    /// Returns `true` if the signature is correct.
+    pub fn verify_timed(&self, msg: &[u8]) -> (bool, Duration) {
+        let start = Instant::now();
+        let valid = self.verify_musig(msg).is_some();
+        (valid, start.elapsed())
+    }
+
     /// Restores a public key from the signature given the initial message.
     /// Returns `None` if an address cannot be recovered from the provided (signature, message) pair.
     pub fn verify_musig_rescue(&self, msg: &[u8]) -> Option<PublicKey<Engine>> {
+        self.verify_musig_rescue_with_generator(msg, FixedGenerators::SpendingKeyGenerator)
+    }
+
+    /// Same as `verify_musig_rescue`, but checks the signature against the provided fixed generator
+    /// instead of the `SpendingKeyGenerator` used by zkSync.
+    ///
+    /// Note that the public key must be derived using the same generator.
+    pub fn verify_musig_rescue_with_generator(
+        &self,
+        msg: &[u8],
+        generator: FixedGenerators,
+    ) -> Option<PublicKey<Engine>> {
+        if !self.is_well_formed() {
+            return None;
+        }
+
         let hashed_msg = rescue_hash_tx_msg(msg);
         let valid = self.pub_key.0.verify_musig_rescue(
+            &hashed_msg,
+            &self.signature.0,
+            generator,
+            &RESCUE_PARAMS,
+            &JUBJUB_PARAMS,
+        );
+        if valid {
+            Some(self.pub_key.0.clone())
+        } else {
+            None
+        }
+    }
+
+    /// Same as `verify_musig_rescue`, but skips the public key structural check,
+    /// which must have been performed by the caller.
+    pub(crate) fn verify_musig_rescue_with_checked_key(&self, msg: &[u8]) -> bool {
+        if !self.has_well_formed_r() {
+            return false;
+        }
+
+        let hashed_msg = rescue_hash_tx_msg(msg);
+        self.pub_key.0.verify_musig_rescue(
             &hashed_msg,
             &self.signature.0,
             FixedGenerators::SpendingKeyGenerator,
             &RESCUE_PARAMS,
             &JUBJUB_PARAMS,
+        )
+    }
+
+    /// Restores a public key from the signature made with the SHA-256 musig variant.
+    /// Returns `None` if an address cannot be recovered from the provided (signature, message) pair.
+    #[cfg(feature = "musig_sha256")]
+    pub fn verify_musig_sha256(&self, msg: &[u8]) -> Option<PublicKey<Engine>> {
+        if !self.is_well_formed() {
+            return None;
+        }
+
+        let valid = self.pub_key.0.verify_musig_sha256(
+            msg,
+            &self.signature.0,
+            FixedGenerators::SpendingKeyGenerator,
+            &JUBJUB_PARAMS,
         );
         if valid {
             Some(self.pub_key.0.clone())
@@ -78,6 +352,105 @@ impl TxSignature {
         }
     }
 
+    /// Restores a public key from the signature using the requested signature scheme.
+    ///
+    /// Signatures of the disabled schemes (see `SignatureType::is_enabled`) are never valid.
+    pub fn verify_musig_with_type(
+        &self,
+        msg: &[u8],
+        variant: SignatureType,
+    ) -> Option<PublicKey<Engine>> {
+        match variant {
+            SignatureType::MusigRescue => self.verify_musig_rescue(msg),
+            #[cfg(feature = "musig_sha256")]
+            SignatureType::MusigSha256 => self.verify_musig_sha256(msg),
+            #[cfg(not(feature = "musig_sha256"))]
+            SignatureType::MusigSha256 => None,
+        }
+    }
+
+    /// Same as `verify_musig_with_type`, but accepts the message as a hexadecimal string
+    /// with an optional `0x` prefix.
+    ///
+    /// Returns an error if the message is not a correct hexadecimal string. Decoded messages
+    /// longer than `MAX_SIGNED_MESSAGE_LEN` are not fully covered by the signature, so they
+    /// are never valid.
+    pub fn verify_hex(
+        &self,
+        msg_hex: &str,
+        variant: SignatureType,
+    ) -> Result<Option<PublicKey<Engine>>, hex::FromHexError> {
+        let msg = hex::decode(strip_hex_prefix(msg_hex))?;
+        if msg.len() > MAX_SIGNED_MESSAGE_LEN {
+            return Ok(None);
+        }
+        Ok(self.verify_musig_with_type(&msg, variant))
+    }
+
+    /// Restores a public key from the signature given the already hashed message.
+    ///
+    /// Unlike `verify_musig_with_type`, which accepts the full message (e.g. transaction bytes),
+    /// this method expects the 32-byte digest that was actually signed:
+    ///
+    /// - for `MusigRescue` it is the Rescue hash of the message (the full-message path computes
+    ///   it internally, so passing the digest there would hash it twice);
+    /// - `MusigSha256` has no separate pre-hashing step, so the digest is verified as the signed
+    ///   message itself. It is up to the client how the digest was obtained.
+    ///
+    /// Signatures of the disabled schemes (see `SignatureType::is_enabled`) are never valid.
+    pub fn verify_prehashed(
+        &self,
+        hash: &[u8; 32],
+        variant: SignatureType,
+    ) -> Option<PublicKey<Engine>> {
+        if !self.is_well_formed() {
+            return None;
+        }
+
+        let valid = match variant {
+            SignatureType::MusigRescue => self.pub_key.0.verify_musig_rescue(
+                hash,
+                &self.signature.0,
+                FixedGenerators::SpendingKeyGenerator,
+                &RESCUE_PARAMS,
+                &JUBJUB_PARAMS,
+            ),
+            #[cfg(feature = "musig_sha256")]
+            SignatureType::MusigSha256 => self.pub_key.0.verify_musig_sha256(
+                hash,
+                &self.signature.0,
+                FixedGenerators::SpendingKeyGenerator,
+                &JUBJUB_PARAMS,
+            ),
+            #[cfg(not(feature = "musig_sha256"))]
+            SignatureType::MusigSha256 => false,
+        };
+        if valid {
+            Some(self.pub_key.0.clone())
+        } else {
+            None
+        }
+    }
+
+    /// Verifies the signature and checks that it was made by the expected public key.
+    /// Returns `None` if the signature is incorrect or the embedded public key differs
+    /// from `expected`.
+    ///
+    /// Public keys are compared in their packed form.
+    pub fn verify_with_pubkey(
+        &self,
+        msg: &[u8],
+        expected: &PackedPublicKey,
+    ) -> Option<PublicKey<Engine>> {
+        let embedded = self.pub_key.serialize_packed().ok()?;
+        let expected = expected.serialize_packed().ok()?;
+        if !ct_eq_bytes(&embedded, &expected) {
+            return None;
+        }
+
+        self.verify_musig(msg)
+    }
+
     /// Deserializes signature from packed bytes representation.
     /// [0..32] - packed pubkey of the signer.
     /// [32..96] - packed r,s of the signature
@@ -86,8 +459,111 @@ impl TxSignature {
         Ok(Self {
             pub_key: PackedPublicKey::deserialize_packed(&bytes[0..32])?,
             signature: PackedSignature::deserialize_packed(&bytes[32..])?,
+            scheme: SignatureType::default(),
         })
     }
+
+    /// Serializes signature into a single fixed-size blob.
+    /// [0..32] - packed pubkey of the signer.
+    /// [32..96] - packed r,s of the signature
+    pub fn to_bytes(&self) -> [u8; 96] {
+        let mut bytes = [0u8; 96];
+        bytes[0..32].copy_from_slice(
+            &self
+                .pub_key
+                .serialize_packed()
+                .expect("packing pub key into fixed-size buffer"),
+        );
+        bytes[32..].copy_from_slice(
+            &self
+                .signature
+                .serialize_packed()
+                .expect("packing signature into fixed-size buffer"),
+        );
+        bytes
+    }
+
+    /// Deserializes signature from the fixed-size blob created by `TxSignature::to_bytes`.
+    pub fn from_bytes(bytes: &[u8; 96]) -> Result<Self, anyhow::Error> {
+        Self::deserialize_from_packed_bytes(bytes)
+    }
+
+    /// Checks whether both signatures have the same packed representation
+    /// (see `TxSignature::to_bytes`). The `scheme` hint is not compared.
+    ///
+    /// The packed bytes are compared in constant time: all the bytes are inspected
+    /// regardless of the position of the first mismatch, so the comparison time doesn't
+    /// reveal how many leading bytes match. Note that packing the signatures itself
+    /// is not guaranteed to be constant time.
+    pub fn ct_eq(&self, other: &TxSignature) -> bool {
+        ct_eq_bytes(&self.to_bytes(), &other.to_bytes())
+    }
+}
+
+/// Compares two byte slices without exiting early on the first mismatch.
+/// Only the lengths of the slices affect the comparison time.
+pub(super) fn ct_eq_bytes(lhs: &[u8], rhs: &[u8]) -> bool {
+    if lhs.len() != rhs.len() {
+        return false;
+    }
+    lhs.iter().zip(rhs).fold(0u8, |diff, (a, b)| diff | (a ^ b)) == 0
+}
+
+/// Verifies the detached signature against the externally supplied transaction bytes,
+/// e.g. obtained from `ZkSyncTx::signing_message`.
+///
+/// Returns the `PubKeyHash` of the signer, or `None` if the signature is incorrect
+/// or the bytes are longer than `MAX_SIGNED_MESSAGE_LEN` (and thus are not fully signed).
+pub fn verify_detached(
+    bytes: &[u8],
+    signature: &TxSignature,
+    variant: SignatureType,
+) -> Option<PubKeyHash> {
+    if bytes.len() > MAX_SIGNED_MESSAGE_LEN {
+        return None;
+    }
+    signature
+        .verify_musig_with_type(bytes, variant)
+        .map(|pub_key| PubKeyHash::from_pubkey(&pub_key))
+}
+
+/// Verifies the detached signature of the string message.
+///
+/// The signed message is the UTF-8 encoding of the string, without a length prefix
+/// or any other framing. Only the strings which encoding fits into `MAX_SIGNED_MESSAGE_LEN`
+/// bytes can be verified: the signature doesn't cover the rest of the longer messages,
+/// so they would be accepted with any suffix.
+///
+/// Returns the `PubKeyHash` of the signer, or `None` if the signature is incorrect
+/// or the message is too long.
+pub fn verify_string(
+    message: &str,
+    signature: &TxSignature,
+    variant: SignatureType,
+) -> Option<PubKeyHash> {
+    verify_detached(message.as_bytes(), signature, variant)
+}
+
+/// Verifies the signature of the message made by the `pub_key` owner, for the encodings
+/// where the public key is transmitted separately from the signature.
+///
+/// Returns `true` if the signature is correct. Messages longer than `MAX_SIGNED_MESSAGE_LEN`
+/// are not fully covered by the signature, so they are always rejected.
+pub fn verify_with_external_pubkey(
+    pub_key: &PackedPublicKey,
+    sign: &PackedSignature,
+    msg: &[u8],
+    variant: SignatureType,
+) -> bool {
+    if msg.len() > MAX_SIGNED_MESSAGE_LEN {
+        return false;
+    }
+    let signature = TxSignature {
+        pub_key: pub_key.clone(),
+        signature: sign.clone(),
+        scheme: variant,
+    };
+    signature.verify_musig_with_type(msg, variant).is_some()
 }
 
 impl Default for TxSignature {
@@ -95,6 +571,7 @@ impl Default for TxSignature {
         Self {
             pub_key: PackedPublicKey::deserialize_packed(&[0; 32]).unwrap(),
             signature: PackedSignature::deserialize_packed(&[0; 64]).unwrap(),
+            scheme: SignatureType::default(),
         }
     }
 }
@@ -0,0 +1,304 @@
+//! RLP encoding of zkSync transactions for the interoperability with the Ethereum tooling.
+//!
+//! Transaction is encoded as an RLP list of its type followed by its fields in the order of
+//! `ZkSyncTx::get_bytes` and the 96-byte zkSync signature as returned by `TxSignature::to_bytes`:
+//!
+//! - integers are encoded as RLP integers, token amounts as big-endian byte strings without
+//!   leading zeros;
+//! - addresses and `PubKeyHash` values are encoded as byte strings;
+//! - `MultiTransfer` outputs are encoded as a nested list of `[to, token, amount]` lists;
+//! - absent optional fields (e.g. idempotency key or Ethereum signature) are encoded as
+//!   empty byte strings.
+//!
+//! This encoding is independent from the signed transaction bytes, changing it doesn't affect
+//! the transaction signatures.
+
+use std::convert::TryInto;
+
+use num::{BigUint, Zero};
+use rlp::{Decodable, DecoderError, Rlp, RlpStream};
+use zksync_basic_types::Address;
+
+use super::{
+    Amount, ChangePubKey, Close, ForcedExit, MultiTransfer, PackedEthSignature, Transfer,
+    TransferOutput, TxSignature, Withdraw, ZkSyncTx,
+};
+use crate::PubKeyHash;
+
+fn append_bytes(s: &mut RlpStream, bytes: &[u8]) {
+    s.append(&bytes);
+}
+
+fn append_amount(s: &mut RlpStream, amount: &BigUint) {
+    if amount.is_zero() {
+        append_bytes(s, &[]);
+    } else {
+        append_bytes(s, &amount.to_bytes_be());
+    }
+}
+
+fn append_optional(s: &mut RlpStream, bytes: Option<&[u8]>) {
+    append_bytes(s, bytes.unwrap_or(&[]));
+}
+
+/// Helper reading the items of the RLP list one by one.
+struct ItemReader<'a> {
+    rlp: Rlp<'a>,
+    index: usize,
+}
+
+impl<'a> ItemReader<'a> {
+    fn new(rlp: Rlp<'a>) -> Result<Self, DecoderError> {
+        if !rlp.is_list() {
+            return Err(DecoderError::RlpExpectedToBeList);
+        }
+        Ok(Self { rlp, index: 0 })
+    }
+
+    fn next(&mut self) -> Result<Rlp<'a>, DecoderError> {
+        let item = self.rlp.at(self.index)?;
+        self.index += 1;
+        Ok(item)
+    }
+
+    fn value<T: Decodable>(&mut self) -> Result<T, DecoderError> {
+        self.next()?.as_val()
+    }
+
+    fn bytes(&mut self) -> Result<&'a [u8], DecoderError> {
+        self.next()?.data()
+    }
+
+    fn fixed_bytes(&mut self, len: usize) -> Result<&'a [u8], DecoderError> {
+        let bytes = self.bytes()?;
+        if bytes.len() != len {
+            return Err(DecoderError::Custom(
+                "Incorrect length of the fixed-size field",
+            ));
+        }
+        Ok(bytes)
+    }
+
+    fn optional_bytes(&mut self, len: usize) -> Result<Option<&'a [u8]>, DecoderError> {
+        let bytes = self.bytes()?;
+        match bytes.len() {
+            0 => Ok(None),
+            actual if actual == len => Ok(Some(bytes)),
+            _ => Err(DecoderError::Custom(
+                "Incorrect length of the optional field",
+            )),
+        }
+    }
+
+    fn address(&mut self) -> Result<Address, DecoderError> {
+        Ok(Address::from_slice(self.fixed_bytes(20)?))
+    }
+
+    fn amount(&mut self) -> Result<BigUint, DecoderError> {
+        Ok(BigUint::from_bytes_be(self.bytes()?))
+    }
+
+    fn signature(&mut self) -> Result<TxSignature, DecoderError> {
+        TxSignature::from_bytes(self.fixed_bytes(96)?.try_into().unwrap())
+            .map_err(|_| DecoderError::Custom("Incorrect zkSync signature"))
+    }
+
+    fn finish(self) -> Result<(), DecoderError> {
+        if self.rlp.item_count()? != self.index {
+            return Err(DecoderError::RlpIncorrectListLen);
+        }
+        Ok(())
+    }
+}
+
+impl ZkSyncTx {
+    /// Encodes the signed transaction as an RLP list (see the `rlp_encoding` module docs).
+    pub fn to_rlp(&self) -> Vec<u8> {
+        let mut s = RlpStream::new();
+        s.begin_unbounded_list();
+        s.append(&self.tx_type());
+        match self {
+            ZkSyncTx::Transfer(tx) => {
+                s.append(&tx.account_id);
+                append_bytes(&mut s, tx.from.as_bytes());
+                append_bytes(&mut s, tx.to.as_bytes());
+                s.append(&tx.token);
+                append_amount(&mut s, &tx.amount);
+                append_amount(&mut s, &tx.fee);
+                s.append(&tx.nonce);
+                append_bytes(&mut s, &tx.signature.to_bytes());
+                append_optional(&mut s, tx.idempotency_key.as_ref().map(|key| &key[..]));
+                append_optional(&mut s, tx.fee_recipient.as_ref().map(Address::as_bytes));
+            }
+            ZkSyncTx::Withdraw(tx) => {
+                s.append(&tx.account_id);
+                append_bytes(&mut s, tx.from.as_bytes());
+                append_bytes(&mut s, tx.to.as_bytes());
+                s.append(&tx.token);
+                append_amount(&mut s, &tx.amount);
+                append_amount(&mut s, &tx.fee);
+                s.append(&tx.nonce);
+                append_bytes(&mut s, &tx.signature.to_bytes());
+                append_optional(&mut s, tx.idempotency_key.as_ref().map(|key| &key[..]));
+                append_optional(&mut s, tx.fee_recipient.as_ref().map(Address::as_bytes));
+            }
+            ZkSyncTx::Close(tx) => {
+                append_bytes(&mut s, tx.account.as_bytes());
+                s.append(&tx.nonce);
+                append_bytes(&mut s, &tx.signature.to_bytes());
+            }
+            ZkSyncTx::ChangePubKey(tx) => {
+                s.append(&tx.account_id);
+                append_bytes(&mut s, tx.account.as_bytes());
+                append_bytes(&mut s, &tx.new_pk_hash.data);
+                s.append(&tx.fee_token);
+                append_amount(&mut s, &tx.fee);
+                s.append(&tx.nonce);
+                append_bytes(&mut s, &tx.signature.to_bytes());
+                let eth_signature = tx
+                    .eth_signature
+                    .as_ref()
+                    .map(|sign| sign.serialize_packed());
+                append_optional(&mut s, eth_signature.as_ref().map(|sign| &sign[..]));
+            }
+            ZkSyncTx::ForcedExit(tx) => {
+                s.append(&tx.initiator_account_id);
+                append_bytes(&mut s, tx.target.as_bytes());
+                s.append(&tx.token);
+                append_amount(&mut s, &tx.fee);
+                s.append(&tx.nonce);
+                append_bytes(&mut s, &tx.signature.to_bytes());
+            }
+            ZkSyncTx::MultiTransfer(tx) => {
+                s.append(&tx.account_id);
+                append_bytes(&mut s, tx.from.as_bytes());
+                s.begin_list(tx.outputs.len());
+                for output in &tx.outputs {
+                    s.begin_list(3);
+                    append_bytes(&mut s, output.to.as_bytes());
+                    s.append(&output.token);
+                    append_amount(&mut s, &output.amount);
+                }
+                s.append(&tx.fee_token);
+                append_amount(&mut s, &tx.fee);
+                s.append(&tx.nonce);
+                append_bytes(&mut s, &tx.signature.to_bytes());
+            }
+        }
+        s.finalize_unbounded_list();
+        s.out()
+    }
+
+    /// Decodes the transaction from its RLP encoding, counterpart of `ZkSyncTx::to_rlp`.
+    pub fn from_rlp(bytes: &[u8]) -> Result<Self, DecoderError> {
+        let mut reader = ItemReader::new(Rlp::new(bytes))?;
+
+        let tx_type: u8 = reader.value()?;
+        let tx = match tx_type {
+            Transfer::TX_TYPE => {
+                let mut tx = Transfer::new(
+                    reader.value()?,
+                    reader.address()?,
+                    reader.address()?,
+                    reader.value()?,
+                    reader.amount()?,
+                    reader.amount()?,
+                    reader.value()?,
+                    Some(reader.signature()?),
+                );
+                tx.idempotency_key = reader
+                    .optional_bytes(16)?
+                    .map(|key| key.try_into().unwrap());
+                tx.fee_recipient = reader.optional_bytes(20)?.map(Address::from_slice);
+                ZkSyncTx::from(tx)
+            }
+            Withdraw::TX_TYPE => {
+                let mut tx = Withdraw::new(
+                    reader.value()?,
+                    reader.address()?,
+                    reader.address()?,
+                    reader.value()?,
+                    reader.amount()?,
+                    reader.amount()?,
+                    reader.value()?,
+                    Some(reader.signature()?),
+                );
+                tx.idempotency_key = reader
+                    .optional_bytes(16)?
+                    .map(|key| key.try_into().unwrap());
+                tx.fee_recipient = reader.optional_bytes(20)?.map(Address::from_slice);
+                ZkSyncTx::from(tx)
+            }
+            Close::TX_TYPE => ZkSyncTx::from(Close {
+                account: reader.address()?,
+                nonce: reader.value()?,
+                signature: reader.signature()?,
+            }),
+            ChangePubKey::TX_TYPE => {
+                let account_id = reader.value()?;
+                let account = reader.address()?;
+                let new_pk_hash = PubKeyHash::from_bytes(reader.fixed_bytes(20)?).unwrap();
+                let fee_token = reader.value()?;
+                let fee = reader.amount()?;
+                let nonce = reader.value()?;
+                let signature = reader.signature()?;
+                let eth_signature = reader
+                    .optional_bytes(65)?
+                    .map(PackedEthSignature::deserialize_packed)
+                    .transpose()
+                    .map_err(|_| DecoderError::Custom("Incorrect Ethereum signature"))?;
+                ZkSyncTx::from(ChangePubKey::new(
+                    account_id,
+                    account,
+                    new_pk_hash,
+                    fee_token,
+                    fee,
+                    nonce,
+                    Some(signature),
+                    eth_signature,
+                ))
+            }
+            ForcedExit::TX_TYPE => ZkSyncTx::from(ForcedExit::new(
+                reader.value()?,
+                reader.address()?,
+                reader.value()?,
+                reader.amount()?,
+                reader.value()?,
+                Some(reader.signature()?),
+            )),
+            MultiTransfer::TX_TYPE => {
+                let account_id = reader.value()?;
+                let from = reader.address()?;
+                let outputs = reader
+                    .next()?
+                    .iter()
+                    .map(|output| {
+                        let mut output = ItemReader::new(output)?;
+                        let decoded = TransferOutput::new(
+                            output.address()?,
+                            output.value()?,
+                            Amount::new(output.amount()?)
+                                .map_err(|_| DecoderError::Custom("Unpackable output amount"))?,
+                        );
+                        output.finish()?;
+                        Ok(decoded)
+                    })
+                    .collect::<Result<Vec<_>, DecoderError>>()?;
+
+                ZkSyncTx::from(MultiTransfer::new(
+                    account_id,
+                    from,
+                    outputs,
+                    reader.value()?,
+                    reader.amount()?,
+                    reader.value()?,
+                    Some(reader.signature()?),
+                ))
+            }
+            _ => return Err(DecoderError::Custom("Unknown transaction type")),
+        };
+        reader.finish()?;
+
+        Ok(tx)
+    }
+}
@@ -13,7 +13,7 @@ use zksync_crypto::rand::{Rng, SeedableRng, XorShiftRng};
 use super::*;
 use crate::{
     helpers::{pack_fee_amount, pack_token_amount},
-    AccountId, Engine, TokenId,
+    AccountId, Engine, Nonce, PubKeyHash, TokenId,
 };
 
 fn gen_pk_and_msg() -> (PrivateKey<Engine>, Vec<Vec<u8>>) {
@@ -160,6 +160,45 @@ fn test_musig_rescue_signing_verification() {
     }
 }
 
+#[test]
+fn test_musig_rescue_verification_with_generator() {
+    use zksync_crypto::franklin_crypto::eddsa::Seed;
+    use zksync_crypto::params::RESCUE_PARAMS;
+    use zksync_crypto::primitives::rescue_hash_tx_msg;
+
+    let (pk, messages) = gen_pk_and_msg();
+    let generator = FixedGenerators::ProofGenerationKey;
+
+    for msg in &messages {
+        let hashed_msg = rescue_hash_tx_msg(msg);
+        let seed = Seed::deterministic_seed(&pk, &hashed_msg);
+        let signature = TxSignature {
+            pub_key: PackedPublicKey(PublicKey::from_private(&pk, generator, &JUBJUB_PARAMS)),
+            signature: PackedSignature(pk.musig_rescue_sign(
+                &hashed_msg,
+                &seed,
+                generator,
+                &RESCUE_PARAMS,
+                &JUBJUB_PARAMS,
+            )),
+            scheme: SignatureType::MusigRescue,
+        };
+
+        assert!(
+            signature
+                .verify_musig_rescue_with_generator(msg, generator)
+                .is_some(),
+            "Signature is incorrect, msg: {}",
+            hex::encode(&msg)
+        );
+        // Default generator doesn't match the one used for signing.
+        assert!(signature.verify_musig_rescue(msg).is_none());
+        assert!(signature
+            .verify_musig_rescue_with_generator(msg, FixedGenerators::SpendingKeyGenerator)
+            .is_none());
+    }
+}
+
 #[test]
 fn test_ethereum_signature_verify_with_serialization() {
     let address: Address = "52312AD6f01657413b2eaE9287f6B9ADaD93D5FE".parse().unwrap();
@@ -283,3 +322,3145 @@ fn eth_sign_data_compatibility() {
     assert_eq!(deserialized.signature, eth_sign_data.signature);
     assert_eq!(deserialized.message, eth_sign_data.message);
 }
+
+#[test]
+fn test_idempotency_key() {
+    let mut rng = XorShiftRng::from_seed([3, 2, 3, 4]);
+    let key = gen_pk_and_msg().0;
+    let mut transfer = Transfer::new(
+        gen_account_id(&mut rng),
+        Address::from(rng.gen::<[u8; 20]>()),
+        Address::from(rng.gen::<[u8; 20]>()),
+        gen_token_id(&mut rng),
+        BigUint::from(12_340_000_000_000u64),
+        BigUint::from(56_700_000_000u64),
+        rng.gen(),
+        None,
+    );
+    transfer.signature = TxSignature::sign_musig(&key, &transfer.get_bytes());
+    let legacy_bytes = transfer.get_bytes();
+
+    let tx = ZkSyncTx::from(transfer.clone());
+    assert_eq!(tx.idempotency_key(), None);
+    assert!(!tx.is_duplicate(&[[0u8; 16]].iter().cloned().collect()));
+
+    // The key is not a part of the signed bytes, so the signature stays valid.
+    let idempotency_key = rng.gen::<[u8; 16]>();
+    transfer.idempotency_key = Some(idempotency_key);
+    assert_eq!(transfer.get_bytes(), legacy_bytes);
+    assert!(!ZkSyncTx::from(transfer.clone())
+        .signed_field_names()
+        .contains(&"idempotency_key"));
+    assert_eq!(
+        transfer.verify_signature(),
+        Some(PubKeyHash::from_privkey(&key))
+    );
+
+    // Key survives the serialization roundtrip.
+    let transfer: Transfer =
+        serde_json::from_value(serde_json::to_value(&transfer).unwrap()).unwrap();
+    assert_eq!(transfer.idempotency_key, Some(idempotency_key));
+
+    let tx = ZkSyncTx::from(transfer);
+    let mut seen_keys = std::collections::HashSet::new();
+    assert!(!tx.is_duplicate(&seen_keys));
+    seen_keys.insert(idempotency_key);
+    assert!(tx.is_duplicate(&seen_keys));
+}
+
+#[test]
+fn test_fee_recipient() {
+    let mut rng = XorShiftRng::from_seed([3, 2, 3, 4]);
+    let key = gen_pk_and_msg().0;
+    let fee_recipient = Address::from(rng.gen::<[u8; 20]>());
+    let transfer = Transfer::new(
+        gen_account_id(&mut rng),
+        Address::from(rng.gen::<[u8; 20]>()),
+        Address::from(rng.gen::<[u8; 20]>()),
+        gen_token_id(&mut rng),
+        BigUint::from(12_340_000_000_000u64),
+        BigUint::from(56_700_000_000u64),
+        rng.gen(),
+        None,
+    );
+    let withdraw = Withdraw::new(
+        gen_account_id(&mut rng),
+        Address::from(rng.gen::<[u8; 20]>()),
+        Address::from(rng.gen::<[u8; 20]>()),
+        gen_token_id(&mut rng),
+        BigUint::from(12_340_000_000_000u64),
+        BigUint::from(56_700_000_000u64),
+        rng.gen(),
+        None,
+    );
+
+    for tx in &[ZkSyncTx::from(transfer), ZkSyncTx::from(withdraw)] {
+        // Without the recipient transaction encoding stays unchanged.
+        assert_eq!(tx.fee_recipient(), None);
+        let legacy_bytes = tx.get_bytes();
+        let legacy_json = serde_json::to_value(tx).unwrap();
+        assert!(legacy_json.get("feeRecipient").is_none());
+
+        let mut tx = tx.clone();
+        match &mut tx {
+            ZkSyncTx::Transfer(tx) => tx.fee_recipient = Some(fee_recipient),
+            ZkSyncTx::Withdraw(tx) => tx.fee_recipient = Some(fee_recipient),
+            _ => unreachable!(),
+        }
+        assert_eq!(tx.fee_recipient(), Some(fee_recipient));
+
+        // The recipient is not supported by the circuit, so it's not a part of the signed bytes.
+        assert_eq!(tx.get_bytes(), legacy_bytes);
+        assert!(!tx.signed_field_names().contains(&"fee_recipient"));
+        assert_eq!(tx.serialized_size(), legacy_bytes.len() + 96);
+
+        let (tx, valid) = tx.sign_and_check(&key);
+        assert!(valid);
+
+        // The recipient survives the JSON roundtrip, but not the wire encoding.
+        let restored: ZkSyncTx =
+            serde_json::from_value(serde_json::to_value(&tx).unwrap()).unwrap();
+        assert_eq!(restored.fee_recipient(), Some(fee_recipient));
+        assert_eq!(restored.hash(), tx.hash());
+
+        let restored = ZkSyncTx::from_wire_bytes_strict(&tx.to_wire_bytes()).unwrap();
+        assert_eq!(restored.fee_recipient(), None);
+        assert_eq!(restored.verify_signature(), tx.verify_signature());
+    }
+}
+
+#[cfg(feature = "musig_sha256")]
+#[test]
+fn test_check_signature_any() {
+    let mut rng = XorShiftRng::from_seed([4, 2, 3, 4]);
+    let key = gen_pk_and_msg().0;
+    let mut transfer = Transfer::new(
+        gen_account_id(&mut rng),
+        Address::from(rng.gen::<[u8; 20]>()),
+        Address::from(rng.gen::<[u8; 20]>()),
+        gen_token_id(&mut rng),
+        BigUint::from(12_340_000_000_000u64),
+        BigUint::from(56_700_000_000u64),
+        rng.gen(),
+        None,
+    );
+
+    for &variant in SignatureType::ALL.iter() {
+        transfer.signature =
+            TxSignature::sign_musig_with_type(&key, &transfer.get_bytes(), variant);
+        let tx = ZkSyncTx::from(transfer.clone());
+        assert_eq!(tx.check_signature_any(), Ok(variant));
+    }
+
+    // Only the Rescue signature is accepted by the default verification.
+    transfer.signature = TxSignature::sign_musig_sha256(&key, &transfer.get_bytes());
+    assert!(ZkSyncTx::from(transfer.clone())
+        .verify_signature()
+        .is_none());
+
+    // Signature over other data is rejected by every scheme.
+    transfer.signature = TxSignature::sign_musig_rescue(&key, b"hello world");
+    assert_eq!(
+        ZkSyncTx::from(transfer).check_signature_any(),
+        Err(SignatureError::IncorrectSignature)
+    );
+}
+
+#[test]
+fn test_identity_pubkey_rejected() {
+    use zksync_crypto::franklin_crypto::alt_babyjubjub::edwards;
+
+    let identity = PackedPublicKey(PublicKey::<Engine>(edwards::Point::zero()));
+    let packed = identity.serialize_packed().unwrap();
+
+    let err = PackedPublicKey::deserialize_packed(&packed)
+        .err()
+        .expect("identity point must be rejected");
+    assert_eq!(err.to_string(), "PublicKey must not be the identity point");
+
+    let json = serde_json::to_value(&identity).unwrap();
+    assert!(serde_json::from_value::<PackedPublicKey>(json).is_err());
+
+    // Identity point hash must not be confused with the unset `PubKeyHash`.
+    assert_ne!(PubKeyHash::from_pubkey(&identity.0), PubKeyHash::zero());
+}
+
+#[test]
+fn test_net_deltas() {
+    let token: TokenId = 1;
+    let alice = Address::from([1u8; 20]);
+    let bob = Address::from([2u8; 20]);
+    let carol = Address::from([3u8; 20]);
+
+    let transfer = |from, to, amount: u64, fee: u64| {
+        ZkSyncTx::from(Transfer::new(
+            0,
+            from,
+            to,
+            token,
+            amount.into(),
+            fee.into(),
+            0,
+            None,
+        ))
+    };
+    let txs = vec![
+        transfer(alice, bob, 100, 1),
+        transfer(bob, carol, 40, 2),
+        transfer(carol, alice, 10, 3),
+        ZkSyncTx::from(Withdraw::new(
+            0,
+            bob,
+            bob,
+            token,
+            20u64.into(),
+            4u64.into(),
+            0,
+            None,
+        )),
+    ];
+
+    let deltas = net_deltas(&txs);
+    assert_eq!(deltas[&(alice, token)], num::BigInt::from(-91));
+    assert_eq!(deltas[&(bob, token)], num::BigInt::from(34));
+    assert_eq!(deltas[&(carol, token)], num::BigInt::from(27));
+
+    // Everything that left the accounts is either fees or withdrawn funds.
+    let total: num::BigInt = deltas.values().sum();
+    assert_eq!(total, num::BigInt::from(-(1 + 2 + 3 + 4 + 20)));
+}
+
+#[test]
+fn test_total_fees() {
+    let alice = Address::from([1u8; 20]);
+    let bob = Address::from([2u8; 20]);
+    let transfer = |token: TokenId, fee: u64| {
+        ZkSyncTx::from(Transfer::new(
+            0,
+            alice,
+            bob,
+            token,
+            100u64.into(),
+            fee.into(),
+            0,
+            None,
+        ))
+    };
+    let close = ZkSyncTx::from(Close {
+        account: alice,
+        nonce: 0,
+        signature: TxSignature::default(),
+    });
+
+    // Single fee token.
+    let txs = vec![transfer(1, 10), transfer(1, 20), close.clone()];
+    let fees = total_fees(&txs);
+    assert_eq!(fees.len(), 1);
+    assert_eq!(fees[&1], BigUint::from(30u64));
+
+    // Multiple fee tokens.
+    let txs = vec![
+        transfer(1, 10),
+        transfer(2, 5),
+        ZkSyncTx::from(Withdraw::new(
+            0,
+            alice,
+            alice,
+            2,
+            50u64.into(),
+            7u64.into(),
+            0,
+            None,
+        )),
+        ZkSyncTx::from(ForcedExit::new(0, bob, 3, 1u64.into(), 0, None)),
+        close,
+    ];
+    let fees = total_fees(&txs);
+    assert_eq!(fees.len(), 3);
+    assert_eq!(fees[&1], BigUint::from(10u64));
+    assert_eq!(fees[&2], BigUint::from(12u64));
+    assert_eq!(fees[&3], BigUint::from(1u64));
+
+    assert!(total_fees(&[]).is_empty());
+}
+
+#[test]
+fn test_unique_accounts() {
+    let alice = Address::from([1u8; 20]);
+    let bob = Address::from([2u8; 20]);
+    let carol = Address::from([3u8; 20]);
+    let dave = Address::from([4u8; 20]);
+    let l1_recipient = Address::from([5u8; 20]);
+
+    let transfer = |from, to| {
+        ZkSyncTx::from(Transfer::new(
+            0,
+            from,
+            to,
+            0,
+            100u64.into(),
+            1u64.into(),
+            0,
+            None,
+        ))
+    };
+    let txs = vec![
+        transfer(bob, alice),
+        transfer(alice, bob),
+        ZkSyncTx::from(Withdraw::new(
+            0,
+            bob,
+            l1_recipient,
+            0,
+            50u64.into(),
+            1u64.into(),
+            0,
+            None,
+        )),
+        ZkSyncTx::from(MultiTransfer::new(
+            0,
+            carol,
+            vec![
+                TransferOutput::new(alice, 0, Amount::new(10u64.into()).unwrap()),
+                TransferOutput::new(dave, 0, Amount::new(20u64.into()).unwrap()),
+            ],
+            0,
+            1u64.into(),
+            0,
+            None,
+        )),
+        ZkSyncTx::from(ForcedExit::new(0, dave, 0, 1u64.into(), 0, None)),
+        ZkSyncTx::from(Close {
+            account: carol,
+            nonce: 0,
+            signature: TxSignature::default(),
+        }),
+    ];
+
+    // Accounts are listed once, in the order of their first appearance.
+    assert_eq!(
+        unique_accounts(&txs),
+        vec![bob, alice, l1_recipient, carol, dave]
+    );
+    assert!(unique_accounts(&[]).is_empty());
+}
+
+#[test]
+fn test_generate_test_transactions() {
+    let txs = generate_test_transactions(10, 42);
+    assert_eq!(txs.len(), 10);
+    for tx in &txs {
+        let mut tx = tx.clone();
+        assert!(tx.check_correctness(), "{:?}", tx);
+    }
+
+    // Generation is deterministic.
+    let hashes = |txs: &[ZkSyncTx]| txs.iter().map(ZkSyncTx::hash).collect::<Vec<_>>();
+    assert_eq!(hashes(&txs), hashes(&generate_test_transactions(10, 42)));
+    assert_ne!(hashes(&txs), hashes(&generate_test_transactions(10, 43)));
+    assert!(generate_test_transactions(0, 42).is_empty());
+}
+
+#[test]
+fn test_tx_merkle_root() {
+    let hash_pair = |left: &[u8], right: &[u8]| {
+        let mut node = [0u8; 32];
+        node.copy_from_slice(&parity_crypto::digest::sha256(&[left, right].concat()));
+        node
+    };
+
+    let txs = gen_signed_txs();
+    let leaves: Vec<_> = txs.iter().map(|tx| tx.hash()).collect();
+    let (a, b, c) = (leaves[0].as_ref(), leaves[1].as_ref(), leaves[2].as_ref());
+
+    assert_eq!(tx_merkle_root(&[]), [0u8; 32]);
+    // Root of a single transaction is its hash.
+    assert_eq!(&tx_merkle_root(&txs[..1])[..], a);
+    assert_eq!(tx_merkle_root(&txs[..2]), hash_pair(a, b));
+    // The last node of the odd level is paired with itself.
+    assert_eq!(
+        tx_merkle_root(&txs[..3]),
+        hash_pair(&hash_pair(a, b), &hash_pair(c, c))
+    );
+
+    // Order of the transactions matters.
+    assert_ne!(
+        tx_merkle_root(&[txs[1].clone(), txs[0].clone()]),
+        tx_merkle_root(&txs[..2])
+    );
+}
+
+#[test]
+fn test_serialized_size() {
+    let mut rng = XorShiftRng::from_seed([5, 2, 3, 4]);
+    let key = gen_pk_and_msg().0;
+    let from = Address::from(rng.gen::<[u8; 20]>());
+    let to = Address::from(rng.gen::<[u8; 20]>());
+
+    let mut transfer =
+        Transfer::new_signed(1, from, to, 0, 1_000u64.into(), 10u64.into(), 0, &key).unwrap();
+    let withdraw =
+        Withdraw::new_signed(1, from, to, 0, 1_000u64.into(), 10u64.into(), 0, &key).unwrap();
+    let change_pubkey = ChangePubKey::new_signed(
+        1,
+        from,
+        PubKeyHash::from_privkey(&key),
+        0,
+        10u64.into(),
+        0,
+        None,
+        &key,
+    )
+    .unwrap();
+    let forced_exit = ForcedExit::new_signed(1, to, 0, 10u64.into(), 0, &key).unwrap();
+    let close = Close {
+        account: from,
+        nonce: 0,
+        signature: TxSignature::default(),
+    };
+
+    let txs = vec![
+        (ZkSyncTx::from(transfer.clone()), 58),
+        (ZkSyncTx::from(withdraw), 69),
+        (ZkSyncTx::from(close), 25),
+        (ZkSyncTx::from(change_pubkey), 53),
+        (ZkSyncTx::from(forced_exit), 33),
+    ];
+    for (tx, tx_bytes_len) in txs {
+        assert_eq!(tx.get_bytes().len(), tx_bytes_len);
+        assert_eq!(tx.serialized_size(), tx_bytes_len + 96);
+    }
+
+    transfer.idempotency_key = Some([1; 16]);
+    let tx = ZkSyncTx::from(transfer);
+    assert_eq!(tx.serialized_size(), tx.get_bytes().len() + 96);
+}
+
+#[test]
+fn test_zero_amount() {
+    let from = Address::from([1u8; 20]);
+    let to = Address::from([2u8; 20]);
+
+    for (amount, is_zero) in vec![(0u64, true), (1u64, false)] {
+        let transfer = Transfer::new(0, from, to, 0, amount.into(), 0u64.into(), 0, None);
+        assert_eq!(transfer.is_zero_amount(), is_zero);
+
+        let withdraw = Withdraw::new(0, from, to, 0, amount.into(), 0u64.into(), 0, None);
+        assert_eq!(withdraw.is_zero_amount(), is_zero);
+    }
+}
+
+#[test]
+fn test_signature_bytes_roundtrip() {
+    let (pk, messages) = gen_pk_and_msg();
+
+    for msg in &messages {
+        let signature = TxSignature::sign_musig(&pk, msg);
+        let bytes = signature.to_bytes();
+
+        assert_eq!(
+            &bytes[..32],
+            signature.pub_key.serialize_packed().unwrap().as_slice()
+        );
+        assert_eq!(
+            &bytes[32..],
+            signature.signature.serialize_packed().unwrap().as_slice()
+        );
+
+        let restored = TxSignature::from_bytes(&bytes).expect("signature roundtrip");
+        assert_eq!(restored.to_bytes().to_vec(), bytes.to_vec());
+        assert!(restored.verify_musig(msg).is_some());
+    }
+}
+
+#[test]
+fn test_verify_with_pubkey() {
+    let (pk, messages) = gen_pk_and_msg();
+    let mut rng = XorShiftRng::from_seed([5, 6, 7, 8]);
+    let other_pk = PrivateKey::<Engine>(rng.gen());
+
+    let expected = PackedPublicKey(public_key_from_private(&pk));
+    let other = PackedPublicKey(public_key_from_private(&other_pk));
+
+    for msg in &messages {
+        let signature = TxSignature::sign_musig(&pk, msg);
+        assert!(signature.verify_with_pubkey(msg, &expected).is_some());
+        assert!(signature.verify_with_pubkey(msg, &other).is_none());
+
+        // Correct key, but the signature is made for another message.
+        assert!(signature
+            .verify_with_pubkey(b"another message", &expected)
+            .is_none());
+    }
+}
+
+#[test]
+fn test_multi_transfer_signing_verification() {
+    let mut rng = XorShiftRng::from_seed([1, 2, 3, 4]);
+    let key = gen_pk_and_msg().0;
+    let token = gen_token_id(&mut rng);
+
+    let outputs = vec![
+        TransferOutput::new(
+            Address::from(rng.gen::<[u8; 20]>()),
+            token,
+            Amount::new(BigUint::from(1_000_000u64)).unwrap(),
+        ),
+        TransferOutput::new(
+            Address::from(rng.gen::<[u8; 20]>()),
+            token,
+            Amount::new(BigUint::from(2_000_000u64)).unwrap(),
+        ),
+    ];
+
+    let multi_transfer = MultiTransfer::new_signed(
+        gen_account_id(&mut rng),
+        Address::from(rng.gen::<[u8; 20]>()),
+        outputs,
+        token,
+        BigUint::from(1_000u64),
+        rng.gen(),
+        &key,
+    )
+    .expect("failed to sign multi transfer");
+
+    let expected_signer = PubKeyHash::from_privkey(&key);
+    assert_eq!(multi_transfer.verify_signature(), Some(expected_signer));
+
+    let bytes = multi_transfer.get_bytes();
+    assert_eq!(bytes[0], MultiTransfer::TX_TYPE);
+    // Outputs list is prefixed with its length.
+    assert_eq!(bytes[1 + 4 + 20], 2);
+
+    let tx = ZkSyncTx::from(multi_transfer.clone());
+    assert_eq!(tx.serialized_size(), bytes.len() + 96);
+
+    // Changing one of the outputs invalidates the signature.
+    let mut tampered = MultiTransfer::new(
+        multi_transfer.account_id,
+        multi_transfer.from,
+        multi_transfer.outputs.clone(),
+        multi_transfer.fee_token,
+        multi_transfer.fee.clone(),
+        multi_transfer.nonce,
+        None,
+    );
+    tampered.outputs[1].amount = Amount::new(BigUint::from(3_000_000u64)).unwrap();
+    tampered.signature = multi_transfer.signature.clone();
+    assert!(!tampered.check_correctness());
+
+    // The number of outputs is capped.
+    let too_many_outputs = vec![multi_transfer.outputs[0].clone(); MultiTransfer::MAX_OUTPUTS + 1];
+    assert!(MultiTransfer::new_signed(
+        multi_transfer.account_id,
+        multi_transfer.from,
+        too_many_outputs,
+        token,
+        BigUint::from(1_000u64),
+        multi_transfer.nonce,
+        &key,
+    )
+    .is_err());
+}
+
+#[test]
+fn test_multi_transfer_signature_covers_all_fields() {
+    let mut rng = XorShiftRng::from_seed([1, 2, 3, 4]);
+    let key = gen_pk_and_msg().0;
+    let token = gen_token_id(&mut rng);
+
+    // Encoding of this many outputs doesn't fit into the 92 bytes covered by the Rescue hash.
+    let outputs = (1..=4u64)
+        .map(|i| {
+            TransferOutput::new(
+                Address::from(rng.gen::<[u8; 20]>()),
+                token,
+                Amount::new(BigUint::from(i * 1_000_000)).unwrap(),
+            )
+        })
+        .collect();
+    let multi_transfer = MultiTransfer::new_signed(
+        gen_account_id(&mut rng),
+        Address::from(rng.gen::<[u8; 20]>()),
+        outputs,
+        token,
+        BigUint::from(1_000u64),
+        rng.gen::<u32>() / 2,
+        &key,
+    )
+    .expect("failed to sign multi transfer");
+    assert!(multi_transfer.get_bytes().len() > 92);
+    assert_eq!(
+        ZkSyncTx::from(multi_transfer.clone()).verify_signature(),
+        Some(PubKeyHash::from_privkey(&key))
+    );
+
+    let tamper = |change: &dyn Fn(&mut MultiTransfer)| {
+        let mut tampered = MultiTransfer::new(
+            multi_transfer.account_id,
+            multi_transfer.from,
+            multi_transfer.outputs.clone(),
+            multi_transfer.fee_token,
+            multi_transfer.fee.clone(),
+            multi_transfer.nonce,
+            None,
+        );
+        change(&mut tampered);
+        tampered.signature = multi_transfer.signature.clone();
+        tampered
+    };
+    let tampered = vec![
+        tamper(&|tx| tx.nonce += 1),
+        tamper(&|tx| tx.fee = BigUint::from(2_000u64)),
+        tamper(&|tx| tx.fee_token = if token == 0 { 1 } else { 0 }),
+        tamper(&|tx| tx.outputs[3].amount = Amount::new(BigUint::from(1u64)).unwrap()),
+        tamper(&|tx| tx.outputs[3].to = Address::repeat_byte(0xff)),
+    ];
+    for mut tx in tampered {
+        assert!(tx.verify_signature().is_none());
+        assert!(!tx.check_correctness());
+    }
+}
+
+#[test]
+fn test_verify_detached() {
+    let mut rng = XorShiftRng::from_seed([1, 2, 3, 4]);
+    let key = gen_pk_and_msg().0;
+    let transfer = Transfer::new_signed(
+        gen_account_id(&mut rng),
+        Address::from(rng.gen::<[u8; 20]>()),
+        Address::from(rng.gen::<[u8; 20]>()),
+        gen_token_id(&mut rng),
+        BigUint::from(12_340_000_000_000u64),
+        BigUint::from(56_700_000_000u64),
+        rng.gen(),
+        &key,
+    )
+    .expect("failed to sign transfer");
+    let bytes = transfer.get_bytes();
+
+    assert_eq!(
+        verify_detached(&bytes, &transfer.signature, SignatureType::MusigRescue),
+        Some(PubKeyHash::from_privkey(&key))
+    );
+    // Signature was made with another scheme.
+    assert_eq!(
+        verify_detached(&bytes, &transfer.signature, SignatureType::MusigSha256),
+        None
+    );
+    // Bytes do not correspond to the signature.
+    assert_eq!(
+        verify_detached(&bytes[1..], &transfer.signature, SignatureType::MusigRescue),
+        None
+    );
+
+    // Messages which are not fully covered by the signature are rejected, even though
+    // the signature is correct for their prefix.
+    let mut msg = vec![7u8; MAX_SIGNED_MESSAGE_LEN];
+    let signature = TxSignature::sign_musig(&key, &msg);
+    assert_eq!(
+        verify_detached(&msg, &signature, SignatureType::MusigRescue),
+        Some(PubKeyHash::from_privkey(&key))
+    );
+    msg.extend_from_slice(b"any suffix");
+    assert!(signature.verify_musig(&msg).is_some());
+    assert_eq!(
+        verify_detached(&msg, &signature, SignatureType::MusigRescue),
+        None
+    );
+}
+
+#[cfg(feature = "musig_sha256")]
+#[test]
+fn test_check_signature_with_preference() {
+    let mut rng = XorShiftRng::from_seed([4, 2, 3, 4]);
+    let key = gen_pk_and_msg().0;
+    let mut transfer = Transfer::new(
+        gen_account_id(&mut rng),
+        Address::from(rng.gen::<[u8; 20]>()),
+        Address::from(rng.gen::<[u8; 20]>()),
+        gen_token_id(&mut rng),
+        BigUint::from(12_340_000_000_000u64),
+        BigUint::from(56_700_000_000u64),
+        rng.gen(),
+        None,
+    );
+    transfer.signature = TxSignature::sign_musig_sha256(&key, &transfer.get_bytes());
+    let tx = ZkSyncTx::from(transfer);
+
+    let mut preferred_schemes = std::collections::HashMap::new();
+    preferred_schemes.insert(tx.account(), SignatureType::MusigSha256);
+    let hook = |tx: &ZkSyncTx| preferred_schemes.get(&tx.account()).copied();
+
+    // The hook puts the preferred scheme first.
+    assert_eq!(
+        SignatureType::verification_order(hook(&tx)),
+        vec![SignatureType::MusigSha256, SignatureType::MusigRescue]
+    );
+    assert_eq!(
+        SignatureType::verification_order(None),
+        SignatureType::ALL.to_vec()
+    );
+
+    assert_eq!(
+        tx.check_signature_with_preference(hook),
+        Ok(SignatureType::MusigSha256)
+    );
+    // An unknown account falls back to the scheme embedded into the signature.
+    assert_eq!(
+        tx.check_signature_with_preference(|_| None),
+        Ok(SignatureType::MusigSha256)
+    );
+}
+
+#[test]
+fn test_transfer_into_zksync_tx() {
+    let mut rng = XorShiftRng::from_seed([1, 2, 3, 4]);
+    let transfer = Transfer::new(
+        gen_account_id(&mut rng),
+        Address::from(rng.gen::<[u8; 20]>()),
+        Address::from(rng.gen::<[u8; 20]>()),
+        gen_token_id(&mut rng),
+        BigUint::from(1_000u64),
+        BigUint::from(10u64),
+        rng.gen(),
+        None,
+    );
+
+    let tx: ZkSyncTx = transfer.clone().into();
+    assert!(matches!(&tx, ZkSyncTx::Transfer(_)));
+    assert_eq!(tx.get_bytes(), transfer.get_bytes());
+}
+
+#[test]
+fn test_withdraw_into_zksync_tx() {
+    let mut rng = XorShiftRng::from_seed([1, 2, 3, 4]);
+    let withdraw = Withdraw::new(
+        gen_account_id(&mut rng),
+        Address::from(rng.gen::<[u8; 20]>()),
+        Address::from(rng.gen::<[u8; 20]>()),
+        gen_token_id(&mut rng),
+        BigUint::from(1_000u64),
+        BigUint::from(10u64),
+        rng.gen(),
+        None,
+    );
+
+    let tx: ZkSyncTx = withdraw.clone().into();
+    assert!(matches!(&tx, ZkSyncTx::Withdraw(_)));
+    assert_eq!(tx.get_bytes(), withdraw.get_bytes());
+}
+
+#[test]
+fn test_close_into_zksync_tx() {
+    let mut rng = XorShiftRng::from_seed([1, 2, 3, 4]);
+    let close = Close {
+        account: Address::from(rng.gen::<[u8; 20]>()),
+        nonce: rng.gen(),
+        signature: TxSignature::default(),
+    };
+
+    let tx: ZkSyncTx = close.clone().into();
+    assert!(matches!(&tx, ZkSyncTx::Close(_)));
+    assert_eq!(tx.get_bytes(), close.get_bytes());
+}
+
+#[test]
+fn test_change_pubkey_into_zksync_tx() {
+    let mut rng = XorShiftRng::from_seed([1, 2, 3, 4]);
+    let change_pubkey = ChangePubKey::new(
+        gen_account_id(&mut rng),
+        Address::from(rng.gen::<[u8; 20]>()),
+        PubKeyHash::from_privkey(&gen_pk_and_msg().0),
+        gen_token_id(&mut rng),
+        BigUint::from(10u64),
+        rng.gen(),
+        None,
+        None,
+    );
+
+    let tx: ZkSyncTx = change_pubkey.clone().into();
+    assert!(matches!(&tx, ZkSyncTx::ChangePubKey(_)));
+    assert_eq!(tx.get_bytes(), change_pubkey.get_bytes());
+}
+
+#[test]
+fn test_forced_exit_into_zksync_tx() {
+    let mut rng = XorShiftRng::from_seed([1, 2, 3, 4]);
+    let forced_exit = ForcedExit::new(
+        gen_account_id(&mut rng),
+        Address::from(rng.gen::<[u8; 20]>()),
+        gen_token_id(&mut rng),
+        BigUint::from(10u64),
+        rng.gen(),
+        None,
+    );
+
+    let tx: ZkSyncTx = forced_exit.clone().into();
+    assert!(matches!(&tx, ZkSyncTx::ForcedExit(_)));
+    assert_eq!(tx.get_bytes(), forced_exit.get_bytes());
+}
+
+#[test]
+fn test_net_after_fee() {
+    let mut rng = XorShiftRng::from_seed([1, 2, 3, 4]);
+    let mut transfer_with_fee = |fee: u64| {
+        Transfer::new(
+            gen_account_id(&mut rng),
+            Address::from(rng.gen::<[u8; 20]>()),
+            Address::from(rng.gen::<[u8; 20]>()),
+            gen_token_id(&mut rng),
+            BigUint::from(1_000u64),
+            BigUint::from(fee),
+            0,
+            None,
+        )
+    };
+
+    assert_eq!(
+        transfer_with_fee(10).net_after_fee(),
+        Ok(BigUint::from(990u64))
+    );
+    assert_eq!(
+        transfer_with_fee(1_000).net_after_fee(),
+        Ok(BigUint::from(0u64))
+    );
+    assert_eq!(
+        transfer_with_fee(1_001).net_after_fee(),
+        Err(FeeError::FeeExceedsAmount {
+            amount: BigUint::from(1_000u64),
+            fee: BigUint::from(1_001u64),
+        })
+    );
+}
+
+#[test]
+fn test_supported_tx_types() {
+    let mut rng = XorShiftRng::from_seed([1, 2, 3, 4]);
+    let address = Address::from(rng.gen::<[u8; 20]>());
+    let txs: Vec<ZkSyncTx> = vec![
+        Transfer::new(0, address, address, 0, 1u64.into(), 1u64.into(), 0, None).into(),
+        Withdraw::new(0, address, address, 0, 1u64.into(), 1u64.into(), 0, None).into(),
+        Close {
+            account: address,
+            nonce: 0,
+            signature: TxSignature::default(),
+        }
+        .into(),
+        ChangePubKey::new(
+            0,
+            address,
+            PubKeyHash::zero(),
+            0,
+            1u64.into(),
+            0,
+            None,
+            None,
+        )
+        .into(),
+        ForcedExit::new(0, address, 0, 1u64.into(), 0, None).into(),
+        MultiTransfer::new(
+            0,
+            address,
+            vec![TransferOutput::new(
+                address,
+                0,
+                Amount::new(1u64.into()).unwrap(),
+            )],
+            0,
+            1u64.into(),
+            0,
+            None,
+        )
+        .into(),
+    ];
+
+    let tx_types: Vec<u8> = txs.iter().map(ZkSyncTx::tx_type).collect();
+    assert_eq!(tx_types, ZkSyncTx::supported_tx_types());
+
+    for tx in &txs {
+        assert_eq!(tx.get_bytes()[0], tx.tx_type());
+    }
+}
+
+#[test]
+fn test_conflicts_with() {
+    let mut rng = XorShiftRng::from_seed([1, 2, 3, 4]);
+    let from = Address::from(rng.gen::<[u8; 20]>());
+    let to = Address::from(rng.gen::<[u8; 20]>());
+    let transfer = |fee: u64, nonce| -> ZkSyncTx {
+        Transfer::new(0, from, to, 0, 1_000u64.into(), fee.into(), nonce, None).into()
+    };
+
+    let tx = transfer(10, 0);
+    // Same account and nonce, but a different fee.
+    assert!(tx.conflicts_with(&transfer(20, 0)));
+    // Different nonce.
+    assert!(!tx.conflicts_with(&transfer(20, 1)));
+    // Identical transaction.
+    assert!(!tx.conflicts_with(&transfer(10, 0)));
+    // Different account.
+    let other: ZkSyncTx =
+        Transfer::new(0, to, from, 0, 1_000u64.into(), 10u64.into(), 0, None).into();
+    assert!(!tx.conflicts_with(&other));
+}
+
+#[test]
+fn test_hex_prefix_deserialization() {
+    let (pk, messages) = gen_pk_and_msg();
+    let signature = TxSignature::sign_musig(&pk, &messages[1]);
+
+    let pub_key = hex::encode(signature.pub_key.serialize_packed().unwrap());
+    let sign = hex::encode(signature.signature.serialize_packed().unwrap());
+
+    for prefix in &["", "0x", "0X"] {
+        let deserialized: PackedPublicKey =
+            serde_json::from_value(format!("{}{}", prefix, pub_key).into()).unwrap();
+        assert_eq!(
+            hex::encode(deserialized.serialize_packed().unwrap()),
+            pub_key
+        );
+
+        let deserialized: PackedSignature =
+            serde_json::from_value(format!("{}{}", prefix, sign).into()).unwrap();
+        assert_eq!(hex::encode(deserialized.serialize_packed().unwrap()), sign);
+    }
+
+    let pubkey_hash = PubKeyHash::from_privkey(&pk);
+    let hex = hex::encode(&pubkey_hash.data);
+    for prefix in &["sync:", "0x", "0X"] {
+        assert_eq!(
+            PubKeyHash::from_hex(&format!("{}{}", prefix, hex)).unwrap(),
+            pubkey_hash
+        );
+    }
+    // Prefix is still required for `PubKeyHash`.
+    assert!(PubKeyHash::from_hex(&hex).is_err());
+}
+
+#[test]
+fn test_sign_and_check() {
+    let mut rng = XorShiftRng::from_seed([1, 2, 3, 4]);
+    let key = gen_pk_and_msg().0;
+    let other_key = PrivateKey::<Engine>(rng.gen());
+
+    let account_id = gen_account_id(&mut rng);
+    let token = gen_token_id(&mut rng);
+    let from = Address::from(rng.gen::<[u8; 20]>());
+    let to = Address::from(rng.gen::<[u8; 20]>());
+    let amount = BigUint::from(1_000u64);
+    let fee = BigUint::from(10u64);
+    let nonce = rng.gen();
+
+    let txs: Vec<ZkSyncTx> = vec![
+        Transfer::new(
+            account_id,
+            from,
+            to,
+            token,
+            amount.clone(),
+            fee.clone(),
+            nonce,
+            None,
+        )
+        .into(),
+        Withdraw::new(
+            account_id,
+            from,
+            to,
+            token,
+            amount.clone(),
+            fee.clone(),
+            nonce,
+            None,
+        )
+        .into(),
+        Close {
+            account: from,
+            nonce,
+            signature: TxSignature::default(),
+        }
+        .into(),
+        ChangePubKey::new(
+            account_id,
+            from,
+            PubKeyHash::from_privkey(&key),
+            token,
+            fee.clone(),
+            nonce,
+            None,
+            None,
+        )
+        .into(),
+        ForcedExit::new(account_id, to, token, fee.clone(), nonce, None).into(),
+        MultiTransfer::new(
+            account_id,
+            from,
+            vec![TransferOutput::new(to, token, Amount::new(amount).unwrap())],
+            token,
+            fee,
+            nonce,
+            None,
+        )
+        .into(),
+    ];
+
+    for tx in txs {
+        let (tx, valid) = tx.sign_and_check(&key);
+        assert!(valid, "{:?}", tx);
+        assert_eq!(tx.verify_signature(), Some(PubKeyHash::from_privkey(&key)));
+
+        // Re-signing replaces the signature along with its cached verification result.
+        let (tx, valid) = tx.sign_and_check(&other_key);
+        assert!(valid, "{:?}", tx);
+        assert_eq!(
+            tx.verify_signature(),
+            Some(PubKeyHash::from_privkey(&other_key))
+        );
+    }
+}
+
+#[test]
+fn test_fee_bps() {
+    let transfer = |amount: u64, fee: u64| {
+        Transfer::new(
+            0,
+            Address::from([1u8; 20]),
+            Address::from([2u8; 20]),
+            0,
+            BigUint::from(amount),
+            BigUint::from(fee),
+            0,
+            None,
+        )
+    };
+
+    for &(amount, fee, expected_bps) in &[
+        (10_000, 0, 0),
+        (10_000, 1, 1),
+        (10_000, 25, 25),
+        (1_000, 1, 10),
+        (3, 1, 3_333),
+        (1_000, 1_000, 10_000),
+        (100, 250, 25_000),
+    ] {
+        assert_eq!(transfer(amount, fee).fee_bps(), Ok(expected_bps));
+    }
+    assert_eq!(
+        transfer(1, u64::max_value()).fee_bps(),
+        Ok(u32::max_value())
+    );
+
+    assert_eq!(transfer(10_000, 30).check_fee_cap(30), Ok(()));
+    assert_eq!(
+        transfer(10_000, 31).check_fee_cap(30),
+        Err(FeeError::FeeCapExceeded {
+            fee_bps: 31,
+            max_bps: 30
+        })
+    );
+
+    assert_eq!(transfer(0, 10).fee_bps(), Err(FeeError::ZeroAmount));
+    assert_eq!(transfer(0, 0).check_fee_cap(100), Err(FeeError::ZeroAmount));
+}
+
+/// Creates a signed transaction of every supported type.
+fn gen_signed_txs() -> Vec<ZkSyncTx> {
+    let mut rng = XorShiftRng::from_seed([1, 2, 3, 4]);
+    let key = gen_pk_and_msg().0;
+
+    let account_id = gen_account_id(&mut rng);
+    let token = gen_token_id(&mut rng);
+    let from = Address::from(rng.gen::<[u8; 20]>());
+    let to = Address::from(rng.gen::<[u8; 20]>());
+    let nonce = rng.gen();
+
+    let mut transfer = Transfer::new(
+        account_id,
+        from,
+        to,
+        token,
+        BigUint::from(1_000u64),
+        BigUint::from(10u64),
+        nonce,
+        None,
+    );
+    transfer.idempotency_key = Some(rng.gen());
+
+    let eth_private_key = "0b43c0f5b5a13a7047408d1f8c8ad32ba5879902ea6212184e0a5d1157281d76"
+        .parse()
+        .unwrap();
+    let mut change_pubkey = ChangePubKey::new(
+        account_id,
+        from,
+        PubKeyHash::from_privkey(&key),
+        token,
+        BigUint::from(10u64),
+        nonce,
+        None,
+        None,
+    );
+    change_pubkey.eth_signature = Some(
+        PackedEthSignature::sign(
+            &eth_private_key,
+            &change_pubkey.get_eth_signed_data().unwrap(),
+        )
+        .unwrap(),
+    );
+
+    let txs: Vec<ZkSyncTx> = vec![
+        transfer.into(),
+        Withdraw::new(
+            account_id,
+            from,
+            to,
+            token,
+            BigUint::from(1_000u64),
+            BigUint::from(10u64),
+            nonce,
+            None,
+        )
+        .into(),
+        Close {
+            account: from,
+            nonce,
+            signature: TxSignature::default(),
+        }
+        .into(),
+        change_pubkey.into(),
+        ForcedExit::new(account_id, to, token, BigUint::from(10u64), nonce, None).into(),
+        MultiTransfer::new(
+            account_id,
+            from,
+            vec![
+                TransferOutput::new(to, token, Amount::new(BigUint::from(1_000u64)).unwrap()),
+                TransferOutput::new(from, token, Amount::new(BigUint::from(2_000u64)).unwrap()),
+            ],
+            token,
+            BigUint::from(10u64),
+            nonce,
+            None,
+        )
+        .into(),
+    ];
+
+    txs.into_iter()
+        .map(|tx| tx.sign_and_check(&key).0)
+        .collect()
+}
+
+#[test]
+fn test_wire_bytes_roundtrip() {
+    for tx in gen_signed_txs() {
+        let bytes = tx.to_wire_bytes();
+        let decoded = ZkSyncTx::from_wire_bytes_strict(&bytes).unwrap();
+
+        assert_eq!(decoded.hash(), tx.hash());
+        assert_eq!(decoded.to_wire_bytes(), bytes);
+        assert_eq!(decoded.verify_signature(), tx.verify_signature());
+        assert!(decoded.verify_signature().is_some());
+        if let (ZkSyncTx::ChangePubKey(decoded), ZkSyncTx::ChangePubKey(tx)) = (&decoded, &tx) {
+            assert_eq!(decoded.eth_signature, tx.eth_signature);
+        }
+    }
+
+    // Incorrect inputs.
+    let mut bytes = gen_signed_txs()[0].to_wire_bytes();
+    bytes[2] = 0xff;
+    assert_eq!(
+        ZkSyncTx::from_wire_bytes_strict(&bytes).unwrap_err(),
+        WireError::UnknownTxType(0xff)
+    );
+    assert!(ZkSyncTx::from_wire_bytes_strict(&[]).is_err());
+}
+
+#[test]
+fn test_wire_bytes_trailing_data() {
+    for tx in gen_signed_txs() {
+        let bytes = tx.to_wire_bytes();
+
+        // Exact input.
+        let (decoded, consumed) = ZkSyncTx::from_wire_bytes(&bytes).unwrap();
+        assert_eq!(decoded.hash(), tx.hash());
+        assert_eq!(consumed, bytes.len());
+        assert_eq!(
+            ZkSyncTx::from_wire_bytes_strict(&bytes).unwrap().hash(),
+            tx.hash()
+        );
+
+        // Trailing data.
+        let mut extended = bytes.clone();
+        extended.extend_from_slice(&[0xaa, 0xbb, 0xcc]);
+        let (decoded, consumed) = ZkSyncTx::from_wire_bytes(&extended).unwrap();
+        assert_eq!(decoded.hash(), tx.hash());
+        assert_eq!(consumed, bytes.len());
+        assert_eq!(
+            ZkSyncTx::from_wire_bytes_strict(&extended).err(),
+            Some(WireError::TrailingBytes(3))
+        );
+
+        // Truncated input.
+        let truncated = &bytes[..bytes.len() - 1];
+        assert!(matches!(
+            ZkSyncTx::from_wire_bytes(truncated).err(),
+            Some(WireError::UnexpectedEnd { .. })
+        ));
+        assert!(matches!(
+            ZkSyncTx::from_wire_bytes_strict(truncated).err(),
+            Some(WireError::UnexpectedEnd { .. })
+        ));
+    }
+}
+
+#[test]
+fn test_framed_roundtrip() {
+    let txs = gen_signed_txs();
+
+    let mut buffer = Vec::new();
+    for tx in &txs {
+        write_framed(&mut buffer, tx).unwrap();
+    }
+
+    let mut reader = std::io::Cursor::new(buffer);
+    for tx in &txs {
+        let decoded = read_framed(&mut reader).unwrap();
+        assert_eq!(decoded.to_wire_bytes(), tx.to_wire_bytes());
+    }
+    // Stream is exhausted.
+    let err = read_framed(&mut reader).unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::UnexpectedEof);
+
+    // Oversized frame is rejected before reading its contents.
+    let mut reader = std::io::Cursor::new((MAX_FRAME_LEN as u32 + 1).to_be_bytes());
+    let err = read_framed(&mut reader).unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+}
+
+#[test]
+fn test_verify_outcome() {
+    let mut rng = XorShiftRng::from_seed([1, 2, 3, 4]);
+    let key = gen_pk_and_msg().0;
+    let transfer = Transfer::new_signed(
+        gen_account_id(&mut rng),
+        Address::from(rng.gen::<[u8; 20]>()),
+        Address::from(rng.gen::<[u8; 20]>()),
+        gen_token_id(&mut rng),
+        BigUint::from(1_000u64),
+        BigUint::from(10u64),
+        rng.gen(),
+        &key,
+    )
+    .unwrap();
+
+    let outcome = ZkSyncTx::from(transfer.clone()).verify_outcome();
+    assert!(outcome.valid);
+    assert_eq!(
+        outcome.pub_key.unwrap().serialize_packed().unwrap(),
+        PackedPublicKey(public_key_from_private(&key))
+            .serialize_packed()
+            .unwrap()
+    );
+    assert_eq!(outcome.address, Some(PubKeyHash::from_privkey(&key)));
+
+    // Signature made for the other transaction.
+    let mut other = transfer;
+    other.nonce += 1;
+    let other = Transfer::new(
+        other.account_id,
+        other.from,
+        other.to,
+        other.token,
+        other.amount,
+        other.fee,
+        other.nonce,
+        Some(other.signature),
+    );
+    let outcome = ZkSyncTx::from(other).verify_outcome();
+    assert!(!outcome.valid);
+    assert!(outcome.pub_key.is_none());
+    assert!(outcome.address.is_none());
+}
+
+#[test]
+fn test_token_id_boundary() {
+    let mut rng = XorShiftRng::from_seed([1, 2, 3, 4]);
+    let key = gen_pk_and_msg().0;
+    let account_id = gen_account_id(&mut rng);
+    let from = Address::from(rng.gen::<[u8; 20]>());
+    let to = Address::from(rng.gen::<[u8; 20]>());
+
+    for &(token, is_valid) in &[(max_token_id(), true), (max_token_id() + 1, false)] {
+        let transfer = Transfer::new_signed(
+            account_id,
+            from,
+            to,
+            token,
+            BigUint::from(1_000u64),
+            BigUint::from(10u64),
+            0,
+            &key,
+        );
+        assert_eq!(transfer.is_ok(), is_valid, "token {}", token);
+
+        let withdraw = Withdraw::new_signed(
+            account_id,
+            from,
+            to,
+            token,
+            BigUint::from(1_000u64),
+            BigUint::from(10u64),
+            0,
+            &key,
+        );
+        assert_eq!(withdraw.is_ok(), is_valid, "token {}", token);
+    }
+}
+
+#[test]
+fn test_check_nonce() {
+    let tx = ZkSyncTx::from(Transfer::new(
+        0,
+        Address::from([1u8; 20]),
+        Address::from([2u8; 20]),
+        0,
+        100u64.into(),
+        10u64.into(),
+        5,
+        None,
+    ));
+
+    assert_eq!(tx.check_nonce(5), Ok(()));
+    assert_eq!(
+        tx.check_nonce(4),
+        Err(crate::error::Error::Nonce {
+            expected: 4,
+            actual: 5
+        })
+    );
+}
+
+#[cfg(feature = "musig_sha256")]
+#[test]
+fn test_verify_prehashed() {
+    let (pk, messages) = gen_pk_and_msg();
+
+    for msg in &messages {
+        // Rescue: the digest is the Rescue hash of the message computed during signing.
+        let signature = TxSignature::sign_musig_rescue(&pk, msg);
+        let mut hash = [0u8; 32];
+        hash.copy_from_slice(&zksync_crypto::primitives::rescue_hash_tx_msg(msg));
+
+        assert!(signature.verify_musig_rescue(msg).is_some());
+        assert!(signature
+            .verify_prehashed(&hash, SignatureType::MusigRescue)
+            .is_some());
+        // Digest must not be passed to the full-message path.
+        assert!(signature.verify_musig_rescue(&hash).is_none());
+        assert!(signature
+            .verify_prehashed(&hash, SignatureType::MusigSha256)
+            .is_none());
+
+        // SHA-256: client signs the digest of the message by itself.
+        let digest = parity_crypto::digest::sha256(msg);
+        let mut hash = [0u8; 32];
+        hash.copy_from_slice(&digest);
+        let signature = TxSignature::sign_musig_sha256(&pk, &hash);
+
+        assert!(signature
+            .verify_prehashed(&hash, SignatureType::MusigSha256)
+            .is_some());
+        assert!(signature.verify_musig_sha256(msg).is_none());
+    }
+}
+
+#[test]
+fn test_normalize_and_sign() {
+    let key = gen_pk_and_msg().0;
+    let other_key = PrivateKey(XorShiftRng::from_seed([5, 6, 7, 8]).gen());
+
+    for tx in gen_signed_txs() {
+        // Replace the original signature, so the output is checked to be signed anew.
+        let (tx, _) = tx.sign_and_check(&other_key);
+        let tx = tx.normalize_and_sign(&key).unwrap();
+
+        assert_eq!(
+            tx.verify_signature(),
+            Some(PubKeyHash::from_privkey(&key)),
+            "{:?}",
+            tx
+        );
+    }
+
+    // Unpackable transfer is normalized before signing.
+    let tx: ZkSyncTx = Transfer::new(
+        0,
+        Address::from([1u8; 20]),
+        Address::from([2u8; 20]),
+        0,
+        BigUint::from(1_234_567_890_123u64),
+        BigUint::from(1_234_123_424u64),
+        0,
+        None,
+    )
+    .into();
+    let mut tx = tx.normalize_and_sign(&key).unwrap();
+    assert!(tx.check_correctness());
+
+    // Key hash of the change pubkey transaction is derived from the signing key,
+    // and the Ethereum signature made for the previous key hash is dropped.
+    let change_pubkey = gen_signed_txs().remove(3);
+    match change_pubkey
+        .clone()
+        .normalize_and_sign(&other_key)
+        .unwrap()
+    {
+        ZkSyncTx::ChangePubKey(tx) => {
+            assert_eq!(tx.new_pk_hash, PubKeyHash::from_privkey(&other_key));
+            assert!(tx.eth_signature.is_none());
+        }
+        _ => panic!("Unexpected transaction type"),
+    }
+    match change_pubkey.normalize_and_sign(&key).unwrap() {
+        ZkSyncTx::ChangePubKey(tx) => assert!(tx.eth_signature.is_some()),
+        _ => panic!("Unexpected transaction type"),
+    }
+}
+
+#[test]
+fn test_normalize() {
+    let key = gen_pk_and_msg().0;
+    let amount = BigUint::from(1_234_567_890_123u64);
+    let fee = BigUint::from(1_234_123_424u64);
+    assert!(!crate::helpers::is_token_amount_packable(&amount));
+    assert!(!crate::helpers::is_fee_amount_packable(&fee));
+
+    let transfer = |amount: BigUint, fee: BigUint| {
+        ZkSyncTx::from(Transfer::new(
+            0,
+            Address::from([1u8; 20]),
+            Address::from([2u8; 20]),
+            0,
+            amount,
+            fee,
+            0,
+            None,
+        ))
+    };
+
+    // Unpackable values are truncated in the signed bytes, so the transaction is rejected.
+    let (mut tx, _) = transfer(amount.clone(), fee.clone()).sign_and_check(&key);
+    assert!(!tx.check_correctness());
+
+    // Normalized transaction is correct and keeps the values up to the packing precision.
+    let mut tx = transfer(amount.clone(), fee.clone());
+    tx.normalize().unwrap();
+    let (mut tx, valid) = tx.sign_and_check(&key);
+    assert!(valid);
+    assert!(tx.check_correctness());
+    if let ZkSyncTx::Transfer(transfer) = &tx {
+        assert_eq!(
+            transfer.amount,
+            crate::helpers::closest_packable_token_amount(&amount)
+        );
+        assert_eq!(
+            transfer.fee,
+            crate::helpers::closest_packable_fee_amount(&fee)
+        );
+        assert!(transfer.amount <= amount && transfer.fee <= fee);
+    }
+
+    // Values which cannot be packed at all are rejected.
+    let mut tx = transfer(amount, BigUint::from(u128::max_value()));
+    assert!(matches!(
+        tx.normalize(),
+        Err(crate::error::Error::Unpackable { field: "fee", .. })
+    ));
+}
+
+#[test]
+fn test_transfer_with_base_units() {
+    let mut rng = XorShiftRng::from_seed([1, 2, 3, 4]);
+    let account_id = gen_account_id(&mut rng);
+    let from = Address::from(rng.gen::<[u8; 20]>());
+    let to = Address::from(rng.gen::<[u8; 20]>());
+    let token = gen_token_id(&mut rng);
+
+    for &(amount, fee) in &[
+        (0u128, 0u64),
+        (1_000, 10),
+        (12_345_000_000_000_000_000, 7_000),
+    ] {
+        let transfer = Transfer::with_base_units(account_id, from, to, token, amount, fee, 1, None);
+        let expected = Transfer::new(
+            account_id,
+            from,
+            to,
+            token,
+            amount.to_string().parse().unwrap(),
+            fee.to_string().parse().unwrap(),
+            1,
+            None,
+        );
+        assert_eq!(transfer.get_bytes(), expected.get_bytes());
+    }
+}
+
+#[test]
+fn test_signed_field_names() {
+    let expected: Vec<Vec<&str>> = vec![
+        vec![
+            "account_id",
+            "from",
+            "to",
+            "token",
+            "amount",
+            "fee",
+            "nonce",
+        ],
+        vec![
+            "account_id",
+            "from",
+            "to",
+            "token",
+            "amount",
+            "fee",
+            "nonce",
+        ],
+        vec!["account", "nonce"],
+        vec![
+            "account_id",
+            "account",
+            "new_pk_hash",
+            "fee_token",
+            "fee",
+            "nonce",
+        ],
+        vec!["initiator_account_id", "target", "token", "fee", "nonce"],
+        vec!["account_id", "from", "outputs", "fee_token", "fee", "nonce"],
+    ];
+
+    // The idempotency key of the transfer created by `gen_signed_txs` is not signed.
+    let txs = gen_signed_txs();
+    assert_eq!(txs.len(), expected.len());
+    for (tx, expected) in txs.iter().zip(expected) {
+        assert_eq!(tx.signed_field_names(), expected, "{:?}", tx);
+    }
+}
+
+#[test]
+fn test_explain_bytes() {
+    let txs = gen_signed_txs();
+
+    let transfer = &txs[0];
+    let explanation = explain_bytes(&transfer.get_bytes()).unwrap();
+    assert_eq!(explanation.tx_type, "Transfer");
+    let layout: Vec<_> = explanation
+        .fields
+        .iter()
+        .map(|field| (field.name.as_str(), field.range.clone()))
+        .collect();
+    assert_eq!(
+        layout,
+        vec![
+            ("type", 0..1),
+            ("account_id", 1..5),
+            ("from", 5..25),
+            ("to", 25..45),
+            ("token", 45..47),
+            ("amount", 47..52),
+            ("fee", 52..54),
+            ("nonce", 54..58),
+        ]
+    );
+    if let ZkSyncTx::Transfer(tx) = transfer {
+        assert_eq!(explanation.fields[2].value, format!("{:?}", tx.from));
+        assert_eq!(explanation.fields[5].value, tx.amount.to_string());
+        assert_eq!(explanation.fields[7].value, tx.nonce.to_string());
+    }
+
+    // Fields of every transaction type cover its bytes without gaps.
+    for tx in &txs {
+        let bytes = tx.get_bytes();
+        let explanation = explain_bytes(&bytes).unwrap();
+        let mut offset = 0;
+        for field in &explanation.fields {
+            assert_eq!(field.range.start, offset, "{:?}", explanation);
+            offset = field.range.end;
+        }
+        assert_eq!(offset, bytes.len(), "{:?}", explanation);
+    }
+
+    let multi_transfer = explain_bytes(&txs[5].get_bytes()).unwrap();
+    assert_eq!(multi_transfer.tx_type, "MultiTransfer");
+    assert_eq!(multi_transfer.fields[3].value, "2");
+    assert_eq!(
+        multi_transfer.fields[9].name,
+        "outputs[1].amount".to_string()
+    );
+
+    // Malformed bytes.
+    let mut bytes = transfer.get_bytes();
+    bytes[0] = 0xff;
+    assert_eq!(explain_bytes(&bytes), Err(WireError::UnknownTxType(0xff)));
+    let bytes = transfer.get_bytes();
+    assert!(matches!(
+        explain_bytes(&bytes[..50]),
+        Err(WireError::UnexpectedEnd { .. })
+    ));
+}
+
+#[test]
+fn test_verify_with_allowlist() {
+    let key = gen_pk_and_msg().0;
+    let txs = gen_signed_txs();
+    let allowed: std::collections::HashSet<_> = txs.iter().map(|tx| tx.account()).collect();
+
+    for tx in &txs {
+        // Target of the forced exit is not its sender, so the allowlist cannot be applied.
+        if let ZkSyncTx::ForcedExit(_) = tx {
+            assert_eq!(
+                tx.verify_with_allowlist(&allowed),
+                Err(crate::error::Error::UnknownSender)
+            );
+            continue;
+        }
+
+        assert_eq!(
+            tx.verify_with_allowlist(&allowed),
+            Ok(PubKeyHash::from_privkey(&key)),
+            "{:?}",
+            tx
+        );
+        assert_eq!(
+            tx.verify_with_allowlist(&std::collections::HashSet::new()),
+            Err(crate::error::Error::NotAllowed(tx.account()))
+        );
+    }
+    assert!(txs.iter().any(|tx| matches!(tx, ZkSyncTx::ForcedExit(_))));
+
+    // Signature is still checked for the allowed accounts.
+    let tx = match txs[0].clone() {
+        ZkSyncTx::Transfer(mut tx) => {
+            tx.signature = TxSignature::default();
+            ZkSyncTx::from(tx)
+        }
+        _ => unreachable!(),
+    };
+    assert_eq!(
+        tx.verify_with_allowlist(&allowed),
+        Err(SignatureError::IncorrectSignature.into())
+    );
+}
+
+#[test]
+fn test_base64url_encoding() {
+    let (pk, messages) = gen_pk_and_msg();
+    let signature = TxSignature::sign_musig(&pk, &messages[1]);
+
+    let pub_key = signature.pub_key.to_base64url().unwrap();
+    let sign = signature.signature.to_base64url().unwrap();
+    // Encoding is URL-safe and unpadded.
+    for encoded in &[&pub_key, &sign] {
+        assert!(encoded
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_'));
+    }
+
+    // Same bytes as the hex representation.
+    let hex_pub_key: String =
+        serde_json::from_value(serde_json::to_value(&signature.pub_key).unwrap()).unwrap();
+    let restored = PackedPublicKey::from_base64url(&pub_key).unwrap();
+    assert_eq!(
+        restored.serialize_packed().unwrap(),
+        hex::decode(hex_pub_key).unwrap()
+    );
+
+    let hex_sign: String =
+        serde_json::from_value(serde_json::to_value(&signature.signature).unwrap()).unwrap();
+    let restored = PackedSignature::from_base64url(&sign).unwrap();
+    assert_eq!(
+        restored.serialize_packed().unwrap(),
+        hex::decode(hex_sign).unwrap()
+    );
+
+    let restored = TxSignature {
+        pub_key: PackedPublicKey::from_base64url(&pub_key).unwrap(),
+        signature: PackedSignature::from_base64url(&sign).unwrap(),
+        scheme: SignatureType::default(),
+    };
+    assert!(restored.verify_musig(&messages[1]).is_some());
+
+    // Standard base64 alphabet with padding is rejected.
+    let padded = base64::encode(signature.pub_key.serialize_packed().unwrap());
+    assert!(PackedPublicKey::from_base64url(&padded).is_err());
+}
+
+#[test]
+fn test_withdraw_validate() {
+    let withdraw = |to: Address| {
+        Withdraw::new(
+            0,
+            Address::repeat_byte(0x01),
+            to,
+            0,
+            BigUint::from(1_000u64),
+            BigUint::from(10u64),
+            0,
+            None,
+        )
+    };
+
+    let tx = withdraw(Address::repeat_byte(0x02));
+    assert_eq!(tx.validate(false), Ok(()));
+    assert_eq!(tx.validate(true), Ok(()));
+
+    // Withdrawal to the zero address is only accepted as an intentional burn.
+    let tx = withdraw(Address::zero());
+    assert_eq!(tx.validate(false), Err(WithdrawError::ZeroAddress));
+    assert_eq!(tx.validate(true), Ok(()));
+}
+
+#[test]
+fn test_tx_type_tag_aliases() {
+    let aliases: &[&[&str]] = &[
+        &["transfer"],
+        &["withdraw"],
+        &["close"],
+        &["changepubkey", "change_pub_key", "changePubKey"],
+        &["forcedexit", "forced_exit", "forcedExit"],
+        &["multitransfer", "multi_transfer", "multiTransfer"],
+    ];
+
+    for (tx, aliases) in gen_signed_txs().into_iter().zip(aliases) {
+        let canonical = serde_json::to_value(&tx).unwrap();
+
+        for alias in aliases.iter() {
+            let mut value = canonical.clone();
+            value["type"] = (*alias).into();
+
+            let restored: ZkSyncTx = serde_json::from_value(value).unwrap();
+            assert_eq!(restored.hash(), tx.hash(), "{}", alias);
+            // Serialization stays canonical.
+            assert_eq!(serde_json::to_value(&restored).unwrap(), canonical);
+        }
+    }
+
+    let mut value = serde_json::to_value(&gen_signed_txs()[0]).unwrap();
+    value["type"] = "TRANSFER".into();
+    assert!(serde_json::from_value::<ZkSyncTx>(value).is_err());
+}
+
+#[test]
+fn test_verify_and_recover() {
+    let key = gen_pk_and_msg().0;
+    let cache = AddressCache::new(10);
+    let txs = gen_signed_txs();
+
+    let outcome = txs[0].verify_and_recover(&cache);
+    assert!(outcome.valid);
+    assert_eq!(outcome.address, Some(PubKeyHash::from_privkey(&key)));
+    assert_eq!((cache.len(), cache.hits()), (1, 0));
+
+    // All the transactions are signed by the same key, so the address is taken from the cache.
+    for tx in &txs {
+        let expected = tx.verify_outcome();
+        let outcome = tx.verify_and_recover(&cache);
+        assert_eq!(outcome.valid, expected.valid);
+        assert_eq!(outcome.address, expected.address);
+    }
+    assert_eq!(cache.len(), 1);
+    assert_eq!(cache.hits(), txs.len() as u64);
+
+    // Incorrect signature doesn't touch the cache.
+    let tx = match txs[0].clone() {
+        ZkSyncTx::Transfer(mut tx) => {
+            tx.signature = TxSignature::default();
+            ZkSyncTx::from(tx)
+        }
+        _ => unreachable!(),
+    };
+    let outcome = tx.verify_and_recover(&cache);
+    assert!(!outcome.valid && outcome.address.is_none());
+    assert_eq!(cache.hits(), txs.len() as u64);
+
+    // Full cache still resolves the addresses.
+    let cache = AddressCache::new(0);
+    assert_eq!(
+        txs[0].verify_and_recover(&cache).address,
+        Some(PubKeyHash::from_privkey(&key))
+    );
+    assert!(cache.is_empty());
+}
+
+#[cfg(feature = "musig_sha256")]
+#[test]
+fn test_signature_scheme_field() {
+    let (key, messages) = gen_pk_and_msg();
+
+    for &variant in SignatureType::ALL.iter() {
+        let mut transfer = Transfer::new_signed(
+            1,
+            Address::from([1; 20]),
+            Address::from([2; 20]),
+            0,
+            BigUint::from(1_000u64),
+            BigUint::from(10u64),
+            0,
+            &key,
+        )
+        .unwrap();
+        transfer.signature =
+            TxSignature::sign_musig_with_type(&key, &transfer.get_bytes(), variant);
+        assert_eq!(transfer.signature.scheme, variant);
+
+        // The scheme survives the JSON roundtrip and is used for the verification.
+        let tx: ZkSyncTx =
+            serde_json::from_value(serde_json::to_value(ZkSyncTx::from(transfer)).unwrap())
+                .unwrap();
+        assert_eq!(tx.signature().scheme, variant);
+        assert_eq!(tx.check_signature_any(), Ok(variant));
+
+        // The scheme isn't a part of the packed signature.
+        let mut signature = tx.signature().clone();
+        let packed = signature.to_bytes();
+        signature.scheme = SignatureType::ALL
+            .iter()
+            .copied()
+            .find(|&other| other != variant)
+            .unwrap();
+        assert_eq!(signature.to_bytes()[..], packed[..]);
+    }
+
+    // Rescue is the default: the field is omitted and assumed if missing.
+    let signature = TxSignature::sign_musig_rescue(&key, &messages[0]);
+    let value = serde_json::to_value(&signature).unwrap();
+    assert!(value.get("scheme").is_none());
+    let restored: TxSignature = serde_json::from_value(value).unwrap();
+    assert_eq!(restored.scheme, SignatureType::MusigRescue);
+
+    let signature = TxSignature::sign_musig_sha256(&key, &messages[0]);
+    let value = serde_json::to_value(&signature).unwrap();
+    assert_eq!(value["scheme"], "MusigSha256");
+
+    // A wrong hint doesn't make a valid signature rejected.
+    let mut transfer = Transfer::new_signed(
+        1,
+        Address::from([1; 20]),
+        Address::from([2; 20]),
+        0,
+        BigUint::from(1_000u64),
+        BigUint::from(10u64),
+        0,
+        &key,
+    )
+    .unwrap();
+    transfer.signature = TxSignature::sign_musig_sha256(&key, &transfer.get_bytes());
+    transfer.signature.scheme = SignatureType::MusigRescue;
+    assert_eq!(
+        ZkSyncTx::from(transfer).check_signature_any(),
+        Ok(SignatureType::MusigSha256)
+    );
+}
+
+#[test]
+fn test_signature_ct_eq() {
+    let (key, messages) = gen_pk_and_msg();
+    let signature = TxSignature::sign_musig(&key, &messages[1]);
+
+    assert!(signature.ct_eq(&signature));
+    assert!(signature.ct_eq(&TxSignature::from_bytes(&signature.to_bytes()).unwrap()));
+
+    // The scheme hint isn't a part of the packed representation.
+    let mut hinted = signature.clone();
+    hinted.scheme = SignatureType::MusigSha256;
+    assert!(signature.ct_eq(&hinted));
+
+    // Signature of another message.
+    assert!(!signature.ct_eq(&TxSignature::sign_musig(&key, &messages[0])));
+    // Same signature attributed to another public key.
+    let mut other_key = signature.clone();
+    other_key.pub_key = TxSignature::sign_musig(
+        &PrivateKey(XorShiftRng::from_seed([5, 6, 7, 8]).gen()),
+        &messages[1],
+    )
+    .pub_key;
+    assert!(!signature.ct_eq(&other_key));
+    assert!(!signature.ct_eq(&TxSignature::default()));
+}
+
+#[cfg(feature = "musig_sha256")]
+#[test]
+fn test_verify_with_external_pubkey() {
+    let (key, messages) = gen_pk_and_msg();
+    let other_key = PrivateKey(XorShiftRng::from_seed([5, 6, 7, 8]).gen());
+
+    for &variant in SignatureType::ALL.iter() {
+        for msg in &messages {
+            let signature = TxSignature::sign_musig_with_type(&key, msg, variant);
+            let verify = |pub_key: &PackedPublicKey, msg: &[u8], variant| {
+                verify_with_external_pubkey(pub_key, &signature.signature, msg, variant)
+            };
+
+            // Same result as for the public key embedded into the signature.
+            for &other_variant in SignatureType::ALL.iter() {
+                assert_eq!(
+                    verify(&signature.pub_key, msg, other_variant),
+                    signature
+                        .verify_musig_with_type(msg, other_variant)
+                        .is_some()
+                );
+            }
+            assert!(verify(&signature.pub_key, msg, variant));
+
+            let other_pub_key = TxSignature::sign_musig(&other_key, msg).pub_key;
+            assert!(!verify(&other_pub_key, msg, variant));
+            assert!(!verify(&signature.pub_key, b"another message", variant));
+        }
+    }
+
+    // Signature of the prefix doesn't verify the longer message.
+    let msg = vec![3u8; MAX_SIGNED_MESSAGE_LEN];
+    let signature = TxSignature::sign_musig(&key, &msg);
+    let verify = |msg: &[u8]| {
+        verify_with_external_pubkey(
+            &signature.pub_key,
+            &signature.signature,
+            msg,
+            SignatureType::MusigRescue,
+        )
+    };
+    assert!(verify(&msg));
+    assert!(!verify(&[&msg[..], b"suffix"].concat()));
+}
+
+#[cfg(feature = "musig_sha256")]
+#[test]
+fn test_verify_string() {
+    let key = gen_pk_and_msg().0;
+    let message = "Hello, zkSync! Привет 👋";
+
+    for &variant in SignatureType::ALL.iter() {
+        let signature = TxSignature::sign_musig_with_type(&key, message.as_bytes(), variant);
+        assert_eq!(
+            verify_string(message, &signature, variant),
+            Some(PubKeyHash::from_privkey(&key))
+        );
+        assert_eq!(
+            verify_string(message, &signature, variant),
+            verify_detached(message.as_bytes(), &signature, variant)
+        );
+        assert_eq!(verify_string("Hello, zkSync!", &signature, variant), None);
+    }
+
+    // The message is not length-prefixed.
+    let mut prefixed = vec![message.len() as u8];
+    prefixed.extend_from_slice(message.as_bytes());
+    let signature = TxSignature::sign_musig(&key, &prefixed);
+    assert_eq!(
+        verify_string(message, &signature, SignatureType::MusigRescue),
+        None
+    );
+
+    // Messages differing only after the signed prefix are not accepted.
+    let prefix = "a".repeat(MAX_SIGNED_MESSAGE_LEN);
+    let signature = TxSignature::sign_musig(&key, prefix.as_bytes());
+    assert!(verify_string(&prefix, &signature, SignatureType::MusigRescue).is_some());
+    for suffix in &[" pay 1 to A", " pay 1000 to B"] {
+        let message = format!("{}{}", prefix, suffix);
+        assert_eq!(
+            verify_string(&message, &signature, SignatureType::MusigRescue),
+            None
+        );
+    }
+}
+
+#[test]
+fn test_check_fee() {
+    let alice = Address::from([1u8; 20]);
+    let bob = Address::from([2u8; 20]);
+    let transfer = |fee: u64| {
+        ZkSyncTx::from(Transfer::new(
+            0,
+            alice,
+            bob,
+            0,
+            1_000u64.into(),
+            fee.into(),
+            0,
+            None,
+        ))
+    };
+    let withdraw = |fee: u64| {
+        ZkSyncTx::from(Withdraw::new(
+            0,
+            alice,
+            alice,
+            0,
+            1_000u64.into(),
+            fee.into(),
+            0,
+            None,
+        ))
+    };
+
+    let table = FeeTable::new()
+        .with_min_fee(Transfer::TX_TYPE, 10u64.into())
+        .with_min_fee(Withdraw::TX_TYPE, 50u64.into());
+
+    assert_eq!(transfer(10).check_fee(&table), Ok(()));
+    assert_eq!(transfer(30).check_fee(&table), Ok(()));
+    assert_eq!(
+        transfer(9).check_fee(&table),
+        Err(crate::error::Error::Validation(FeeError::FeeBelowMinimum {
+            fee: 9u64.into(),
+            min_fee: 10u64.into(),
+        }))
+    );
+
+    // Fee sufficient for the transfer is not enough for the withdraw.
+    assert_eq!(withdraw(50).check_fee(&table), Ok(()));
+    assert_eq!(
+        withdraw(30).check_fee(&table),
+        Err(crate::error::Error::Validation(FeeError::FeeBelowMinimum {
+            fee: 30u64.into(),
+            min_fee: 50u64.into(),
+        }))
+    );
+
+    // Types without the minimum fee are accepted.
+    let forced_exit = ZkSyncTx::from(ForcedExit::new(0, bob, 0, 0u64.into(), 0, None));
+    assert_eq!(forced_exit.check_fee(&table), Ok(()));
+    assert_eq!(transfer(0).check_fee(&FeeTable::new()), Ok(()));
+}
+
+#[test]
+fn test_signing_request() {
+    let tx = ZkSyncTx::from(Transfer::new(
+        1,
+        Address::from([1u8; 20]),
+        Address::from([2u8; 20]),
+        0,
+        1_000u64.into(),
+        10u64.into(),
+        7,
+        None,
+    ));
+
+    let request = tx.to_signing_request().unwrap();
+    assert_eq!(request.tx_type, "Transfer");
+    assert_eq!(request.bytes, tx.get_bytes());
+    assert_eq!(request.tx_hash, tx.hash());
+    assert_eq!(
+        request.fields,
+        explain_bytes(&tx.get_bytes()).unwrap().fields
+    );
+
+    let value = serde_json::to_value(&request).unwrap();
+    assert_eq!(value["txType"], "Transfer");
+    assert_eq!(
+        value["bytes"],
+        format!("0x{}", hex::encode(tx.get_bytes())).as_str()
+    );
+    assert_eq!(value["txHash"], tx.hash().to_string().as_str());
+    assert_eq!(value["fields"][7]["name"], "nonce");
+    assert_eq!(value["fields"][7]["value"], "7");
+
+    let restored: SigningRequest = serde_json::from_value(value).unwrap();
+    assert_eq!(restored, request);
+
+    // Number of outputs doesn't fit into the encoding, so the bytes cannot be explained.
+    let output = TransferOutput::new(
+        Address::from([2u8; 20]),
+        0,
+        Amount::new(1u64.into()).unwrap(),
+    );
+    let tx = ZkSyncTx::from(MultiTransfer::new(
+        1,
+        Address::from([1u8; 20]),
+        vec![output; 256],
+        0,
+        10u64.into(),
+        7,
+        None,
+    ));
+    assert!(matches!(
+        tx.to_signing_request(),
+        Err(WireError::InvalidLength { .. })
+    ));
+}
+
+#[test]
+fn test_verify_timed() {
+    let (key, messages) = gen_pk_and_msg();
+
+    for msg in &messages {
+        let signature = TxSignature::sign_musig(&key, msg);
+        let (valid, elapsed) = signature.verify_timed(msg);
+        assert_eq!(valid, signature.verify_musig(msg).is_some());
+        assert!(valid);
+        assert!(elapsed > std::time::Duration::from_secs(0));
+
+        let (valid, _) = signature.verify_timed(b"another message");
+        assert_eq!(valid, signature.verify_musig(b"another message").is_some());
+        assert!(!valid);
+    }
+}
+
+#[test]
+fn test_target_account_type() {
+    let alice = Address::from([1u8; 20]);
+    let bob = Address::from([2u8; 20]);
+
+    let withdraw = ZkSyncTx::from(Withdraw::new(
+        0,
+        alice,
+        bob,
+        0,
+        1_000u64.into(),
+        10u64.into(),
+        0,
+        None,
+    ));
+    assert_eq!(withdraw.target_account_type(), AccountType::L1);
+
+    let transfer = ZkSyncTx::from(Transfer::new(
+        0,
+        alice,
+        bob,
+        0,
+        1_000u64.into(),
+        10u64.into(),
+        0,
+        None,
+    ));
+    assert_eq!(transfer.target_account_type(), AccountType::L2);
+
+    let forced_exit = ZkSyncTx::from(ForcedExit::new(0, bob, 0, 10u64.into(), 0, None));
+    assert_eq!(forced_exit.target_account_type(), AccountType::L1);
+}
+
+#[test]
+fn test_multi_transfer_max_outputs() {
+    let key = gen_pk_and_msg().0;
+    let from = Address::from([1u8; 20]);
+    let output = TransferOutput::new(
+        Address::from([2u8; 20]),
+        0,
+        Amount::new(1_000u64.into()).unwrap(),
+    );
+    let multi_transfer = |outputs_count| {
+        let mut tx = MultiTransfer::new(
+            0,
+            from,
+            vec![output.clone(); outputs_count],
+            0,
+            10u64.into(),
+            0,
+            None,
+        );
+        tx.signature = TxSignature::sign_musig(&key, &tx.get_bytes());
+        tx
+    };
+
+    // Exactly at the limit.
+    let outputs = vec![output.clone(); MultiTransfer::MAX_OUTPUTS];
+    assert!(MultiTransfer::new_signed(0, from, outputs, 0, 10u64.into(), 0, &key).is_ok());
+    let mut tx = multi_transfer(MultiTransfer::MAX_OUTPUTS);
+    assert!(tx.check_correctness());
+    let tx = ZkSyncTx::from(tx);
+    let restored = ZkSyncTx::from_wire_bytes_strict(&tx.to_wire_bytes()).unwrap();
+    assert_eq!(restored.hash(), tx.hash());
+
+    // One over the limit.
+    let outputs = vec![output; MultiTransfer::MAX_OUTPUTS + 1];
+    assert!(MultiTransfer::new_signed(0, from, outputs, 0, 10u64.into(), 0, &key).is_err());
+    let mut tx = multi_transfer(MultiTransfer::MAX_OUTPUTS + 1);
+    assert!(!tx.check_correctness());
+    assert_eq!(
+        ZkSyncTx::from_wire_bytes_strict(&ZkSyncTx::from(tx).to_wire_bytes()).err(),
+        Some(WireError::TooManyOutputs(MultiTransfer::MAX_OUTPUTS + 1))
+    );
+}
+
+#[test]
+fn test_yaml_roundtrip() {
+    for tx in gen_signed_txs() {
+        let yaml = tx.to_yaml().unwrap();
+        let restored = ZkSyncTx::from_yaml(&yaml).unwrap();
+
+        assert_eq!(restored.hash(), tx.hash(), "{}", yaml);
+        assert_eq!(
+            serde_json::to_value(&restored).unwrap(),
+            serde_json::to_value(&tx).unwrap()
+        );
+        assert_eq!(restored.verify_signature(), tx.verify_signature());
+    }
+
+    // Hex string fields are written as is.
+    let yaml = r#"
+type: Close
+account: "0x0101010101010101010101010101010101010101"
+nonce: 1
+signature:
+  pubKey: "0000000000000000000000000000000000000000000000000000000000000000"
+  signature: "00000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000"
+"#;
+    let tx = ZkSyncTx::from_yaml(yaml).unwrap();
+    assert_eq!(tx.account(), Address::from([1u8; 20]));
+    assert_eq!(tx.nonce(), 1);
+}
+
+#[test]
+fn test_signature_is_well_formed() {
+    use zksync_crypto::franklin_crypto::alt_babyjubjub::edwards;
+
+    let (key, messages) = gen_pk_and_msg();
+    for &variant in SignatureType::ALL.iter() {
+        let signature = TxSignature::sign_musig_with_type(&key, &messages[1], variant);
+        assert!(signature.is_well_formed());
+    }
+
+    // Identity public key.
+    let mut signature = TxSignature::sign_musig(&key, &messages[1]);
+    signature.pub_key = PackedPublicKey(PublicKey::<Engine>(edwards::Point::zero()));
+    assert!(!signature.is_well_formed());
+    assert!(signature.verify_musig(&messages[1]).is_none());
+
+    // Identity `R` point.
+    let mut signature = TxSignature::sign_musig(&key, &messages[1]);
+    signature.signature.0.r = edwards::Point::zero();
+    assert!(!signature.is_well_formed());
+    for &variant in SignatureType::ALL.iter() {
+        assert!(signature
+            .verify_musig_with_type(&messages[1], variant)
+            .is_none());
+    }
+
+    // All-zero bytes decode into the points of a small order.
+    assert!(!TxSignature::default().is_well_formed());
+}
+
+#[test]
+fn test_verify_with_operator() {
+    let key = gen_pk_and_msg().0;
+    let operator_key = PrivateKey(XorShiftRng::from_seed([5, 6, 7, 8]).gen());
+    let operator_pubkey = PackedPublicKey(public_key_from_private(&operator_key));
+
+    let mut transfer = Transfer::new_signed(
+        1,
+        Address::from([1u8; 20]),
+        Address::from([2u8; 20]),
+        0,
+        1_000u64.into(),
+        10u64.into(),
+        0,
+        &key,
+    )
+    .unwrap();
+    let bytes = transfer.get_bytes();
+
+    // Operator signature is absent.
+    let tx = ZkSyncTx::from(transfer.clone());
+    assert!(tx.operator_signature().is_none());
+    assert_eq!(tx.verify_with_operator(&operator_pubkey), None);
+    assert!(serde_json::to_value(&tx)
+        .unwrap()
+        .get("operatorSignature")
+        .is_none());
+
+    // Operator signature is present.
+    transfer.operator_signature = Some(TxSignature::sign_musig(&operator_key, &bytes));
+    let tx = ZkSyncTx::from(transfer.clone());
+    assert_eq!(
+        tx.verify_with_operator(&operator_pubkey),
+        Some(PubKeyHash::from_privkey(&key))
+    );
+    // The operator signature isn't a part of the signed data.
+    assert_eq!(tx.get_bytes(), bytes);
+    let restored: ZkSyncTx = serde_json::from_value(serde_json::to_value(&tx).unwrap()).unwrap();
+    assert_eq!(
+        restored.verify_with_operator(&operator_pubkey),
+        Some(PubKeyHash::from_privkey(&key))
+    );
+
+    // Signature of another operator.
+    let other_pubkey = PackedPublicKey(public_key_from_private(&key));
+    assert_eq!(tx.verify_with_operator(&other_pubkey), None);
+
+    // Operator signature of other data.
+    transfer.operator_signature = Some(TxSignature::sign_musig(&operator_key, b"hello world"));
+    assert_eq!(
+        ZkSyncTx::from(transfer.clone()).verify_with_operator(&operator_pubkey),
+        None
+    );
+
+    // Incorrect user signature.
+    transfer.operator_signature = Some(TxSignature::sign_musig(&operator_key, &bytes));
+    let mut tx = ZkSyncTx::from(Transfer::new(
+        transfer.account_id,
+        transfer.from,
+        transfer.to,
+        transfer.token,
+        transfer.amount.clone(),
+        transfer.fee.clone(),
+        transfer.nonce,
+        Some(TxSignature::sign_musig(&key, b"hello world")),
+    ));
+    if let ZkSyncTx::Transfer(tx) = &mut tx {
+        tx.operator_signature = transfer.operator_signature.clone();
+    }
+    assert_eq!(tx.verify_with_operator(&operator_pubkey), None);
+
+    // Placeholder operator signature is never accepted, even for the zero public key.
+    transfer.operator_signature = Some(TxSignature::default());
+    let tx = ZkSyncTx::from(transfer.clone());
+    assert_eq!(
+        tx.verify_with_operator(&TxSignature::default().pub_key),
+        None
+    );
+    assert_eq!(tx.verify_with_operator(&operator_pubkey), None);
+
+    // Operator signatures of the incorrect length are rejected on deserialization.
+    transfer.operator_signature = Some(TxSignature::sign_musig(&operator_key, &bytes));
+    let json = serde_json::to_value(ZkSyncTx::from(transfer)).unwrap();
+    let signature = json["operatorSignature"]["signature"].as_str().unwrap();
+    for malformed in &[String::new(), format!("{}00", signature)] {
+        let mut json = json.clone();
+        json["operatorSignature"]["signature"] = malformed.as_str().into();
+        assert!(serde_json::from_value::<ZkSyncTx>(json).is_err());
+    }
+}
+
+#[test]
+fn test_precomputed_verifier() {
+    let (key, messages) = gen_pk_and_msg();
+    let other_key = PrivateKey(XorShiftRng::from_seed([5, 6, 7, 8]).gen());
+    let pub_key = PackedPublicKey(public_key_from_private(&key));
+    let other_pub_key = PackedPublicKey(public_key_from_private(&other_key));
+    let malformed_pub_key = TxSignature::default().pub_key;
+
+    let verifier = PrecomputedVerifier::new(vec![&pub_key, &pub_key, &malformed_pub_key]);
+    assert_eq!(verifier.len(), 1);
+
+    let direct = |pub_key: &PackedPublicKey, sign: &TxSignature, msg: &[u8]| {
+        sign.verify_with_pubkey(msg, pub_key)
+            .map(|pub_key| PubKeyHash::from_pubkey(&pub_key))
+    };
+
+    let signatures = vec![
+        TxSignature::sign_musig(&key, &messages[1]),
+        TxSignature::sign_musig(&other_key, &messages[1]),
+        TxSignature::sign_musig_sha256(&key, &messages[1]),
+        TxSignature::default(),
+    ];
+    for &pub_key in &[&pub_key, &other_pub_key, &malformed_pub_key] {
+        for sign in &signatures {
+            for msg in &messages {
+                assert_eq!(
+                    verifier.verify(pub_key, sign, msg),
+                    direct(pub_key, sign, msg.as_slice())
+                );
+            }
+        }
+    }
+
+    assert_eq!(
+        verifier.verify(&pub_key, &signatures[0], &messages[1]),
+        Some(PubKeyHash::from_privkey(&key))
+    );
+    // Keys unknown to the verifier are checked as well.
+    assert_eq!(
+        verifier.verify(&other_pub_key, &signatures[1], &messages[1]),
+        Some(PubKeyHash::from_privkey(&other_key))
+    );
+    // Signature made by another key.
+    assert_eq!(
+        verifier.verify(&pub_key, &signatures[1], &messages[1]),
+        None
+    );
+}
+
+#[test]
+fn test_invalid_txs() {
+    let key = gen_pk_and_msg().0;
+    let mut txs = generate_test_transactions(10, 1);
+    assert!(invalid_txs(&txs).is_empty());
+
+    // Signature made for other data.
+    if let ZkSyncTx::Transfer(tx) = &mut txs[3] {
+        tx.signature = TxSignature::sign_musig(&key, b"hello world");
+    }
+    // Correctly signed transfer to the zero address.
+    txs[7] = match txs[7].clone() {
+        ZkSyncTx::Transfer(mut tx) => {
+            tx.to = Address::zero();
+            ZkSyncTx::from(tx).sign_and_check(&key).0
+        }
+        _ => unreachable!(),
+    };
+    assert!(txs[7].verify_signature().is_some());
+
+    assert_eq!(
+        invalid_txs(&txs),
+        vec![
+            (3, InvalidTxReason::IncorrectSignature),
+            (7, InvalidTxReason::Malformed)
+        ]
+    );
+    assert!(invalid_txs(&[]).is_empty());
+}
+
+#[test]
+fn test_signature_scheme_enabled() {
+    assert!(SignatureType::MusigRescue.is_enabled());
+    assert_eq!(SignatureType::MusigRescue.ensure_enabled(), Ok(()));
+    assert_eq!(
+        SignatureType::MusigSha256.is_enabled(),
+        cfg!(feature = "musig_sha256")
+    );
+}
+
+#[cfg(not(feature = "musig_sha256"))]
+#[test]
+fn test_sha256_scheme_disabled() {
+    let key = gen_pk_and_msg().0;
+    let mut transfer = Transfer::new_signed(
+        1,
+        Address::from([1; 20]),
+        Address::from([2; 20]),
+        0,
+        BigUint::from(1_000u64),
+        BigUint::from(10u64),
+        0,
+        &key,
+    )
+    .unwrap();
+    let bytes = transfer.get_bytes();
+    transfer.signature = TxSignature::sign_musig_sha256(&key, &bytes);
+
+    assert_eq!(
+        SignatureType::MusigSha256.ensure_enabled(),
+        Err(SignatureError::SchemeDisabled(SignatureType::MusigSha256))
+    );
+    assert!(transfer
+        .signature
+        .verify_musig_with_type(&bytes, SignatureType::MusigSha256)
+        .is_none());
+    assert_eq!(
+        verify_detached(&bytes, &transfer.signature, SignatureType::MusigSha256),
+        None
+    );
+    assert_eq!(
+        ZkSyncTx::from(transfer).check_signature_any(),
+        Err(SignatureError::SchemeDisabled(SignatureType::MusigSha256))
+    );
+}
+
+#[test]
+fn test_direction() {
+    let alice = Address::repeat_byte(0x01);
+    let bob = Address::repeat_byte(0x02);
+    let carol = Address::repeat_byte(0x03);
+    let transfer = |from, to| {
+        Transfer::new(
+            0,
+            from,
+            to,
+            0,
+            BigUint::from(1_000u64),
+            BigUint::from(10u64),
+            0,
+            None,
+        )
+    };
+
+    let tx = transfer(alice, bob);
+    assert_eq!(tx.direction(&alice), Direction::Outgoing);
+    assert_eq!(tx.direction(&bob), Direction::Incoming);
+    assert_eq!(tx.direction(&carol), Direction::Unrelated);
+    assert_eq!(
+        transfer(alice, alice).direction(&alice),
+        Direction::SelfTransfer
+    );
+
+    // Withdrawal to the own L1 address is still outgoing.
+    for &to in &[alice, bob] {
+        let tx = Withdraw::new(
+            0,
+            alice,
+            to,
+            0,
+            BigUint::from(1_000u64),
+            BigUint::from(10u64),
+            0,
+            None,
+        );
+        assert_eq!(tx.direction(&alice), Direction::Outgoing);
+        assert_eq!(tx.direction(&bob), Direction::Unrelated);
+    }
+}
+
+#[test]
+fn test_assign_nonces() {
+    let key = gen_pk_and_msg().0;
+    let mut txs = gen_signed_txs();
+
+    assign_nonces(&mut txs, 42).unwrap();
+    let nonces: Vec<_> = txs.iter().map(ZkSyncTx::nonce).collect();
+    assert_eq!(nonces, (42..42 + txs.len() as Nonce).collect::<Vec<_>>());
+    // Signatures were made for the previous nonces.
+    assert!(txs[0].verify_signature().is_none());
+    let tx = txs[0].clone().sign_and_check(&key);
+    assert!(tx.1);
+    assert_eq!(tx.0.nonce(), 42);
+
+    // The last nonce may be the maximum one.
+    let start = Nonce::max_value() - txs.len() as Nonce + 1;
+    assign_nonces(&mut txs, start).unwrap();
+    assert_eq!(txs.last().unwrap().nonce(), Nonce::max_value());
+
+    // Nothing is changed on overflow.
+    assert_eq!(
+        assign_nonces(&mut txs, start + 1),
+        Err(crate::error::Error::NonceOverflow {
+            start: start + 1,
+            count: txs.len(),
+        })
+    );
+    assert_eq!(txs[0].nonce(), start);
+
+    assert_eq!(assign_nonces(&mut [], Nonce::max_value()), Ok(()));
+}
+
+#[cfg(feature = "verify_trace")]
+#[test]
+fn test_verify_traced() {
+    let (key, messages) = gen_pk_and_msg();
+    let signature = TxSignature::sign_musig(&key, &messages[1]);
+    let packed = signature.signature.serialize_packed().unwrap();
+
+    let trace = signature.verify_traced(&messages[1]);
+    assert_eq!(
+        trace.hashed_msg,
+        zksync_crypto::primitives::rescue_hash_tx_msg(&messages[1])
+    );
+    assert_eq!(trace.r[..], packed[..32]);
+    assert_eq!(trace.s[..], packed[32..]);
+    assert!(trace.well_formed);
+    assert!(trace.valid);
+
+    // Signature of another message.
+    let trace = signature.verify_traced(&messages[0]);
+    assert_eq!(
+        trace.hashed_msg,
+        zksync_crypto::primitives::rescue_hash_tx_msg(&messages[0])
+    );
+    assert!(trace.well_formed);
+    assert!(!trace.valid);
+
+    let trace = TxSignature::default().verify_traced(&messages[1]);
+    assert!(!trace.well_formed && !trace.valid);
+}
+
+#[test]
+fn test_rlp_roundtrip() {
+    let key = gen_pk_and_msg().0;
+    let mut txs = gen_signed_txs();
+    txs.push(ZkSyncTx::from(Close {
+        account: Address::from([3; 20]),
+        nonce: 7,
+        signature: TxSignature::default(),
+    }));
+    let mut withdraw = Withdraw::new(
+        1,
+        Address::from([1; 20]),
+        Address::from([2; 20]),
+        0,
+        BigUint::from(0u64),
+        BigUint::from(10u64),
+        0,
+        None,
+    );
+    withdraw.fee_recipient = Some(Address::from([4; 20]));
+    txs.push(ZkSyncTx::from(withdraw).sign_and_check(&key).0);
+
+    for tx in txs {
+        let rlp = tx.to_rlp();
+        let decoded = ZkSyncTx::from_rlp(&rlp).unwrap();
+
+        assert_eq!(decoded.hash(), tx.hash());
+        assert_eq!(decoded.to_rlp(), rlp);
+        assert_eq!(decoded.get_bytes(), tx.get_bytes());
+        assert_eq!(
+            decoded.signature().to_bytes()[..],
+            tx.signature().to_bytes()[..]
+        );
+        assert_eq!(decoded.verify_signature(), tx.verify_signature());
+        if let (ZkSyncTx::ChangePubKey(decoded), ZkSyncTx::ChangePubKey(tx)) = (&decoded, &tx) {
+            assert_eq!(decoded.eth_signature, tx.eth_signature);
+        }
+        // RLP encoding is a list, unlike the signed bytes.
+        assert!(rlp[0] >= 0xc0);
+        assert_ne!(rlp, tx.get_bytes());
+    }
+
+    // Incorrect inputs.
+    let rlp = gen_signed_txs()[0].to_rlp();
+    assert!(ZkSyncTx::from_rlp(&rlp[..rlp.len() - 1]).is_err());
+    assert!(ZkSyncTx::from_rlp(&[0x80]).is_err());
+    let mut stream = rlp::RlpStream::new_list(1);
+    stream.append(&0xffu8);
+    assert!(ZkSyncTx::from_rlp(&stream.out()).is_err());
+}
+
+#[test]
+fn test_verify_batch_signature() {
+    let (key, messages) = gen_pk_and_msg();
+    let mut txs = gen_signed_txs();
+    let sign = TxSignature::sign_musig(&key, &batch_signing_message(&txs));
+
+    assert!(verify_batch_signature(&txs, &sign));
+    // Signature of the transactions concatenation itself is rejected.
+    let bytes: Vec<u8> = txs.iter().flat_map(ZkSyncTx::get_bytes).collect();
+    assert!(!verify_batch_signature(
+        &txs,
+        &TxSignature::sign_musig(&key, &bytes)
+    ));
+    assert!(!verify_batch_signature(
+        &txs,
+        &TxSignature::sign_musig(&key, &messages[1])
+    ));
+
+    // Tampering with the last transaction, far beyond the first 92 bytes of the batch.
+    if let Some(ZkSyncTx::MultiTransfer(tx)) = txs.last_mut() {
+        tx.nonce += 1;
+    } else {
+        panic!("MultiTransfer is expected to be the last transaction");
+    }
+    assert!(!verify_batch_signature(&txs, &sign));
+
+    // Reordering the transactions.
+    let mut txs = gen_signed_txs();
+    txs.swap(0, 1);
+    assert!(!verify_batch_signature(&txs, &sign));
+    assert!(!verify_batch_signature(&txs[..1], &sign));
+}
+
+#[test]
+fn test_amount() {
+    for &value in &[0u64, 1, 1_000, 34_359_738_367, 1_000_000_000_000_000_000] {
+        let amount = Amount::new(BigUint::from(value)).unwrap();
+        assert_eq!(*amount, BigUint::from(value));
+
+        let json = serde_json::to_string(&amount).unwrap();
+        assert_eq!(json, format!("\"{}\"", value));
+        assert_eq!(serde_json::from_str::<Amount>(&json).unwrap(), amount);
+    }
+
+    // Values which cannot be packed precisely are rejected.
+    assert_eq!(
+        Amount::new(BigUint::from(1_234_567_890_123u64)),
+        Err(crate::error::Error::Imprecise {
+            field: "amount",
+            value: BigUint::from(1_234_567_890_123u64),
+        })
+    );
+    assert!(matches!(
+        Amount::new(BigUint::from(u128::max_value()) + 1u64),
+        Err(crate::error::Error::Unpackable {
+            field: "amount",
+            ..
+        })
+    ));
+
+    // Deserialization performs the same checks.
+    assert!(serde_json::from_str::<Amount>("\"1234567890123\"").is_err());
+    assert!(serde_json::from_str::<Amount>("\"-1000\"").is_err());
+    assert!(serde_json::from_str::<Amount>("\"1000.5\"").is_err());
+    assert_eq!(
+        serde_json::from_str::<Amount>("\"1000.00\"").unwrap(),
+        Amount::new(BigUint::from(1_000u64)).unwrap()
+    );
+
+    // Transactions with unpackable output amounts cannot be deserialized.
+    let multi_transfer = gen_signed_txs().pop().unwrap();
+    let mut json = serde_json::to_value(&multi_transfer).unwrap();
+    json["outputs"][0]["amount"] = "1234567890123".into();
+    assert!(serde_json::from_value::<ZkSyncTx>(json).is_err());
+}
+
+#[test]
+fn test_label() {
+    let key = gen_pk_and_msg().0;
+    for tx in gen_signed_txs().into_iter().take(2) {
+        assert_eq!(tx.label(), None);
+        let bytes = tx.get_bytes();
+        let json = serde_json::to_value(&tx).unwrap();
+        assert!(json.get("label").is_none());
+
+        let mut labeled = tx.clone();
+        match &mut labeled {
+            ZkSyncTx::Transfer(tx) => tx.label = Some("Rent for May".to_owned()),
+            ZkSyncTx::Withdraw(tx) => tx.label = Some("Rent for May".to_owned()),
+            _ => panic!("Unexpected transaction type"),
+        }
+
+        // Label doesn't affect the signed bytes, so the signature stays valid.
+        assert_eq!(labeled.get_bytes(), bytes);
+        assert_eq!(labeled.hash(), tx.hash());
+        assert_eq!(
+            labeled.verify_signature(),
+            Some(PubKeyHash::from_privkey(&key))
+        );
+
+        // Label survives the JSON roundtrip.
+        let json = serde_json::to_string(&labeled).unwrap();
+        let mut deserialized: ZkSyncTx = serde_json::from_str(&json).unwrap();
+        assert_eq!(deserialized.label(), Some("Rent for May"));
+        assert_eq!(deserialized.get_bytes(), bytes);
+        assert!(deserialized.check_correctness());
+
+        // Too long labels are rejected.
+        for (len, valid) in &[(MAX_LABEL_LEN, true), (MAX_LABEL_LEN + 1, false)] {
+            let mut labeled = tx.clone();
+            let label = Some("a".repeat(*len));
+            match &mut labeled {
+                ZkSyncTx::Transfer(tx) => tx.label = label,
+                ZkSyncTx::Withdraw(tx) => tx.label = label,
+                _ => panic!("Unexpected transaction type"),
+            }
+            assert_eq!(labeled.check_correctness(), *valid);
+        }
+    }
+}
+
+#[test]
+fn test_verify_hex() {
+    let (key, messages) = gen_pk_and_msg();
+    let signer_hash = PubKeyHash::from_privkey(&key);
+    let variant = SignatureType::MusigRescue;
+    for msg in &messages {
+        let signature = TxSignature::sign_musig(&key, msg);
+        let msg_hex = hex::encode(msg);
+
+        for hex_str in &[
+            msg_hex.clone(),
+            format!("0x{}", msg_hex),
+            format!("0X{}", msg_hex.to_uppercase()),
+        ] {
+            let signer = signature.verify_hex(hex_str, variant).unwrap();
+            assert_eq!(
+                signer.map(|pub_key| PubKeyHash::from_pubkey(&pub_key)),
+                Some(signer_hash.clone())
+            );
+        }
+
+        // Correct hex of another message.
+        assert!(signature
+            .verify_hex(&format!("0x{}ff", msg_hex), variant)
+            .unwrap()
+            .is_none());
+    }
+
+    // Signature of the prefix doesn't verify the longer message.
+    let msg = vec![0xab; MAX_SIGNED_MESSAGE_LEN];
+    let signature = TxSignature::sign_musig(&key, &msg);
+    assert!(signature
+        .verify_hex(&hex::encode(&msg), variant)
+        .unwrap()
+        .is_some());
+    assert!(signature
+        .verify_hex(&format!("{}00", hex::encode(&msg)), variant)
+        .unwrap()
+        .is_none());
+
+    let signature = TxSignature::sign_musig(&key, &messages[1]);
+    for &invalid in &["0x123", "0xzz", "hello world", "0x0x00"] {
+        assert!(
+            signature.verify_hex(invalid, variant).is_err(),
+            "{}",
+            invalid
+        );
+    }
+}
+
+#[test]
+fn test_signature_type_from_str() {
+    for &variant in &SignatureType::ALL {
+        let name = serde_json::to_value(variant).unwrap();
+        assert_eq!(
+            name.as_str().unwrap().parse::<SignatureType>().unwrap(),
+            variant
+        );
+    }
+    assert!("musig_rescue".parse::<SignatureType>().is_err());
+    assert!("".parse::<SignatureType>().is_err());
+}
+
+#[test]
+fn test_multi_transfer_canonical_outputs() {
+    let key = gen_pk_and_msg().0;
+    let from = Address::from([1u8; 20]);
+    let output = |to: u8, token, amount: u64| {
+        TransferOutput::new(
+            Address::from([to; 20]),
+            token,
+            Amount::new(amount.into()).unwrap(),
+        )
+    };
+    let outputs = vec![
+        output(3, 0, 100),
+        output(2, 1, 100),
+        output(2, 0, 200),
+        output(2, 0, 100),
+    ];
+    let mut reversed = outputs.clone();
+    reversed.reverse();
+
+    let tx = MultiTransfer::new_signed(0, from, outputs, 0, 10u64.into(), 0, &key).unwrap();
+    let reversed_tx =
+        MultiTransfer::new_signed(0, from, reversed, 0, 10u64.into(), 0, &key).unwrap();
+    assert_eq!(tx.get_bytes(), reversed_tx.get_bytes());
+    assert_eq!(
+        tx.signature.signature.serialize_packed().unwrap(),
+        reversed_tx.signature.signature.serialize_packed().unwrap()
+    );
+    assert_eq!(
+        tx.outputs,
+        vec![
+            output(2, 0, 100),
+            output(2, 0, 200),
+            output(2, 1, 100),
+            output(3, 0, 100),
+        ]
+    );
+    assert!(tx.has_canonical_outputs());
+
+    // Transaction with the outputs out of the canonical order is rejected,
+    // even if the signature is correct.
+    let mut shuffled = tx.clone();
+    shuffled.outputs.swap(0, 3);
+    assert!(!shuffled.has_canonical_outputs());
+    shuffled.signature = TxSignature::sign_musig(&key, &shuffled.get_bytes());
+    assert!(!shuffled.check_correctness());
+
+    let json = serde_json::to_value(&ZkSyncTx::from(shuffled)).unwrap();
+    let mut deserialized: ZkSyncTx = serde_json::from_value(json).unwrap();
+    assert!(!deserialized.check_correctness());
+}
+
+#[test]
+fn test_cross_token_fee() {
+    let from = Address::from([1u8; 20]);
+    let to = Address::from([2u8; 20]);
+    let output = |token| TransferOutput::new(to, token, Amount::new(100u64.into()).unwrap());
+    let multi_transfer =
+        |outputs, fee_token| MultiTransfer::new(0, from, outputs, fee_token, 10u64.into(), 0, None);
+    assert!(!multi_transfer(vec![output(1)], 1).is_cross_token_fee());
+    assert!(!multi_transfer(vec![output(1), output(1)], 1).is_cross_token_fee());
+    assert!(multi_transfer(vec![output(1)], 0).is_cross_token_fee());
+    assert!(multi_transfer(vec![output(0), output(1)], 1).is_cross_token_fee());
+}
+
+#[test]
+fn test_verify_with_policy() {
+    let mut txs = generate_test_transactions(200, 1);
+    // Transaction with an incorrect signature.
+    txs[0] = match &txs[0] {
+        ZkSyncTx::Transfer(tx) => Transfer::new(
+            tx.account_id,
+            tx.from,
+            tx.to,
+            tx.token,
+            tx.amount.clone(),
+            tx.fee.clone(),
+            tx.nonce,
+            Some(TxSignature::default()),
+        )
+        .into(),
+        _ => panic!("Transfers are expected"),
+    };
+
+    // Verified and skipped regardless of the transaction.
+    assert_eq!(
+        txs[0].verify_with_policy(VerificationPolicy::Always),
+        Some(false)
+    );
+    assert_eq!(
+        txs[1].verify_with_policy(VerificationPolicy::Always),
+        Some(true)
+    );
+    assert_eq!(txs[0].verify_with_policy(VerificationPolicy::Never), None);
+    assert_eq!(txs[1].verify_with_policy(VerificationPolicy::Never), None);
+    let all = VerificationPolicy::Sampled { rate: 1.0, seed: 1 };
+    let none = VerificationPolicy::Sampled { rate: 0.0, seed: 1 };
+    for tx in &txs[1..] {
+        assert_eq!(tx.verify_with_policy(all), Some(true));
+        assert_eq!(tx.verify_with_policy(none), None);
+    }
+
+    // Sampling is deterministic for the same seed.
+    let sample = |seed| {
+        let policy = VerificationPolicy::Sampled { rate: 0.5, seed };
+        txs[1..]
+            .iter()
+            .map(|tx| tx.verify_with_policy(policy))
+            .collect::<Vec<_>>()
+    };
+    let first = sample(42);
+    assert_eq!(first, sample(42));
+    assert_ne!(first, sample(43));
+    assert!(first.iter().all(|result| *result != Some(false)));
+    let verified = first.iter().filter(|result| result.is_some()).count();
+    assert!(verified > 50 && verified < 150, "{}", verified);
+}
+
+#[test]
+fn test_referenced_tokens() {
+    let from = Address::from([1u8; 20]);
+    let to = Address::from([2u8; 20]);
+    let output = |token| TransferOutput::new(to, token, Amount::new(100u64.into()).unwrap());
+    let txs: Vec<ZkSyncTx> = vec![
+        Transfer::new(0, from, to, 1, 100u64.into(), 1u64.into(), 0, None).into(),
+        Withdraw::new(0, from, to, 2, 100u64.into(), 1u64.into(), 1, None).into(),
+        Transfer::new(0, from, to, 1, 100u64.into(), 1u64.into(), 2, None).into(),
+        Close {
+            account: from,
+            nonce: 3,
+            signature: TxSignature::default(),
+        }
+        .into(),
+        ChangePubKey::new(
+            0,
+            from,
+            PubKeyHash::default(),
+            3,
+            1u64.into(),
+            4,
+            None,
+            None,
+        )
+        .into(),
+        ForcedExit::new(0, to, 4, 1u64.into(), 5, None).into(),
+        MultiTransfer::new(0, from, vec![output(5), output(1)], 6, 1u64.into(), 6, None).into(),
+    ];
+
+    let expected: std::collections::HashSet<TokenId> = (1..=6).collect();
+    assert_eq!(referenced_tokens(&txs), expected);
+    assert_eq!(referenced_tokens(&txs[..1]), vec![1].into_iter().collect());
+    assert!(referenced_tokens(&txs[3..4]).is_empty());
+    assert!(referenced_tokens(&[]).is_empty());
+}
+
+#[test]
+fn test_bytes_endianness() {
+    use zksync_crypto::franklin_crypto::{
+        alt_babyjubjub::fs::FsRepr,
+        bellman::pairing::ff::{PrimeField, PrimeFieldRepr},
+    };
+
+    let field = |layout: &[(&'static str, std::ops::Range<usize>)], name: &str| {
+        layout
+            .iter()
+            .find(|(field, _)| *field == name)
+            .unwrap_or_else(|| panic!("no field {}", name))
+            .1
+            .clone()
+    };
+
+    let from = Address::from([0x11; 20]);
+    let to = Address::from([0x22; 20]);
+    let amount = BigUint::from(1_000u64);
+    let fee = BigUint::from(10u64);
+    let transfer = Transfer::new(
+        0x0102_0304,
+        from,
+        to,
+        0x0506,
+        amount.clone(),
+        fee.clone(),
+        0x0708_090a,
+        None,
+    );
+    let bytes = transfer.get_bytes();
+    let layout = Transfer::field_layout();
+    assert_eq!(bytes.len(), layout.last().unwrap().1.end);
+    assert_eq!(bytes[field(&layout, "type")], [Transfer::TX_TYPE]);
+    assert_eq!(
+        bytes[field(&layout, "account_id")],
+        [0x01, 0x02, 0x03, 0x04]
+    );
+    assert_eq!(bytes[field(&layout, "from")], [0x11; 20]);
+    assert_eq!(bytes[field(&layout, "to")], [0x22; 20]);
+    assert_eq!(bytes[field(&layout, "token")], [0x05, 0x06]);
+    assert_eq!(
+        bytes[field(&layout, "amount")],
+        pack_token_amount(&amount)[..]
+    );
+    assert_eq!(bytes[field(&layout, "fee")], pack_fee_amount(&fee)[..]);
+    assert_eq!(bytes[field(&layout, "nonce")], [0x07, 0x08, 0x09, 0x0a]);
+
+    // Withdraw amount is not packed and encoded as big-endian `u128`.
+    let withdraw = Withdraw::new(
+        0x0102_0304,
+        from,
+        to,
+        0x0506,
+        BigUint::from(0x0102_0304_0506_0708_090a_0b0c_0d0e_0f10u128),
+        fee,
+        0x0708_090a,
+        None,
+    );
+    let bytes = withdraw.get_bytes();
+    let layout = Withdraw::field_layout();
+    assert_eq!(bytes.len(), layout.last().unwrap().1.end);
+    assert_eq!(
+        bytes[field(&layout, "amount")],
+        [1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16]
+    );
+    assert_eq!(bytes[field(&layout, "nonce")], [0x07, 0x08, 0x09, 0x0a]);
+
+    // Layouts of all the transaction types match the explained bytes.
+    let layouts = vec![
+        Transfer::field_layout(),
+        Withdraw::field_layout(),
+        Close::field_layout(),
+        ChangePubKey::field_layout(),
+        ForcedExit::field_layout(),
+        MultiTransfer::field_layout(),
+    ];
+    for (tx, layout) in gen_signed_txs().iter().zip(layouts) {
+        let explained: Vec<_> = explain_bytes(&tx.get_bytes())
+            .unwrap()
+            .fields
+            .into_iter()
+            .take(layout.len())
+            .map(|field| (field.name, field.range))
+            .collect();
+        let layout: Vec<_> = layout
+            .into_iter()
+            .map(|(name, range)| (name.to_owned(), range))
+            .collect();
+        assert_eq!(explained, layout);
+    }
+
+    // The scalar `s` of the packed signature is encoded as little-endian,
+    // unlike the transaction fields.
+    let key = gen_pk_and_msg().0;
+    let signature = TxSignature::sign_musig(&key, &transfer.get_bytes()).signature;
+    let packed = signature.serialize_packed().unwrap();
+    let mut s_le = Vec::new();
+    signature.0.s.into_repr().write_le(&mut s_le).unwrap();
+    assert_eq!(packed[32..], s_le[..]);
+
+    let mut s_repr = FsRepr::default();
+    s_repr.read_be(&packed[32..]).unwrap();
+    assert_ne!(s_repr, signature.0.s.into_repr());
+}
+
+#[test]
+fn test_amount_base_units() {
+    let txs = gen_signed_txs();
+    for tx in &txs[..2] {
+        let decimal = serde_json::to_value(tx).unwrap();
+        let amount = decimal["amount"].as_str().unwrap().to_owned();
+        assert!(decimal.get("amountBaseUnits").is_none());
+
+        // Base units and the decimal amount with the trailing zeros produce
+        // the same signed transaction.
+        let mut base_units = decimal.clone();
+        base_units.as_object_mut().unwrap().remove("amount");
+        base_units["amountBaseUnits"] = amount.clone().into();
+
+        let mut with_fraction = decimal.clone();
+        with_fraction["amount"] = format!("{}.00", amount).into();
+
+        for value in vec![decimal.clone(), base_units.clone(), with_fraction] {
+            let restored: ZkSyncTx = serde_json::from_value(value).unwrap();
+            assert_eq!(restored.get_bytes(), tx.get_bytes());
+            assert!(restored.verify_signature().is_some());
+            assert_eq!(serde_json::to_value(&restored).unwrap(), decimal);
+        }
+
+        // Both forms at once are rejected.
+        let mut both = base_units.clone();
+        both["amount"] = amount.clone().into();
+        assert!(serde_json::from_value::<ZkSyncTx>(both).is_err());
+
+        // Neither of the forms.
+        let mut missing = base_units.clone();
+        missing.as_object_mut().unwrap().remove("amountBaseUnits");
+        assert!(serde_json::from_value::<ZkSyncTx>(missing).is_err());
+
+        // Values which are not non-negative integers are rejected.
+        for invalid in &["", "1.5", "-1", "0x10", "ten"] {
+            let mut value = base_units.clone();
+            value["amountBaseUnits"] = (*invalid).into();
+            assert!(
+                serde_json::from_value::<ZkSyncTx>(value).is_err(),
+                "{}",
+                invalid
+            );
+        }
+    }
+}
+
+#[test]
+fn test_with_nonce() {
+    let key = gen_pk_and_msg().0;
+    for tx in gen_signed_txs() {
+        assert!(tx.is_signed());
+        assert!(tx.verify_signature().is_some());
+
+        let nonce = tx.nonce().wrapping_add(1);
+        let bumped = tx.with_nonce(nonce);
+        assert_eq!(bumped.nonce(), nonce);
+        assert_eq!(bumped.tx_type(), tx.tx_type());
+        assert!(!bumped.is_signed());
+        assert!(bumped.verify_signature().is_none());
+        assert!(bumped.operator_signature().is_none());
+        if let ZkSyncTx::ChangePubKey(tx) = &bumped {
+            assert!(tx.eth_signature.is_none());
+        }
+
+        // The original transaction is left intact.
+        assert!(tx.is_signed());
+        assert_ne!(tx.nonce(), nonce);
+
+        // Signing the result again makes it valid.
+        let (resigned, valid) = bumped.sign_and_check(&key);
+        assert!(valid);
+        assert!(resigned.is_signed());
+        assert_eq!(resigned.nonce(), nonce);
+    }
+}
+
+#[test]
+fn test_copy_cached_signer() {
+    for tx in gen_signed_txs() {
+        let mut checked = tx.clone();
+        checked.check_correctness();
+
+        let mut copy = tx.clone();
+        assert!(copy.copy_cached_signer(&checked));
+        assert_eq!(copy.verify_signature(), tx.verify_signature());
+
+        // Results of the checks made for the other signed data or signature are not copied.
+        let mut bumped = tx.with_nonce(tx.nonce().wrapping_add(1));
+        assert!(!bumped.copy_cached_signer(&checked));
+        let mut unsigned = tx.with_nonce(tx.nonce());
+        assert!(!unsigned.copy_cached_signer(&checked));
+    }
+
+    // Results are never copied between the different transaction types.
+    let txs = gen_signed_txs();
+    for (i, tx) in txs.iter().enumerate() {
+        let mut checked = tx.clone();
+        checked.check_correctness();
+        for (j, other) in txs.iter().enumerate() {
+            if tx.tx_type() != other.tx_type() {
+                assert!(!other.clone().copy_cached_signer(&checked), "{} {}", i, j);
+            }
+        }
+    }
+}
@@ -4,12 +4,13 @@ use crate::{
     },
     AccountId, Nonce, TokenId,
 };
-use num::BigUint;
+use num::{BigUint, ToPrimitive, Zero};
 
 use crate::account::PubKeyHash;
 use crate::Engine;
 use anyhow::bail;
 use serde::{Deserialize, Serialize};
+use thiserror::Error;
 use zksync_basic_types::Address;
 use zksync_crypto::franklin_crypto::eddsa::PrivateKey;
 use zksync_crypto::params::{max_account_id, max_token_id};
@@ -18,6 +19,38 @@ use zksync_utils::BigUintSerdeAsRadix10Str;
 
 use super::{TxSignature, VerifiedSignatureCache};
 
+/// Number of basis points in the whole amount.
+const BASIS_POINTS: u32 = 10_000;
+
+/// Maximum length of the transaction `label` in bytes.
+pub const MAX_LABEL_LEN: usize = 64;
+
+/// Error returned when the fee of the transaction is incorrect relative to its amount.
+#[derive(Debug, Clone, PartialEq, Error)]
+pub enum FeeError {
+    #[error("Fee {fee} exceeds the transferred amount {amount}")]
+    FeeExceedsAmount { amount: BigUint, fee: BigUint },
+    #[error("Fee cannot be expressed relative to the zero amount")]
+    ZeroAmount,
+    #[error("Fee of {fee_bps} basis points exceeds the cap of {max_bps} basis points")]
+    FeeCapExceeded { fee_bps: u32, max_bps: u32 },
+    #[error("Fee {fee} is below the minimum fee {min_fee} of the transaction type")]
+    FeeBelowMinimum { fee: BigUint, min_fee: BigUint },
+}
+
+/// Direction of the funds movement relative to an account, e.g. for the account activity views.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Direction {
+    /// Account receives the funds.
+    Incoming,
+    /// Account sends the funds.
+    Outgoing,
+    /// Account sends the funds to itself.
+    SelfTransfer,
+    /// Account is neither the sender nor the recipient.
+    Unrelated,
+}
+
 /// `Transfer` transaction performs a move of funds from one zkSync account to another.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -31,7 +64,12 @@ pub struct Transfer {
     /// Type of token for transfer. Also represents the token in which fee will be paid.
     pub token: TokenId,
     /// Amount of funds to transfer.
-    #[serde(with = "BigUintSerdeAsRadix10Str")]
+    ///
+    /// Can also be supplied as the `amountBaseUnits` field, e.g. by the clients operating
+    /// with the integer amounts of the token base units. Specifying both fields is an error.
+    ///
+    /// Not guaranteed to be packable, see `check_correctness` and the `Amount` type.
+    #[serde(with = "BigUintSerdeAsRadix10Str", alias = "amountBaseUnits")]
     pub amount: BigUint,
     /// Fee for the transaction.
     #[serde(with = "BigUintSerdeAsRadix10Str")]
@@ -40,8 +78,36 @@ pub struct Transfer {
     pub nonce: Nonce,
     /// Transaction zkSync signature.
     pub signature: TxSignature,
+    /// Optional client-chosen key used to detect resubmissions of the same transaction.
+    ///
+    /// Not covered by the transaction signature and not included into the wire encoding:
+    /// the signed bytes must match the fixed layout of the circuit. Thus anyone relaying
+    /// the transaction can change or strip the key, see `ZkSyncTx::is_duplicate`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub idempotency_key: Option<[u8; 16]>,
+    /// Optional account the fee is paid to instead of the operator.
+    ///
+    /// Not supported by the state transition and the circuit yet, so it's not covered by
+    /// the transaction signature, not included into the wire encoding, and transactions
+    /// setting it are rejected by the API.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fee_recipient: Option<Address>,
+    /// Optional operator signature of the same bytes as the transaction signature,
+    /// for transactions requiring the operator approval.
+    ///
+    /// Not covered by the transaction signature and not included into the wire encoding.
+    /// Has the fixed size of `TxSignature`, values of any other length are rejected on
+    /// deserialization.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub operator_signature: Option<TxSignature>,
+    /// Optional human-readable label chosen by the client, e.g. to be shown in the UI.
+    /// At most `MAX_LABEL_LEN` bytes long.
+    ///
+    /// Not covered by the transaction signature and not included into the wire encoding.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
     #[serde(skip)]
-    cached_signer: VerifiedSignatureCache,
+    pub(super) cached_signer: VerifiedSignatureCache,
 }
 
 impl Transfer {
@@ -72,6 +138,10 @@ impl Transfer {
             fee,
             nonce,
             signature: signature.clone().unwrap_or_default(),
+            idempotency_key: None,
+            fee_recipient: None,
+            operator_signature: None,
+            label: None,
             cached_signer: VerifiedSignatureCache::NotCached,
         };
         if signature.is_some() {
@@ -80,6 +150,31 @@ impl Transfer {
         tx
     }
 
+    /// Same as `Transfer::new`, but amount and fee are provided as integers in the
+    /// token base units.
+    #[allow(clippy::too_many_arguments)]
+    pub fn with_base_units(
+        account_id: AccountId,
+        from: Address,
+        to: Address,
+        token: TokenId,
+        amount: u128,
+        fee: u64,
+        nonce: Nonce,
+        signature: Option<TxSignature>,
+    ) -> Self {
+        Self::new(
+            account_id,
+            from,
+            to,
+            token,
+            BigUint::from(amount),
+            BigUint::from(fee),
+            nonce,
+            signature,
+        )
+    }
+
     /// Creates a signed transaction using private key and
     /// checks for the transaction correcteness.
     #[allow(clippy::too_many_arguments)]
@@ -122,6 +217,7 @@ impl Transfer {
     /// - `amount` field must represent a packable value.
     /// - `fee` field must represent a packable value.
     /// - transfer recipient must not be `Adddress::zero()`.
+    /// - `label` field (if any) must not be longer than `MAX_LABEL_LEN` bytes.
     /// - zkSync signature must correspond to the PubKeyHash of the account.
     pub fn check_correctness(&mut self) -> bool {
         let mut valid = self.amount <= BigUint::from(u128::max_value())
@@ -130,7 +226,8 @@ impl Transfer {
             && is_fee_amount_packable(&self.fee)
             && self.account_id <= max_account_id()
             && self.token <= max_token_id()
-            && self.to != Address::zero();
+            && self.to != Address::zero()
+            && is_label_valid(self.label.as_deref());
         if valid {
             let signer = self.verify_signature();
             valid = valid && signer.is_some();
@@ -139,6 +236,61 @@ impl Transfer {
         valid
     }
 
+    /// Classifies the transfer relative to the `account`.
+    pub fn direction(&self, account: &Address) -> Direction {
+        match (&self.from == account, &self.to == account) {
+            (true, true) => Direction::SelfTransfer,
+            (true, false) => Direction::Outgoing,
+            (false, true) => Direction::Incoming,
+            (false, false) => Direction::Unrelated,
+        }
+    }
+
+    /// Returns `true` if the transaction amount is zero.
+    ///
+    /// Such transactions are valid, but usually indicate either a client mistake or spam.
+    pub fn is_zero_amount(&self) -> bool {
+        self.amount.is_zero()
+    }
+
+    /// Returns the transferred amount minus the fee.
+    ///
+    /// The fee is paid in the same token as the transfer itself, so the result is
+    /// the net amount the sender gives away. Returns an error if the fee exceeds the amount.
+    pub fn net_after_fee(&self) -> Result<BigUint, FeeError> {
+        if self.fee > self.amount {
+            return Err(FeeError::FeeExceedsAmount {
+                amount: self.amount.clone(),
+                fee: self.fee.clone(),
+            });
+        }
+
+        Ok(&self.amount - &self.fee)
+    }
+
+    /// Returns the fee relative to the transferred amount in basis points (1/100th of a percent),
+    /// rounded down. Values which don't fit into `u32` are saturated.
+    ///
+    /// Returns an error for the zero amount transfers.
+    pub fn fee_bps(&self) -> Result<u32, FeeError> {
+        if self.amount.is_zero() {
+            return Err(FeeError::ZeroAmount);
+        }
+
+        let fee_bps = &self.fee * BigUint::from(BASIS_POINTS) / &self.amount;
+        Ok(fee_bps.to_u32().unwrap_or(u32::max_value()))
+    }
+
+    /// Checks that the fee doesn't exceed `max_bps` basis points of the transferred amount.
+    pub fn check_fee_cap(&self, max_bps: u32) -> Result<(), FeeError> {
+        let fee_bps = self.fee_bps()?;
+        if fee_bps > max_bps {
+            return Err(FeeError::FeeCapExceeded { fee_bps, max_bps });
+        }
+
+        Ok(())
+    }
+
     /// Restores the `PubKeyHash` from the transaction signature.
     pub fn verify_signature(&self) -> Option<PubKeyHash> {
         if let VerifiedSignatureCache::Cached(cached_signer) = &self.cached_signer {
@@ -167,3 +319,8 @@ impl Transfer {
         )
     }
 }
+
+/// Checks that the optional transaction label is not longer than `MAX_LABEL_LEN` bytes.
+pub(super) fn is_label_valid(label: Option<&str>) -> bool {
+    label.map_or(true, |label| label.len() <= MAX_LABEL_LEN)
+}
@@ -0,0 +1,323 @@
+//! Binary wire encoding of zkSync transactions.
+//!
+//! Encoded transaction has the following layout:
+//!
+//! - 2-byte big-endian length of the transaction bytes;
+//! - transaction bytes as returned by `ZkSyncTx::get_bytes`;
+//! - 96-byte zkSync signature as returned by `TxSignature::to_bytes`;
+//! - for `ChangePubKey` only: 1-byte flag denoting whether the Ethereum signature is present,
+//!   followed by the 65-byte packed Ethereum signature if it is.
+//!
+//! Unlike JSON, this encoding is compact and can be used in the binary-native protocols.
+
+use std::convert::TryInto;
+use std::io::{self, Read, Write};
+
+use num::BigUint;
+use thiserror::Error;
+use zksync_basic_types::Address;
+use zksync_crypto::params::FR_ADDRESS_LEN;
+
+use crate::{
+    helpers::{unpack_fee_amount, unpack_token_amount},
+    PubKeyHash,
+};
+
+use super::{
+    Amount, ChangePubKey, Close, ForcedExit, MultiTransfer, PackedEthSignature, Transfer,
+    TransferOutput, TxSignature, Withdraw, ZkSyncTx,
+};
+
+/// Size of the zkSync signature in the wire encoding.
+const SIGNATURE_LEN: usize = 96;
+/// Size of the packed Ethereum signature in the wire encoding.
+const ETH_SIGNATURE_LEN: usize = 65;
+/// Maximum size of the framed transaction accepted by `read_framed`.
+pub const MAX_FRAME_LEN: usize = 8 * 1024;
+
+/// Error returned when the transaction cannot be decoded from its wire encoding.
+#[derive(Debug, Clone, PartialEq, Error)]
+pub enum WireError {
+    #[error("Unexpected end of input: {needed} more bytes required")]
+    UnexpectedEnd { needed: usize },
+    #[error("Unknown transaction type {0}")]
+    UnknownTxType(u8),
+    #[error("Incorrect length {len} of the transaction bytes with type {tx_type}")]
+    InvalidLength { tx_type: u8, len: usize },
+    #[error("Incorrect packed amount of the field {0}")]
+    InvalidAmount(&'static str),
+    #[error("Incorrect signature: {0}")]
+    InvalidSignature(String),
+    #[error("Unexpected {0} trailing bytes")]
+    TrailingBytes(usize),
+    #[error("Number of outputs {0} exceeds the limit")]
+    TooManyOutputs(usize),
+}
+
+/// Helper reading the fields of the transaction bytes one by one.
+pub(super) struct FieldReader<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> FieldReader<'a> {
+    pub(super) fn new(bytes: &'a [u8]) -> Self {
+        Self { bytes }
+    }
+
+    pub(super) fn take(&mut self, len: usize) -> Result<&'a [u8], WireError> {
+        if self.bytes.len() < len {
+            return Err(WireError::UnexpectedEnd {
+                needed: len - self.bytes.len(),
+            });
+        }
+        let (field, rest) = self.bytes.split_at(len);
+        self.bytes = rest;
+        Ok(field)
+    }
+
+    pub(super) fn remaining(&self) -> usize {
+        self.bytes.len()
+    }
+
+    pub(super) fn u8(&mut self) -> Result<u8, WireError> {
+        Ok(self.take(1)?[0])
+    }
+
+    pub(super) fn u16(&mut self) -> Result<u16, WireError> {
+        Ok(u16::from_be_bytes(self.take(2)?.try_into().unwrap()))
+    }
+
+    pub(super) fn u32(&mut self) -> Result<u32, WireError> {
+        Ok(u32::from_be_bytes(self.take(4)?.try_into().unwrap()))
+    }
+
+    pub(super) fn address(&mut self) -> Result<Address, WireError> {
+        Ok(Address::from_slice(self.take(20)?))
+    }
+
+    pub(super) fn token_amount(&mut self, field: &'static str) -> Result<BigUint, WireError> {
+        unpack_token_amount(self.take(5)?).ok_or(WireError::InvalidAmount(field))
+    }
+
+    pub(super) fn fee_amount(&mut self) -> Result<BigUint, WireError> {
+        unpack_fee_amount(self.take(2)?).ok_or(WireError::InvalidAmount("fee"))
+    }
+}
+
+/// Decodes the transaction from the bytes returned by `ZkSyncTx::get_bytes`.
+/// The signature is left empty.
+fn decode_tx_bytes(bytes: &[u8]) -> Result<ZkSyncTx, WireError> {
+    let mut reader = FieldReader::new(bytes);
+    let tx_type = reader.u8()?;
+    if !ZkSyncTx::supported_tx_types().contains(&tx_type) {
+        return Err(WireError::UnknownTxType(tx_type));
+    }
+
+    let tx: ZkSyncTx = match tx_type {
+        Transfer::TX_TYPE => {
+            let mut tx = Transfer::new(
+                reader.u32()?,
+                reader.address()?,
+                reader.address()?,
+                reader.u16()?,
+                reader.token_amount("amount")?,
+                reader.fee_amount()?,
+                reader.u32()?,
+                None,
+            );
+            tx.into()
+        }
+        Withdraw::TX_TYPE => {
+            let account_id = reader.u32()?;
+            let from = reader.address()?;
+            let to = reader.address()?;
+            let token = reader.u16()?;
+            let amount = u128::from_be_bytes(reader.take(16)?.try_into().unwrap());
+            let mut tx = Withdraw::new(
+                account_id,
+                from,
+                to,
+                token,
+                amount.into(),
+                reader.fee_amount()?,
+                reader.u32()?,
+                None,
+            );
+            tx.into()
+        }
+        Close::TX_TYPE => Close {
+            account: reader.address()?,
+            nonce: reader.u32()?,
+            signature: TxSignature::default(),
+        }
+        .into(),
+        ChangePubKey::TX_TYPE => ChangePubKey::new(
+            reader.u32()?,
+            reader.address()?,
+            PubKeyHash::from_bytes(reader.take(FR_ADDRESS_LEN)?).unwrap(),
+            reader.u16()?,
+            reader.fee_amount()?,
+            reader.u32()?,
+            None,
+            None,
+        )
+        .into(),
+        ForcedExit::TX_TYPE => ForcedExit::new(
+            reader.u32()?,
+            reader.address()?,
+            reader.u16()?,
+            reader.fee_amount()?,
+            reader.u32()?,
+            None,
+        )
+        .into(),
+        MultiTransfer::TX_TYPE => {
+            let account_id = reader.u32()?;
+            let from = reader.address()?;
+            let outputs_count = reader.u8()?;
+            if usize::from(outputs_count) > MultiTransfer::MAX_OUTPUTS {
+                return Err(WireError::TooManyOutputs(outputs_count.into()));
+            }
+            let outputs = (0..outputs_count)
+                .map(|_| {
+                    Ok(TransferOutput::new(
+                        reader.address()?,
+                        reader.u16()?,
+                        Amount::new(reader.token_amount("output amount")?)
+                            .map_err(|_| WireError::InvalidAmount("output amount"))?,
+                    ))
+                })
+                .collect::<Result<_, WireError>>()?;
+            MultiTransfer::new(
+                account_id,
+                from,
+                outputs,
+                reader.u16()?,
+                reader.fee_amount()?,
+                reader.u32()?,
+                None,
+            )
+            .into()
+        }
+        _ => unreachable!("all the supported transaction types are handled"),
+    };
+
+    if reader.remaining() != 0 {
+        return Err(WireError::InvalidLength {
+            tx_type,
+            len: bytes.len(),
+        });
+    }
+    Ok(tx)
+}
+
+/// Attaches the decoded signatures to the transaction. Since the transaction
+/// was created without signature, its verification result is not cached yet.
+fn attach_signatures(
+    tx: &mut ZkSyncTx,
+    signature: TxSignature,
+    eth_signature: Option<PackedEthSignature>,
+) {
+    match tx {
+        ZkSyncTx::Transfer(tx) => tx.signature = signature,
+        ZkSyncTx::Withdraw(tx) => tx.signature = signature,
+        ZkSyncTx::Close(tx) => tx.signature = signature,
+        ZkSyncTx::ChangePubKey(tx) => {
+            tx.signature = signature;
+            tx.eth_signature = eth_signature;
+        }
+        ZkSyncTx::ForcedExit(tx) => tx.signature = signature,
+        ZkSyncTx::MultiTransfer(tx) => tx.signature = signature,
+    }
+}
+
+impl ZkSyncTx {
+    /// Encodes the signed transaction into the binary wire format (see the `wire` module docs).
+    pub fn to_wire_bytes(&self) -> Vec<u8> {
+        let tx_bytes = self.get_bytes();
+
+        let mut out = Vec::with_capacity(2 + tx_bytes.len() + SIGNATURE_LEN);
+        // Transaction bytes length is limited by the transaction layout, so it always fits.
+        out.extend_from_slice(&(tx_bytes.len() as u16).to_be_bytes());
+        out.extend_from_slice(&tx_bytes);
+        out.extend_from_slice(&self.signature().to_bytes());
+        if let ZkSyncTx::ChangePubKey(tx) = self {
+            match &tx.eth_signature {
+                Some(eth_signature) => {
+                    out.push(1);
+                    out.extend_from_slice(&eth_signature.serialize_packed());
+                }
+                None => out.push(0),
+            }
+        }
+        out
+    }
+
+    /// Decodes the transaction from the beginning of its binary wire format.
+    ///
+    /// Trailing bytes after the encoded transaction are ignored. Returns the decoded
+    /// transaction along with the number of consumed bytes.
+    pub fn from_wire_bytes(bytes: &[u8]) -> Result<(Self, usize), WireError> {
+        let mut reader = FieldReader::new(bytes);
+
+        let tx_len = reader.u16()? as usize;
+        let mut tx = decode_tx_bytes(reader.take(tx_len)?)?;
+
+        let signature = TxSignature::from_bytes(reader.take(SIGNATURE_LEN)?.try_into().unwrap())
+            .map_err(|err| WireError::InvalidSignature(err.to_string()))?;
+        let eth_signature = if let ZkSyncTx::ChangePubKey(_) = tx {
+            match reader.u8()? {
+                0 => None,
+                _ => Some(
+                    PackedEthSignature::deserialize_packed(reader.take(ETH_SIGNATURE_LEN)?)
+                        .map_err(|err| WireError::InvalidSignature(err.to_string()))?,
+                ),
+            }
+        } else {
+            None
+        };
+        attach_signatures(&mut tx, signature, eth_signature);
+
+        Ok((tx, bytes.len() - reader.remaining()))
+    }
+
+    /// Decodes the transaction from its binary wire format.
+    ///
+    /// Unlike `from_wire_bytes`, `bytes` must contain exactly one encoded transaction,
+    /// trailing bytes result in the `WireError::TrailingBytes` error.
+    pub fn from_wire_bytes_strict(bytes: &[u8]) -> Result<Self, WireError> {
+        let (tx, consumed) = Self::from_wire_bytes(bytes)?;
+        if consumed != bytes.len() {
+            return Err(WireError::TrailingBytes(bytes.len() - consumed));
+        }
+        Ok(tx)
+    }
+}
+
+/// Reads a single framed transaction: 4-byte big-endian length of the wire encoding
+/// followed by the encoding itself (see `ZkSyncTx::to_wire_bytes`).
+///
+/// Frames longer than `MAX_FRAME_LEN` are rejected.
+pub fn read_framed<R: Read>(r: &mut R) -> io::Result<ZkSyncTx> {
+    let mut len = [0u8; 4];
+    r.read_exact(&mut len)?;
+    let len = u32::from_be_bytes(len) as usize;
+    if len > MAX_FRAME_LEN {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("Frame length {} exceeds the limit {}", len, MAX_FRAME_LEN),
+        ));
+    }
+
+    let mut bytes = vec![0u8; len];
+    r.read_exact(&mut bytes)?;
+    ZkSyncTx::from_wire_bytes_strict(&bytes)
+        .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))
+}
+
+/// Writes a single framed transaction, counterpart of `read_framed`.
+pub fn write_framed<W: Write>(w: &mut W, tx: &ZkSyncTx) -> io::Result<()> {
+    let bytes = tx.to_wire_bytes();
+    w.write_all(&(bytes.len() as u32).to_be_bytes())?;
+    w.write_all(&bytes)
+}
@@ -2,19 +2,27 @@ use crate::{
     helpers::{is_fee_amount_packable, pack_fee_amount},
     AccountId, Nonce, TokenId,
 };
-use num::{BigUint, ToPrimitive};
+use num::{BigUint, ToPrimitive, Zero};
 
 use crate::account::PubKeyHash;
 use crate::Engine;
 use anyhow::bail;
 use serde::{Deserialize, Serialize};
+use thiserror::Error;
 use zksync_basic_types::Address;
 use zksync_crypto::franklin_crypto::eddsa::PrivateKey;
 use zksync_crypto::params::{max_account_id, max_token_id};
 use zksync_utils::format_units;
 use zksync_utils::BigUintSerdeAsRadix10Str;
 
-use super::{TxSignature, VerifiedSignatureCache};
+use super::{transfer::is_label_valid, Direction, TxSignature, VerifiedSignatureCache};
+
+/// Error returned when the withdrawal is correct, but most likely is a client mistake.
+#[derive(Debug, Clone, PartialEq, Error)]
+pub enum WithdrawError {
+    #[error("Withdrawal to the zero address burns the funds")]
+    ZeroAddress,
+}
 
 /// `Withdraw` transaction performs a withdrawal of funds from zkSync account to L1 account.
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -29,7 +37,12 @@ pub struct Withdraw {
     /// Type of token for withdrawal. Also represents the token in which fee will be paid.
     pub token: TokenId,
     /// Amount of funds to withdraw.
-    #[serde(with = "BigUintSerdeAsRadix10Str")]
+    ///
+    /// Can also be supplied as the `amountBaseUnits` field, e.g. by the clients operating
+    /// with the integer amounts of the token base units. Specifying both fields is an error.
+    ///
+    /// Withdrawal amounts are not packed, so the `Amount` type is not used here.
+    #[serde(with = "BigUintSerdeAsRadix10Str", alias = "amountBaseUnits")]
     pub amount: BigUint,
     /// Fee for the transaction.
     #[serde(with = "BigUintSerdeAsRadix10Str")]
@@ -38,8 +51,36 @@ pub struct Withdraw {
     pub nonce: Nonce,
     /// Transaction zkSync signature.
     pub signature: TxSignature,
+    /// Optional client-chosen key used to detect resubmissions of the same transaction.
+    ///
+    /// Not covered by the transaction signature and not included into the wire encoding:
+    /// the signed bytes must match the fixed layout of the circuit. Thus anyone relaying
+    /// the transaction can change or strip the key, see `ZkSyncTx::is_duplicate`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub idempotency_key: Option<[u8; 16]>,
+    /// Optional account the fee is paid to instead of the operator.
+    ///
+    /// Not supported by the state transition and the circuit yet, so it's not covered by
+    /// the transaction signature, not included into the wire encoding, and transactions
+    /// setting it are rejected by the API.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fee_recipient: Option<Address>,
+    /// Optional operator signature of the same bytes as the transaction signature,
+    /// for transactions requiring the operator approval.
+    ///
+    /// Not covered by the transaction signature and not included into the wire encoding.
+    /// Has the fixed size of `TxSignature`, values of any other length are rejected on
+    /// deserialization.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub operator_signature: Option<TxSignature>,
+    /// Optional human-readable label chosen by the client, e.g. to be shown in the UI.
+    /// At most `MAX_LABEL_LEN` bytes long.
+    ///
+    /// Not covered by the transaction signature and not included into the wire encoding.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
     #[serde(skip)]
-    cached_signer: VerifiedSignatureCache,
+    pub(super) cached_signer: VerifiedSignatureCache,
     /// Optional setting signalizing state keeper to speed up creation
     /// of the block with provided transaction.
     /// This field is only set by the server. Transaction with this field set manually will be
@@ -76,6 +117,10 @@ impl Withdraw {
             fee,
             nonce,
             signature: signature.clone().unwrap_or_default(),
+            idempotency_key: None,
+            fee_recipient: None,
+            operator_signature: None,
+            label: None,
             cached_signer: VerifiedSignatureCache::NotCached,
             fast: false,
         };
@@ -126,12 +171,14 @@ impl Withdraw {
     /// - `token` field must be within supported range.
     /// - `amount` field must represent a packable value.
     /// - `fee` field must represent a packable value.
+    /// - `label` field (if any) must not be longer than `MAX_LABEL_LEN` bytes.
     /// - zkSync signature must correspond to the PubKeyHash of the account.
     pub fn check_correctness(&mut self) -> bool {
         let mut valid = self.amount <= BigUint::from(u128::max_value())
             && is_fee_amount_packable(&self.fee)
             && self.account_id <= max_account_id()
-            && self.token <= max_token_id();
+            && self.token <= max_token_id()
+            && is_label_valid(self.label.as_deref());
 
         if valid {
             let signer = self.verify_signature();
@@ -141,6 +188,38 @@ impl Withdraw {
         valid
     }
 
+    /// Classifies the withdrawal relative to the `account`.
+    ///
+    /// Withdrawn funds leave zkSync, so the withdrawal is always outgoing for its initiator,
+    /// even if the L1 recipient has the same address.
+    pub fn direction(&self, account: &Address) -> Direction {
+        if &self.from == account {
+            Direction::Outgoing
+        } else {
+            Direction::Unrelated
+        }
+    }
+
+    /// Checks the withdrawal for the common client mistakes:
+    ///
+    /// - `to` field must not be the zero address, since the withdrawn funds would be burned.
+    ///   Set `allow_burn` to accept intentional burns.
+    ///
+    /// Unlike `check_correctness`, this check is not enforced by the protocol.
+    pub fn validate(&self, allow_burn: bool) -> Result<(), WithdrawError> {
+        if !allow_burn && self.to == Address::zero() {
+            return Err(WithdrawError::ZeroAddress);
+        }
+        Ok(())
+    }
+
+    /// Returns `true` if the transaction amount is zero.
+    ///
+    /// Such transactions are valid, but usually indicate either a client mistake or spam.
+    pub fn is_zero_amount(&self) -> bool {
+        self.amount.is_zero()
+    }
+
     /// Restores the `PubKeyHash` from the transaction signature.
     pub fn verify_signature(&self) -> Option<PubKeyHash> {
         if let VerifiedSignatureCache::Cached(cached_signer) = &self.cached_signer {
@@ -1,16 +1,61 @@
+use std::collections::HashSet;
+
+use crate::error::Error;
 use crate::Nonce;
 
 use crate::{
-    tx::{ChangePubKey, Close, ForcedExit, Transfer, TxEthSignature, TxHash, Withdraw},
-    CloseOp, ForcedExitOp, TokenLike, TransferOp, TxFeeTypes, WithdrawOp,
+    helpers::{closest_packable_fee_amount, closest_packable_token_amount, unpack_fee_amount},
+    tx::{
+        explain_bytes, AddressCache, ChangePubKey, Close, FeeError, FeeTable, ForcedExit,
+        MultiTransfer, PackedPublicKey, SignatureError, SignatureType, SigningRequest, Transfer,
+        TransferOutput, TxEthSignature, TxHash, TxSignature, WireError, Withdraw,
+    },
+    CloseOp, Engine, ForcedExitOp, PubKeyHash, TokenLike, TransferOp, TxFeeTypes, WithdrawOp,
 };
 use num::BigUint;
 use parity_crypto::digest::sha256;
 
-use super::utils::deserialize_eth_message;
+use super::{utils::deserialize_eth_message, VerifiedSignatureCache};
 use crate::operations::ChangePubKeyOp;
 use serde::{Deserialize, Serialize};
 use zksync_basic_types::Address;
+use zksync_crypto::franklin_crypto::eddsa::PrivateKey;
+use zksync_crypto::params::{
+    ETH_ADDRESS_BIT_WIDTH, FEE_EXPONENT_BIT_WIDTH, FEE_MANTISSA_BIT_WIDTH, NONCE_BIT_WIDTH,
+    SIGNED_CHANGE_PUBKEY_BIT_WIDTH, SIGNED_FORCED_EXIT_BIT_WIDTH, SIGNED_TRANSFER_BIT_WIDTH,
+    SIGNED_WITHDRAW_BIT_WIDTH, TX_TYPE_BIT_WIDTH,
+};
+
+fn check_u128(value: &BigUint, field: &'static str) -> Result<(), Error> {
+    if *value > BigUint::from(u128::max_value()) {
+        return Err(Error::Unpackable {
+            field,
+            value: value.clone(),
+        });
+    }
+    Ok(())
+}
+
+fn normalize_token_amount(amount: &mut BigUint, field: &'static str) -> Result<(), Error> {
+    // Any `u128` value can be represented by the packed token amount.
+    check_u128(amount, field)?;
+    *amount = closest_packable_token_amount(amount);
+    Ok(())
+}
+
+fn normalize_fee(fee: &mut BigUint) -> Result<(), Error> {
+    // Maximum value of the packed fee: all the bits of both mantissa and exponent are set.
+    let max_fee = unpack_fee_amount(&[0xff; (FEE_EXPONENT_BIT_WIDTH + FEE_MANTISSA_BIT_WIDTH) / 8])
+        .expect("maximum fee fits into u128");
+    if *fee > max_fee {
+        return Err(Error::Unpackable {
+            field: "fee",
+            value: fee.clone(),
+        });
+    }
+    *fee = closest_packable_fee_amount(fee);
+    Ok(())
+}
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct EthSignData {
@@ -30,16 +75,89 @@ pub struct SignedZkSyncTx {
     pub eth_sign_data: Option<EthSignData>,
 }
 
+/// Result of the transaction zkSync signature verification along with the recovered signer.
+#[derive(Clone)]
+pub struct VerifyOutcome {
+    /// Whether the signature is correct.
+    pub valid: bool,
+    /// Public key of the signer, `None` if the signature is incorrect.
+    pub pub_key: Option<PackedPublicKey>,
+    /// `PubKeyHash` of the signer, `None` if the signature is incorrect.
+    pub address: Option<PubKeyHash>,
+}
+
+/// Kind of the account receiving the transaction funds.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum AccountType {
+    /// Ethereum address: funds leave zkSync, e.g. withdrawn to L1.
+    L1,
+    /// zkSync account: funds stay within the zkSync network.
+    L2,
+}
+
+/// Policy deciding whether the transaction signature should be verified, e.g. to skip
+/// the verification of the transactions received from the trusted sources.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum VerificationPolicy {
+    /// Every transaction is verified.
+    Always,
+    /// Verification is always skipped.
+    Never,
+    /// Approximately `rate` (from `0.0` to `1.0`) of all the transactions are verified.
+    ///
+    /// The sample is deterministic: it only depends on the transaction hash and the `seed`,
+    /// so with the same seed a transaction is either always verified or always skipped.
+    Sampled { rate: f64, seed: u64 },
+}
+
+impl VerificationPolicy {
+    /// Checks whether the transaction with the `tx_hash` should be verified.
+    pub fn should_verify(&self, tx_hash: &TxHash) -> bool {
+        match *self {
+            VerificationPolicy::Always => true,
+            VerificationPolicy::Never => false,
+            VerificationPolicy::Sampled { rate, seed } => {
+                let digest = sha256(&[&seed.to_be_bytes()[..], tx_hash.as_ref()].concat());
+                let mut sample = [0u8; 8];
+                sample.copy_from_slice(&digest[..8]);
+                // Top 53 bits are converted to `f64` losslessly, giving a value in `[0, 1)`.
+                let sample = (u64::from_be_bytes(sample) >> 11) as f64 / (1u64 << 53) as f64;
+                sample < rate
+            }
+        }
+    }
+}
+
 /// A set of L2 transaction supported by the zkSync network.
+///
+/// Transaction type tag is always serialized as the variant name, but lowercase, snake_case
+/// and camelCase spellings of the tag are accepted during deserialization for compatibility
+/// with the other SDKs.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(tag = "type")]
 pub enum ZkSyncTx {
+    #[serde(alias = "transfer")]
     Transfer(Box<Transfer>),
+    #[serde(alias = "withdraw")]
     Withdraw(Box<Withdraw>),
     #[doc(hidden)]
+    #[serde(alias = "close")]
     Close(Box<Close>),
+    #[serde(
+        alias = "changepubkey",
+        alias = "change_pub_key",
+        alias = "changePubKey"
+    )]
     ChangePubKey(Box<ChangePubKey>),
+    #[serde(alias = "forcedexit", alias = "forced_exit", alias = "forcedExit")]
     ForcedExit(Box<ForcedExit>),
+    /// Not supported by the zkSync circuit yet, so it is rejected during execution.
+    #[serde(
+        alias = "multitransfer",
+        alias = "multi_transfer",
+        alias = "multiTransfer"
+    )]
+    MultiTransfer(Box<MultiTransfer>),
 }
 
 impl From<Transfer> for ZkSyncTx {
@@ -72,6 +190,12 @@ impl From<ForcedExit> for ZkSyncTx {
     }
 }
 
+impl From<MultiTransfer> for ZkSyncTx {
+    fn from(tx: MultiTransfer) -> Self {
+        Self::MultiTransfer(Box::new(tx))
+    }
+}
+
 impl From<ZkSyncTx> for SignedZkSyncTx {
     fn from(tx: ZkSyncTx) -> Self {
         Self {
@@ -90,6 +214,33 @@ impl std::ops::Deref for SignedZkSyncTx {
 }
 
 impl ZkSyncTx {
+    /// Returns the `TX_TYPE` bytes of all the supported transaction types.
+    ///
+    /// Decoders of the raw transaction bytes should use this list to check the
+    /// transaction type byte.
+    pub fn supported_tx_types() -> &'static [u8] {
+        &[
+            Transfer::TX_TYPE,
+            Withdraw::TX_TYPE,
+            Close::TX_TYPE,
+            ChangePubKey::TX_TYPE,
+            ForcedExit::TX_TYPE,
+            MultiTransfer::TX_TYPE,
+        ]
+    }
+
+    /// Returns the `TX_TYPE` byte of the transaction, i.e. the first byte of its encoding.
+    pub fn tx_type(&self) -> u8 {
+        match self {
+            ZkSyncTx::Transfer(_) => Transfer::TX_TYPE,
+            ZkSyncTx::Withdraw(_) => Withdraw::TX_TYPE,
+            ZkSyncTx::Close(_) => Close::TX_TYPE,
+            ZkSyncTx::ChangePubKey(_) => ChangePubKey::TX_TYPE,
+            ZkSyncTx::ForcedExit(_) => ForcedExit::TX_TYPE,
+            ZkSyncTx::MultiTransfer(_) => MultiTransfer::TX_TYPE,
+        }
+    }
+
     /// Returns the hash of the transaction.
     pub fn hash(&self) -> TxHash {
         let bytes = match self {
@@ -98,6 +249,7 @@ impl ZkSyncTx {
             ZkSyncTx::Close(tx) => tx.get_bytes(),
             ZkSyncTx::ChangePubKey(tx) => tx.get_bytes(),
             ZkSyncTx::ForcedExit(tx) => tx.get_bytes(),
+            ZkSyncTx::MultiTransfer(tx) => tx.get_bytes(),
         };
 
         let hash = sha256(&bytes);
@@ -114,6 +266,7 @@ impl ZkSyncTx {
             ZkSyncTx::Close(tx) => tx.account,
             ZkSyncTx::ChangePubKey(tx) => tx.account,
             ZkSyncTx::ForcedExit(tx) => tx.target,
+            ZkSyncTx::MultiTransfer(tx) => tx.from,
         }
     }
 
@@ -125,9 +278,447 @@ impl ZkSyncTx {
             ZkSyncTx::Close(tx) => tx.nonce,
             ZkSyncTx::ChangePubKey(tx) => tx.nonce,
             ZkSyncTx::ForcedExit(tx) => tx.nonce,
+            ZkSyncTx::MultiTransfer(tx) => tx.nonce,
+        }
+    }
+
+    /// Replaces the transaction nonce. The attached signature becomes invalid,
+    /// so the cached verification result is reset.
+    pub(super) fn set_nonce(&mut self, nonce: Nonce) {
+        match self {
+            ZkSyncTx::Transfer(tx) => {
+                tx.nonce = nonce;
+                tx.cached_signer = VerifiedSignatureCache::NotCached;
+            }
+            ZkSyncTx::Withdraw(tx) => {
+                tx.nonce = nonce;
+                tx.cached_signer = VerifiedSignatureCache::NotCached;
+            }
+            ZkSyncTx::Close(tx) => tx.nonce = nonce,
+            ZkSyncTx::ChangePubKey(tx) => {
+                tx.nonce = nonce;
+                tx.cached_signer = VerifiedSignatureCache::NotCached;
+            }
+            ZkSyncTx::ForcedExit(tx) => {
+                tx.nonce = nonce;
+                tx.cached_signer = VerifiedSignatureCache::NotCached;
+            }
+            ZkSyncTx::MultiTransfer(tx) => {
+                tx.nonce = nonce;
+                tx.cached_signer = VerifiedSignatureCache::NotCached;
+            }
+        }
+    }
+
+    /// Returns a copy of the transaction with the nonce replaced, e.g. to resubmit it
+    /// with a bumped nonce.
+    ///
+    /// The signatures made for the previous nonce are no longer valid, so the zkSync signature
+    /// is replaced with the placeholder `TxSignature::default()`, and the operator signature and
+    /// the Ethereum signature of `ChangePubKey` are dropped. The result must be signed again
+    /// (e.g. via `sign_and_check`) before the submission.
+    pub fn with_nonce(&self, nonce: Nonce) -> ZkSyncTx {
+        let mut tx = self.clone();
+        tx.set_nonce(nonce);
+        tx.set_signature(TxSignature::default());
+        match &mut tx {
+            ZkSyncTx::Transfer(tx) => tx.operator_signature = None,
+            ZkSyncTx::Withdraw(tx) => tx.operator_signature = None,
+            ZkSyncTx::ChangePubKey(tx) => tx.eth_signature = None,
+            _ => {}
+        }
+        tx
+    }
+
+    /// Returns the fee provided in the transaction.
+    ///
+    /// Returns `None` for `Close` transaction which doesn't have a fee.
+    pub fn fee(&self) -> Option<&BigUint> {
+        match self {
+            ZkSyncTx::Transfer(tx) => Some(&tx.fee),
+            ZkSyncTx::Withdraw(tx) => Some(&tx.fee),
+            ZkSyncTx::Close(_) => None,
+            ZkSyncTx::ChangePubKey(tx) => Some(&tx.fee),
+            ZkSyncTx::ForcedExit(tx) => Some(&tx.fee),
+            ZkSyncTx::MultiTransfer(tx) => Some(&tx.fee),
+        }
+    }
+
+    /// Checks that the transaction fee is not below the minimum fee of its type in the `table`.
+    ///
+    /// `Close` transaction has no fee, so it is rejected if the table sets a non-zero
+    /// minimum for it.
+    pub fn check_fee(&self, table: &FeeTable) -> Result<(), Error> {
+        let min_fee = match table.min_fee(self.tx_type()) {
+            Some(min_fee) => min_fee,
+            None => return Ok(()),
+        };
+
+        let fee = self.fee().cloned().unwrap_or_default();
+        if &fee < min_fee {
+            return Err(FeeError::FeeBelowMinimum {
+                fee,
+                min_fee: min_fee.clone(),
+            }
+            .into());
+        }
+        Ok(())
+    }
+
+    /// Returns the zkSync signature of the transaction.
+    pub fn signature(&self) -> &TxSignature {
+        match self {
+            ZkSyncTx::Transfer(tx) => &tx.signature,
+            ZkSyncTx::Withdraw(tx) => &tx.signature,
+            ZkSyncTx::Close(tx) => &tx.signature,
+            ZkSyncTx::ChangePubKey(tx) => &tx.signature,
+            ZkSyncTx::ForcedExit(tx) => &tx.signature,
+            ZkSyncTx::MultiTransfer(tx) => &tx.signature,
+        }
+    }
+
+    /// Returns `true` if the transaction has a zkSync signature attached, i.e. the signature
+    /// is not the `TxSignature::default()` placeholder (as set by `with_nonce`).
+    ///
+    /// Note that the signature itself is not verified, see `verify_signature`.
+    pub fn is_signed(&self) -> bool {
+        !self.signature().ct_eq(&TxSignature::default())
+    }
+
+    /// Replaces the zkSync signature of the transaction. The cached verification result
+    /// belongs to the replaced signature, so it's reset.
+    fn set_signature(&mut self, signature: TxSignature) {
+        match self {
+            ZkSyncTx::Transfer(tx) => {
+                tx.signature = signature;
+                tx.cached_signer = VerifiedSignatureCache::NotCached;
+            }
+            ZkSyncTx::Withdraw(tx) => {
+                tx.signature = signature;
+                tx.cached_signer = VerifiedSignatureCache::NotCached;
+            }
+            ZkSyncTx::Close(tx) => tx.signature = signature,
+            ZkSyncTx::ChangePubKey(tx) => {
+                tx.signature = signature;
+                tx.cached_signer = VerifiedSignatureCache::NotCached;
+            }
+            ZkSyncTx::ForcedExit(tx) => {
+                tx.signature = signature;
+                tx.cached_signer = VerifiedSignatureCache::NotCached;
+            }
+            ZkSyncTx::MultiTransfer(tx) => {
+                tx.signature = signature;
+                tx.cached_signer = VerifiedSignatureCache::NotCached;
+            }
+        }
+    }
+
+    /// Copies the cached signature verification result from the `checked` transaction,
+    /// so that the signature of `self` doesn't have to be verified again.
+    ///
+    /// The result is only copied if both transactions have the same type, signed bytes and
+    /// signature, i.e. the verification would restore the same signer, and the signature of
+    /// `checked` has already been verified. Returns whether the result was copied.
+    ///
+    /// Only the signature check is reused: other checks of `check_correctness` depend on
+    /// the data not covered by the signed bytes (e.g. the exact amounts before packing).
+    pub fn copy_cached_signer(&mut self, checked: &ZkSyncTx) -> bool {
+        if self.tx_type() != checked.tx_type()
+            || self.get_bytes() != checked.get_bytes()
+            || !self.signature().ct_eq(checked.signature())
+        {
+            return false;
+        }
+
+        let cached_signer = match checked.cached_signer() {
+            Some(cached_signer @ VerifiedSignatureCache::Cached(_)) => cached_signer.clone(),
+            _ => return false,
+        };
+        match self {
+            ZkSyncTx::Transfer(tx) => tx.cached_signer = cached_signer,
+            ZkSyncTx::Withdraw(tx) => tx.cached_signer = cached_signer,
+            ZkSyncTx::ChangePubKey(tx) => tx.cached_signer = cached_signer,
+            ZkSyncTx::ForcedExit(tx) => tx.cached_signer = cached_signer,
+            ZkSyncTx::MultiTransfer(tx) => tx.cached_signer = cached_signer,
+            ZkSyncTx::Close(_) => return false,
+        }
+        true
+    }
+
+    /// Returns the cached signature verification result, `Close` doesn't have one.
+    fn cached_signer(&self) -> Option<&VerifiedSignatureCache> {
+        match self {
+            ZkSyncTx::Transfer(tx) => Some(&tx.cached_signer),
+            ZkSyncTx::Withdraw(tx) => Some(&tx.cached_signer),
+            ZkSyncTx::ChangePubKey(tx) => Some(&tx.cached_signer),
+            ZkSyncTx::ForcedExit(tx) => Some(&tx.cached_signer),
+            ZkSyncTx::MultiTransfer(tx) => Some(&tx.cached_signer),
+            ZkSyncTx::Close(_) => None,
+        }
+    }
+
+    /// Restores the `PubKeyHash` from the transaction signature.
+    ///
+    /// Only the default (Rescue) signature scheme is checked.
+    pub fn verify_signature(&self) -> Option<PubKeyHash> {
+        match self {
+            ZkSyncTx::Transfer(tx) => tx.verify_signature(),
+            ZkSyncTx::Withdraw(tx) => tx.verify_signature(),
+            ZkSyncTx::Close(tx) => tx.verify_signature(),
+            ZkSyncTx::ChangePubKey(tx) => tx.verify_signature(),
+            ZkSyncTx::ForcedExit(tx) => tx.verify_signature(),
+            ZkSyncTx::MultiTransfer(tx) => tx.verify_signature(),
+        }
+    }
+
+    /// Verifies the transaction signature if required by the `policy`.
+    ///
+    /// Returns whether the signature is correct, or `None` if the verification was skipped.
+    pub fn verify_with_policy(&self, policy: VerificationPolicy) -> Option<bool> {
+        if policy.should_verify(&self.hash()) {
+            Some(self.verify_signature().is_some())
+        } else {
+            None
         }
     }
 
+    /// Verifies the transaction signature and returns the recovered signer identity.
+    ///
+    /// Same as `verify_signature`, only the default (Rescue) signature scheme is checked.
+    pub fn verify_outcome(&self) -> VerifyOutcome {
+        match self.signature().verify_musig(&self.signing_message()) {
+            Some(pub_key) => VerifyOutcome {
+                valid: true,
+                address: Some(PubKeyHash::from_pubkey(&pub_key)),
+                pub_key: Some(PackedPublicKey(pub_key)),
+            },
+            None => VerifyOutcome {
+                valid: false,
+                pub_key: None,
+                address: None,
+            },
+        }
+    }
+
+    /// Same as `verify_outcome`, but the signer address is resolved via the `cache`,
+    /// which is populated on miss.
+    pub fn verify_and_recover(&self, cache: &AddressCache) -> VerifyOutcome {
+        match self.signature().verify_musig(&self.signing_message()) {
+            Some(pub_key) => {
+                let pub_key = PackedPublicKey(pub_key);
+                VerifyOutcome {
+                    valid: true,
+                    address: Some(cache.resolve(&pub_key)),
+                    pub_key: Some(pub_key),
+                }
+            }
+            None => VerifyOutcome {
+                valid: false,
+                pub_key: None,
+                address: None,
+            },
+        }
+    }
+
+    /// Checks the transaction signature against every supported signature scheme
+    /// and returns the scheme the transaction was signed with.
+    ///
+    /// The scheme embedded into the signature is tried first.
+    ///
+    /// Unlike `verify_signature`, this method doesn't reject transactions signed
+    /// with the SHA-256 musig variant.
+    pub fn check_signature_any(&self) -> Result<SignatureType, SignatureError> {
+        self.check_signature_with_preference(|_| None)
+    }
+
+    /// Same as `check_signature_any`, but the scheme returned by the caller-provided
+    /// `preferred_scheme` hook is tried first. If the hook returns `None`, the scheme
+    /// embedded into the signature is preferred.
+    ///
+    /// In deployments where accounts use different signature schemes this allows to
+    /// avoid wasted verification attempts, e.g. by looking up the scheme used by the
+    /// transaction account or token in a caller-provided map.
+    pub fn check_signature_with_preference<F>(
+        &self,
+        preferred_scheme: F,
+    ) -> Result<SignatureType, SignatureError>
+    where
+        F: FnOnce(&ZkSyncTx) -> Option<SignatureType>,
+    {
+        let bytes = self.signing_message();
+        let preferred = preferred_scheme(self).unwrap_or(self.signature().scheme);
+        let variant = SignatureType::verification_order(Some(preferred))
+            .into_iter()
+            .find(|&variant| {
+                self.signature()
+                    .verify_musig_with_type(&bytes, variant)
+                    .is_some()
+            });
+        match variant {
+            Some(variant) => Ok(variant),
+            None => {
+                // Signature might be correct, but it cannot be checked.
+                self.signature().scheme.ensure_enabled()?;
+                Err(SignatureError::IncorrectSignature)
+            }
+        }
+    }
+
+    /// Checks that the transaction nonce is equal to the `expected` account nonce.
+    pub fn check_nonce(&self, expected: Nonce) -> Result<(), Error> {
+        let actual = self.nonce();
+        if actual != expected {
+            return Err(Error::Nonce { expected, actual });
+        }
+        Ok(())
+    }
+
+    /// Verifies the transaction signature and checks that the transaction is sent from
+    /// one of the `allowed` accounts.
+    ///
+    /// Returns the signer of the transaction on success. The allowlist is checked first,
+    /// so transactions from unknown accounts are rejected without the signature verification.
+    ///
+    /// `ForcedExit` is signed by the initiator, which is only known by its account ID, while
+    /// `account` is the exit target. Such transactions are rejected with `UnknownSender`.
+    pub fn verify_with_allowlist(&self, allowed: &HashSet<Address>) -> Result<PubKeyHash, Error> {
+        let account = match self {
+            ZkSyncTx::ForcedExit(_) => return Err(Error::UnknownSender),
+            _ => self.account(),
+        };
+        if !allowed.contains(&account) {
+            return Err(Error::NotAllowed(account));
+        }
+
+        self.verify_signature()
+            .ok_or_else(|| SignatureError::IncorrectSignature.into())
+    }
+
+    /// Checks whether the transaction conflicts with `other`, i.e. both of them are sent
+    /// from the same account with the same nonce, but carry different data.
+    ///
+    /// Only one of the conflicting transactions can be executed. Identical transactions
+    /// (with the same hash) are not considered conflicting.
+    pub fn conflicts_with(&self, other: &ZkSyncTx) -> bool {
+        self.account() == other.account()
+            && self.nonce() == other.nonce()
+            && self.hash() != other.hash()
+    }
+
+    /// Returns the idempotency key of the transaction, if it was set.
+    ///
+    /// Only `Transfer` and `Withdraw` transactions can carry an idempotency key.
+    pub fn idempotency_key(&self) -> Option<[u8; 16]> {
+        match self {
+            ZkSyncTx::Transfer(tx) => tx.idempotency_key,
+            ZkSyncTx::Withdraw(tx) => tx.idempotency_key,
+            _ => None,
+        }
+    }
+
+    /// Returns the account the transaction fee is paid to, if it differs from the operator.
+    ///
+    /// Only `Transfer` and `Withdraw` transactions can specify a fee recipient. It is not
+    /// supported by the network yet, such transactions are rejected by the API.
+    pub fn fee_recipient(&self) -> Option<Address> {
+        match self {
+            ZkSyncTx::Transfer(tx) => tx.fee_recipient,
+            ZkSyncTx::Withdraw(tx) => tx.fee_recipient,
+            _ => None,
+        }
+    }
+
+    /// Returns the operator signature attached to the transaction, if any.
+    ///
+    /// Only `Transfer` and `Withdraw` transactions can carry an operator signature.
+    pub fn operator_signature(&self) -> Option<&TxSignature> {
+        match self {
+            ZkSyncTx::Transfer(tx) => tx.operator_signature.as_ref(),
+            ZkSyncTx::Withdraw(tx) => tx.operator_signature.as_ref(),
+            _ => None,
+        }
+    }
+
+    /// Returns the client-side label of the transaction, if any.
+    ///
+    /// Only `Transfer` and `Withdraw` transactions can carry a label.
+    pub fn label(&self) -> Option<&str> {
+        match self {
+            ZkSyncTx::Transfer(tx) => tx.label.as_deref(),
+            ZkSyncTx::Withdraw(tx) => tx.label.as_deref(),
+            _ => None,
+        }
+    }
+
+    /// Verifies that the transaction was signed both by its sender and by the operator
+    /// with the `operator_pubkey` key.
+    ///
+    /// Returns the `PubKeyHash` of the sender, or `None` if either signature is incorrect
+    /// or the operator signature is missing. The placeholder `TxSignature::default()` is
+    /// treated as a missing signature.
+    pub fn verify_with_operator(&self, operator_pubkey: &PackedPublicKey) -> Option<PubKeyHash> {
+        let operator_signature = self.operator_signature()?;
+        if operator_signature.ct_eq(&TxSignature::default()) {
+            return None;
+        }
+
+        operator_signature.verify_with_pubkey(&self.signing_message(), operator_pubkey)?;
+        self.verify_signature()
+    }
+
+    /// Checks whether the transaction is a resubmission of one of the already
+    /// seen transactions, i.e. its idempotency key is contained in `seen_keys`.
+    ///
+    /// Transactions without an idempotency key are never considered duplicates.
+    ///
+    /// The key is not signed, so the result is only a hint for the client-side tooling
+    /// (e.g. to avoid re-sending a transaction after a timeout). It must not be used to
+    /// discard transactions on the server side (e.g. in the mempool): a copied key would
+    /// make a genuine transaction look like a duplicate. Use `ZkSyncTx::hash` instead.
+    pub fn is_duplicate(&self, seen_keys: &HashSet<[u8; 16]>) -> bool {
+        self.idempotency_key()
+            .map(|key| seen_keys.contains(&key))
+            .unwrap_or(false)
+    }
+
+    /// Snaps the amounts and fees of the transaction to the closest packable values,
+    /// so the signature is made for exactly the same values the network will process.
+    ///
+    /// Values are rounded down, which changes them by less than one unit in the last
+    /// digit of the packed mantissa. Values which are too big to be packed at all
+    /// are rejected. Since the signed data changes, the transaction must be (re)signed
+    /// after normalization.
+    pub fn normalize(&mut self) -> Result<(), Error> {
+        match self {
+            ZkSyncTx::Transfer(tx) => {
+                normalize_token_amount(&mut tx.amount, "amount")?;
+                normalize_fee(&mut tx.fee)?;
+                tx.cached_signer = VerifiedSignatureCache::NotCached;
+            }
+            ZkSyncTx::Withdraw(tx) => {
+                // Withdrawal amount is not packed, it only must fit into `u128`.
+                check_u128(&tx.amount, "amount")?;
+                normalize_fee(&mut tx.fee)?;
+                tx.cached_signer = VerifiedSignatureCache::NotCached;
+            }
+            ZkSyncTx::Close(_) => {}
+            ZkSyncTx::ChangePubKey(tx) => {
+                normalize_fee(&mut tx.fee)?;
+                tx.cached_signer = VerifiedSignatureCache::NotCached;
+            }
+            ZkSyncTx::ForcedExit(tx) => {
+                normalize_fee(&mut tx.fee)?;
+                tx.cached_signer = VerifiedSignatureCache::NotCached;
+            }
+            ZkSyncTx::MultiTransfer(tx) => {
+                // Output amounts are packable by construction.
+                normalize_fee(&mut tx.fee)?;
+                tx.cached_signer = VerifiedSignatureCache::NotCached;
+            }
+        }
+        Ok(())
+    }
+
     /// Checks whether transaction is well-formed and can be executed.
     ///
     /// Note that this method doesn't check whether transaction will succeed, so transaction
@@ -139,6 +730,56 @@ impl ZkSyncTx {
             ZkSyncTx::Close(tx) => tx.check_correctness(),
             ZkSyncTx::ChangePubKey(tx) => tx.check_correctness(),
             ZkSyncTx::ForcedExit(tx) => tx.check_correctness(),
+            ZkSyncTx::MultiTransfer(tx) => tx.check_correctness(),
+        }
+    }
+
+    /// Signs the transaction with the provided private key and verifies the attached
+    /// signature as a self-check.
+    ///
+    /// Returns the signed transaction and `true` if the signature is correct and was made
+    /// by the `private_key`. Any previously attached signature is replaced.
+    pub fn sign_and_check(mut self, private_key: &PrivateKey<Engine>) -> (Self, bool) {
+        let signature = TxSignature::sign_musig(private_key, &self.signing_message());
+        self.set_signature(signature);
+
+        let valid = self.verify_signature() == Some(PubKeyHash::from_privkey(private_key));
+        (self, valid)
+    }
+
+    /// Prepares the transaction for submission in one call: normalizes the amounts and fees
+    /// (see `normalize`), sets the data derived from the `private_key` and signs the result.
+    ///
+    /// For `ChangePubKey` the new public key hash is set to the one of the `private_key`.
+    /// If it changes, the Ethereum signature made for the previous key hash is dropped,
+    /// so it has to be signed again.
+    ///
+    /// Returns an error if the transaction cannot be normalized or the signature made
+    /// for it doesn't verify.
+    pub fn normalize_and_sign(mut self, private_key: &PrivateKey<Engine>) -> Result<Self, Error> {
+        self.normalize()?;
+        if let ZkSyncTx::ChangePubKey(tx) = &mut self {
+            let pub_key_hash = PubKeyHash::from_privkey(private_key);
+            if tx.new_pk_hash != pub_key_hash {
+                tx.new_pk_hash = pub_key_hash;
+                tx.eth_signature = None;
+            }
+        }
+
+        match self.sign_and_check(private_key) {
+            (tx, true) => Ok(tx),
+            (_, false) => Err(SignatureError::IncorrectSignature.into()),
+        }
+    }
+
+    /// Returns the message signed by the zkSync signature of the transaction.
+    ///
+    /// It is the `get_bytes` encoding for all the transactions except `MultiTransfer`,
+    /// which signs the hash of its bytes (see `MultiTransfer::signing_message`).
+    pub fn signing_message(&self) -> Vec<u8> {
+        match self {
+            ZkSyncTx::MultiTransfer(tx) => tx.signing_message(),
+            _ => self.get_bytes(),
         }
     }
 
@@ -150,9 +791,95 @@ impl ZkSyncTx {
             ZkSyncTx::Close(tx) => tx.get_bytes(),
             ZkSyncTx::ChangePubKey(tx) => tx.get_bytes(),
             ZkSyncTx::ForcedExit(tx) => tx.get_bytes(),
+            ZkSyncTx::MultiTransfer(tx) => tx.get_bytes(),
         }
     }
 
+    /// Deserializes the transaction from YAML, e.g. from the test fixtures.
+    ///
+    /// The layout is the same as for JSON, including the `type` tag.
+    pub fn from_yaml(yaml: &str) -> Result<Self, serde_yaml::Error> {
+        serde_yaml::from_str(yaml)
+    }
+
+    /// Serializes the transaction into YAML, see `ZkSyncTx::from_yaml`.
+    pub fn to_yaml(&self) -> Result<String, serde_yaml::Error> {
+        serde_yaml::to_string(self)
+    }
+
+    /// Describes the transaction for the external signer (e.g. a hardware wallet):
+    /// the bytes to be signed along with their field by field breakdown.
+    ///
+    /// Returns an error if the bytes cannot be explained, e.g. for a `MultiTransfer`
+    /// with too many outputs to be encoded.
+    pub fn to_signing_request(&self) -> Result<SigningRequest, WireError> {
+        let bytes = self.get_bytes();
+        let explanation = explain_bytes(&bytes)?;
+
+        Ok(SigningRequest {
+            tx_type: explanation.tx_type,
+            tx_hash: self.hash(),
+            fields: explanation.fields,
+            bytes,
+        })
+    }
+
+    /// Returns the names of the transaction fields covered by the zkSync signature,
+    /// in the order they are encoded by `get_bytes`.
+    ///
+    /// Transaction type byte always precedes the fields and is not listed.
+    pub fn signed_field_names(&self) -> Vec<&'static str> {
+        match self {
+            ZkSyncTx::Transfer(_) | ZkSyncTx::Withdraw(_) => vec![
+                "account_id",
+                "from",
+                "to",
+                "token",
+                "amount",
+                "fee",
+                "nonce",
+            ],
+            ZkSyncTx::Close(_) => vec!["account", "nonce"],
+            ZkSyncTx::ChangePubKey(_) => vec![
+                "account_id",
+                "account",
+                "new_pk_hash",
+                "fee_token",
+                "fee",
+                "nonce",
+            ],
+            ZkSyncTx::ForcedExit(_) => {
+                vec!["initiator_account_id", "target", "token", "fee", "nonce"]
+            }
+            ZkSyncTx::MultiTransfer(_) => {
+                vec!["account_id", "from", "outputs", "fee_token", "fee", "nonce"]
+            }
+        }
+    }
+
+    /// Returns the size of the serialized transaction in bytes: the length of the
+    /// `get_bytes` encoding followed by the packed zkSync signature.
+    ///
+    /// The size is calculated from the fixed transaction layout, so no encoding is performed.
+    pub fn serialized_size(&self) -> usize {
+        /// Packed public key (32 bytes) followed by the packed signature (64 bytes).
+        const SIGNATURE_BYTES: usize = 32 + 64;
+
+        let tx_bits = match self {
+            ZkSyncTx::Transfer(_) => SIGNED_TRANSFER_BIT_WIDTH,
+            ZkSyncTx::Withdraw(_) => SIGNED_WITHDRAW_BIT_WIDTH,
+            ZkSyncTx::Close(_) => TX_TYPE_BIT_WIDTH + ETH_ADDRESS_BIT_WIDTH + NONCE_BIT_WIDTH,
+            ZkSyncTx::ChangePubKey(_) => SIGNED_CHANGE_PUBKEY_BIT_WIDTH,
+            ZkSyncTx::ForcedExit(_) => SIGNED_FORCED_EXIT_BIT_WIDTH,
+            ZkSyncTx::MultiTransfer(tx) => {
+                // Header with the outputs count, the outputs list and the fee part.
+                (1 + 4 + 20 + 1 + tx.outputs.len() * TransferOutput::BYTES + 2 + 2 + 4) * 8
+            }
+        };
+
+        tx_bits / 8 + SIGNATURE_BYTES
+    }
+
     /// Returns the minimum amount of block chunks required for this operation.
     /// Maximum amount of chunks in block is a part of  the server and provers configuration,
     /// and this value determines the block capacity.
@@ -163,6 +890,7 @@ impl ZkSyncTx {
             ZkSyncTx::Close(_) => CloseOp::CHUNKS,
             ZkSyncTx::ChangePubKey(_) => ChangePubKeyOp::CHUNKS,
             ZkSyncTx::ForcedExit(_) => ForcedExitOp::CHUNKS,
+            ZkSyncTx::MultiTransfer(tx) => TransferOp::CHUNKS * tx.outputs.len(),
         }
     }
 
@@ -171,12 +899,32 @@ impl ZkSyncTx {
         matches!(self, ZkSyncTx::Withdraw(_) | ZkSyncTx::ForcedExit(_))
     }
 
+    /// Returns the kind of the account receiving the transaction funds.
+    ///
+    /// `Withdraw` and `ForcedExit` send funds to the Ethereum address, so their target
+    /// is L1-bound. Other transactions either move funds within zkSync or only affect
+    /// the L2 state of the account itself.
+    pub fn target_account_type(&self) -> AccountType {
+        match self {
+            ZkSyncTx::Withdraw(_) | ZkSyncTx::ForcedExit(_) => AccountType::L1,
+            ZkSyncTx::Transfer(_)
+            | ZkSyncTx::MultiTransfer(_)
+            | ZkSyncTx::ChangePubKey(_)
+            | ZkSyncTx::Close(_) => AccountType::L2,
+        }
+    }
+
     /// Returns `true` if transaction is `ZkSyncTx::Withdraw`.
     #[doc(hidden)]
     pub fn is_close(&self) -> bool {
         matches!(self, ZkSyncTx::Close(_))
     }
 
+    /// Returns `true` if transaction is `ZkSyncTx::MultiTransfer`.
+    pub fn is_multi_transfer(&self) -> bool {
+        matches!(self, ZkSyncTx::MultiTransfer(_))
+    }
+
     /// Returns the data required to calculate fee for the transaction.
     ///
     /// Response includes the following items:
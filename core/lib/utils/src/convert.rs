@@ -3,7 +3,7 @@ use num::{
     bigint::ToBigInt,
     rational::Ratio,
     traits::{sign::Signed, Pow},
-    BigUint,
+    BigInt, BigUint, Integer, Zero,
 };
 
 pub fn ratio_to_big_decimal(num: &Ratio<BigUint>, precision: usize) -> BigDecimal {
@@ -21,6 +21,26 @@ pub fn big_decimal_to_ratio(num: &BigDecimal) -> Result<Ratio<BigUint>, anyhow::
     Ok(Ratio::new(big_uint, ten_pow))
 }
 
+/// Strips the trailing zeros of the fractional part, so that e.g. `1.500` and `1.5`
+/// have the same scale. Integer part is left intact: `1500` keeps the zero scale.
+pub fn normalize_big_decimal(num: &BigDecimal) -> BigDecimal {
+    let (mut digits, mut scale) = num.as_bigint_and_exponent();
+    if digits.is_zero() {
+        return BigDecimal::new(digits, 0);
+    }
+
+    let ten = BigInt::from(10u32);
+    while scale > 0 {
+        let (quotient, remainder) = digits.div_rem(&ten);
+        if !remainder.is_zero() {
+            break;
+        }
+        digits = quotient;
+        scale -= 1;
+    }
+    BigDecimal::new(digits, scale)
+}
+
 fn round_precision_raw_no_div(num: &Ratio<BigUint>, precision: usize) -> BigUint {
     let ten_pow = BigUint::from(10u32).pow(precision);
     (num * ten_pow).round().to_integer()
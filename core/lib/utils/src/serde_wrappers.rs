@@ -24,11 +24,11 @@ impl UnsignedRatioSerializeAsDecimal {
         // hexadecimal representation of `Fr`.
         let big_decimal_string = BigDecimal::deserialize(deserializer)?;
 
-        big_decimal_to_ratio(&big_decimal_string).map_err(de::Error::custom)
+        big_decimal_to_ratio(&normalize_big_decimal(&big_decimal_string)).map_err(de::Error::custom)
     }
 
     pub fn deserialize_from_str_with_dot(input: &str) -> Result<Ratio<BigUint>, anyhow::Error> {
-        big_decimal_to_ratio(&BigDecimal::from_str(input)?)
+        big_decimal_to_ratio(&normalize_big_decimal(&BigDecimal::from_str(input)?))
     }
 
     pub fn serialize_to_str_with_dot(num: &Ratio<BigUint>, precision: usize) -> String {
@@ -58,7 +58,14 @@ impl BigUintSerdeAsRadix10Str {
     {
         use serde::de::Error;
         BigDecimal::deserialize(deserializer).and_then(|bigdecimal| {
-            let big_int = bigdecimal
+            // Amounts with trailing zeros in the fractional part (e.g. `1000.00`)
+            // are treated the same way as their minimal representation, while
+            // the non-zero fractional part (e.g. `1.5`) is an error.
+            let normalized = normalize_big_decimal(&bigdecimal);
+            if normalized.as_bigint_and_exponent().1 > 0 {
+                return Err(Error::custom("Expected integer value"));
+            }
+            let big_int = normalized
                 .to_bigint()
                 .ok_or_else(|| Error::custom("Expected integer value"))?;
             big_int
@@ -76,3 +83,56 @@ impl From<BigUint> for BigUintSerdeWrapper {
         BigUintSerdeWrapper(uint)
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[derive(Debug, Serialize, Deserialize)]
+    struct Price(#[serde(with = "UnsignedRatioSerializeAsDecimal")] Ratio<BigUint>);
+
+    #[test]
+    fn test_normalize_big_decimal() {
+        let vals = vec![
+            ("1.500", "1.5"),
+            ("1.5", "1.5"),
+            ("1.000", "1"),
+            ("1500", "1500"),
+            ("0.000", "0"),
+            ("0.010", "0.01"),
+        ];
+
+        for (input, expected) in vals {
+            let normalized = normalize_big_decimal(&BigDecimal::from_str(input).unwrap());
+            assert_eq!(normalized.to_string(), expected, "input: {}", input);
+        }
+    }
+
+    #[test]
+    fn test_non_minimal_scale_deserialization() {
+        let serialize_price = |input: &str| {
+            let price: Price = serde_json::from_str(&format!("\"{}\"", input)).unwrap();
+            serde_json::to_vec(&price).unwrap()
+        };
+        assert_eq!(serialize_price("1.500"), serialize_price("1.5"));
+
+        let serialize_amount = |input: &str| {
+            let amount: BigUintSerdeWrapper =
+                serde_json::from_str(&format!("\"{}\"", input)).unwrap();
+            serde_json::to_vec(&amount).unwrap()
+        };
+        assert_eq!(serialize_amount("1500.000"), serialize_amount("1500"));
+        assert_eq!(serialize_amount("1500"), b"\"1500\"".to_vec());
+    }
+
+    #[test]
+    fn test_fractional_amount_deserialization() {
+        let deserialize_amount =
+            |input: &str| serde_json::from_str::<BigUintSerdeWrapper>(&format!("\"{}\"", input));
+
+        assert_eq!(deserialize_amount("1000.00").unwrap().0, 1000u32.into());
+        for input in &["1.5", "1000.001", "0.1", "-1"] {
+            assert!(deserialize_amount(input).is_err(), "input: {}", input);
+        }
+    }
+}